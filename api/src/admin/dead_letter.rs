@@ -0,0 +1,111 @@
+use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
+use hub_core::uuid::Uuid;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+
+use super::MAX_LIMIT;
+use crate::{db::Connection, entities::dead_letters, events::Processor};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query;
+
+#[Object(name = "DeadLetterQuery")]
+impl Query {
+    /// Query messages that failed permanently, most recent first.
+    /// Operators use this to inspect messages before replaying them.
+    ///
+    /// # Errors
+    /// This function fails if the database connection cannot be accessed or fails to load from the database.
+    async fn dead_letters(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Filter on whether the message has already been replayed.")]
+        replayed: Option<bool>,
+        #[graphql(default = 50)] limit: u64,
+        #[graphql(default = 0)] offset: u64,
+    ) -> Result<Vec<dead_letters::Model>> {
+        let db = ctx.data::<Connection>()?;
+
+        let mut query = dead_letters::Entity::find();
+
+        if let Some(replayed) = replayed {
+            query = query.filter(if replayed {
+                dead_letters::Column::ReplayedAt.is_not_null()
+            } else {
+                dead_letters::Column::ReplayedAt.is_null()
+            });
+        }
+
+        let letters = query
+            .order_by_desc(dead_letters::Column::CreatedAt)
+            .limit(limit.min(MAX_LIMIT))
+            .offset(offset)
+            .all(db.get())
+            .await?;
+
+        Ok(letters)
+    }
+
+    /// Query a message that failed permanently by its ID.
+    ///
+    /// # Errors
+    /// This function fails if the database connection cannot be accessed or fails to load from the database.
+    async fn dead_letter(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> Result<Option<dead_letters::Model>> {
+        let db = ctx.data::<Connection>()?;
+
+        let letter = dead_letters::Entity::find_by_id(id).one(db.get()).await?;
+
+        Ok(letter)
+    }
+}
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "DeadLetterMutation")]
+impl Mutation {
+    /// Process a message that failed permanently again, once the cause is fixed.
+    /// A dead letter is replayed at most once. A message that fails permanently again is recorded as a new dead letter.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the dead letter does not exist or was already replayed, it is unable to interact with the database or the message fails with an error that is not permanent.
+    pub async fn replay_dead_letter(
+        &self,
+        ctx: &Context<'_>,
+        input: ReplayDeadLetterInput,
+    ) -> Result<ReplayDeadLetterPayload> {
+        let db = ctx.data::<Connection>()?;
+        let processor = ctx.data::<Processor>()?;
+
+        let letter = dead_letters::Entity::find_by_id(input.id)
+            .one(db.get())
+            .await?
+            .ok_or(Error::new("dead letter not found"))?;
+
+        if letter.replayed_at.is_some() {
+            return Err(Error::new("dead letter was already replayed"));
+        }
+
+        let dead_letter = processor
+            .replay_dead_letter(letter.id)
+            .await?
+            .ok_or(Error::new("dead letter was already replayed"))?;
+
+        Ok(ReplayDeadLetterPayload { dead_letter })
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct ReplayDeadLetterInput {
+    /// The ID of the dead letter to replay.
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ReplayDeadLetterPayload {
+    /// The replayed dead letter.
+    pub dead_letter: dead_letters::Model,
+}
@@ -0,0 +1,72 @@
+//! The operator-only GraphQL API. It is served on its own port, which is not exposed through the
+//! gateway, since its operations are not scoped to an organization.
+
+mod dead_letter;
+mod reconciliation;
+
+use async_graphql::{extensions::Logger, EmptySubscription, Schema};
+use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
+use poem::{handler, web::Data};
+
+use crate::{db::Connection, events::Processor, reconciliation::Reconciler};
+
+/// The most records an admin query returns at once.
+const MAX_LIMIT: u64 = 100;
+
+pub type AdminSchema = Schema<Query, Mutation, EmptySubscription>;
+
+#[derive(async_graphql::MergedObject, Default)]
+pub struct Query(dead_letter::Query, reconciliation::Query);
+
+#[derive(async_graphql::MergedObject, Default)]
+pub struct Mutation(dead_letter::Mutation, reconciliation::Mutation);
+
+/// Builds the schema of the admin API.
+#[must_use]
+pub fn build_schema() -> AdminSchema {
+    Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+        .extension(Logger)
+        .finish()
+}
+
+#[derive(Clone)]
+pub struct AdminState {
+    pub schema: AdminSchema,
+    pub connection: Connection,
+    pub processor: Processor,
+    pub reconciler: Reconciler,
+}
+
+impl AdminState {
+    #[must_use]
+    pub fn new(
+        schema: AdminSchema,
+        connection: Connection,
+        processor: Processor,
+        reconciler: Reconciler,
+    ) -> Self {
+        Self {
+            schema,
+            connection,
+            processor,
+            reconciler,
+        }
+    }
+}
+
+#[handler]
+pub async fn graphql_handler(
+    Data(state): Data<&AdminState>,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
+    state
+        .schema
+        .execute(
+            req.0
+                .data(state.connection.clone())
+                .data(state.processor.clone())
+                .data(state.reconciler.clone()),
+        )
+        .await
+        .into()
+}
@@ -0,0 +1,64 @@
+use async_graphql::{Context, InputObject, Object, Result, SimpleObject};
+
+use crate::reconciliation::{Reconciler, ReconciliationReport};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query;
+
+#[Object(name = "ReconciliationQuery")]
+impl Query {
+    /// Query the report of the latest reconciliation of treasuries against the vaults of the custodian.
+    /// Reports are kept in memory, so none is returned until a reconciliation ran since the service started.
+    ///
+    /// # Errors
+    /// This function fails if the reconciler cannot be accessed.
+    async fn treasury_reconciliation(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<ReconciliationReport>> {
+        let reconciler = ctx.data::<Reconciler>()?;
+
+        Ok(reconciler.latest())
+    }
+}
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "ReconciliationMutation")]
+impl Mutation {
+    /// Start a reconciliation of treasuries and wallets against the vaults of the custodian in the background.
+    /// No reconciliation is started while one is running. Query `treasuryReconciliation` for its report once it finishes.
+    ///
+    /// # Errors
+    /// This function fails if the reconciler cannot be accessed.
+    pub async fn reconcile_treasuries(
+        &self,
+        ctx: &Context<'_>,
+        input: ReconcileTreasuriesInput,
+    ) -> Result<ReconcileTreasuriesPayload> {
+        let reconciler = ctx.data::<Reconciler>()?;
+
+        let started = reconciler.trigger(input.repair);
+
+        Ok(ReconcileTreasuriesPayload {
+            started,
+            report: reconciler.latest(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, InputObject)]
+pub struct ReconcileTreasuriesInput {
+    /// Record the project treasuries and wallets found in the custodian but missing from the database.
+    #[graphql(default)]
+    pub repair: bool,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ReconcileTreasuriesPayload {
+    /// Whether a reconciliation was started. It is not when one is already running.
+    pub started: bool,
+    /// The report of the latest finished reconciliation, if any.
+    pub report: Option<ReconciliationReport>,
+}
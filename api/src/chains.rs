@@ -0,0 +1,353 @@
+//! Registry of the chains treasuries hold wallets on.
+//!
+//! Every chain is described once here: its Fireblocks asset ids, the blockchains it is reported as
+//! in events and charged as by the credits service, and the topic the events of its NFT service are
+//! consumed from. Its value in the database is the `num_value` of its [`AssetType`] variant.
+//! Adding a chain takes a new [`AssetType`] variant and an entry in the registry behind [`CHAINS`].
+//!
+//! Fungible tokens, such as USDC on Solana, are listed in [`TOKENS`] along with the chain they
+//! are issued on. Their wallets share the address of the vault's wallet on that chain.
+
+use fireblocks::assets::{
+    ARBITRUM, ARBITRUM_ETH, ARBITRUM_TEST, BASE, BASE_ETH, BASE_TEST, ETH, ETH_TEST, MATIC,
+    MATIC_POLYGON, MATIC_TEST, OPTIMISM, OPTIMISM_ETH, OPTIMISM_TEST, SOL, SOL_TEST, SOL_USDC,
+    SOL_USDC_TEST, USDC_POLYGON, USDC_POLYGON_TEST, USDC_SOL,
+};
+use hub_core::credits;
+
+use crate::{
+    entities::wallets::AssetType,
+    proto::{treasury_events, Blockchain},
+};
+
+/// A chain treasuries hold wallets on.
+#[derive(Debug)]
+pub struct Chain {
+    /// The asset type of the chain's wallets.
+    pub asset_type: AssetType,
+    /// The name of the chain, e.g. in metrics.
+    pub name: &'static str,
+    /// The network agnostic symbol of the chain's native asset, e.g. `MATIC`.
+    pub symbol: &'static str,
+    /// The Fireblocks asset id of the native asset on mainnet.
+    pub asset_id: &'static str,
+    /// The Fireblocks asset id of the native asset on testnet.
+    pub test_asset_id: &'static str,
+    /// The blockchain the chain is reported as in project wallet events.
+    pub blockchain: Blockchain,
+    /// The blockchain the chain is reported as in other treasury events.
+    pub event_blockchain: treasury_events::Blockchain,
+    /// The blockchain the credits service charges the chain's actions as.
+    pub credits_blockchain: credits::Blockchain,
+    /// The number of decimals amounts of the chain's native asset are given in.
+    pub decimals: u32,
+    /// Whether the chain uses EVM addresses.
+    pub evm: bool,
+    /// The topic the events of the chain's NFT service are consumed from.
+    pub topic: &'static str,
+    /// Whether the chain's NFT service sends events in the schema shared by EVM chains. Polygon
+    /// and Ethereum have schemas of their own.
+    pub shared_evm_schema: bool,
+}
+
+impl Chain {
+    /// The Fireblocks asset id of the chain's native asset.
+    #[must_use]
+    pub fn fireblocks_asset_id(&self, test_mode: bool) -> &'static str {
+        if test_mode {
+            self.test_asset_id
+        } else {
+            self.asset_id
+        }
+    }
+}
+
+/// Every supported chain.
+pub static CHAINS: [Chain; 6] = REGISTRY;
+
+/// The topics events are consumed from: those of the organization and customer services and the
+/// NFT service of every chain.
+pub const TOPICS: [&str; 2 + REGISTRY.len()] = topics();
+
+const fn topics() -> [&'static str; 2 + REGISTRY.len()] {
+    let mut topics = [""; 2 + REGISTRY.len()];
+    topics[0] = "hub-orgs";
+    topics[1] = "hub-customers";
+
+    let mut i = 0;
+    while i < REGISTRY.len() {
+        topics[i + 2] = REGISTRY[i].topic;
+        i += 1;
+    }
+
+    topics
+}
+
+// The registry is a constant so that the topics can be derived from it at compile time. It is used
+// through the `CHAINS` static, which chains are referenced in.
+// The credits service has no prices for the Ethereum L2s, so their actions are charged as Ethereum.
+const REGISTRY: [Chain; 6] = [
+    Chain {
+        asset_type: AssetType::Solana,
+        name: "Solana",
+        symbol: SOL,
+        asset_id: SOL,
+        test_asset_id: SOL_TEST,
+        blockchain: Blockchain::Solana,
+        event_blockchain: treasury_events::Blockchain::Solana,
+        credits_blockchain: credits::Blockchain::Solana,
+        decimals: 9,
+        evm: false,
+        topic: "hub-nfts-solana",
+        shared_evm_schema: false,
+    },
+    Chain {
+        asset_type: AssetType::Matic,
+        name: "Polygon",
+        symbol: MATIC,
+        asset_id: MATIC_POLYGON,
+        test_asset_id: MATIC_TEST,
+        blockchain: Blockchain::Polygon,
+        event_blockchain: treasury_events::Blockchain::Polygon,
+        credits_blockchain: credits::Blockchain::Polygon,
+        decimals: 18,
+        evm: true,
+        topic: "hub-nfts-polygon",
+        shared_evm_schema: false,
+    },
+    Chain {
+        asset_type: AssetType::Eth,
+        name: "Ethereum",
+        symbol: ETH,
+        asset_id: ETH,
+        test_asset_id: ETH_TEST,
+        blockchain: Blockchain::Ethereum,
+        event_blockchain: treasury_events::Blockchain::Ethereum,
+        credits_blockchain: credits::Blockchain::Ethereum,
+        decimals: 18,
+        evm: true,
+        topic: "hub-nfts-ethereum",
+        shared_evm_schema: false,
+    },
+    Chain {
+        asset_type: AssetType::Base,
+        name: "Base",
+        symbol: BASE,
+        asset_id: BASE_ETH,
+        test_asset_id: BASE_TEST,
+        blockchain: Blockchain::Base,
+        event_blockchain: treasury_events::Blockchain::Base,
+        credits_blockchain: credits::Blockchain::Ethereum,
+        decimals: 18,
+        evm: true,
+        topic: "hub-nfts-base",
+        shared_evm_schema: true,
+    },
+    Chain {
+        asset_type: AssetType::Arbitrum,
+        name: "Arbitrum",
+        symbol: ARBITRUM,
+        asset_id: ARBITRUM_ETH,
+        test_asset_id: ARBITRUM_TEST,
+        blockchain: Blockchain::Arbitrum,
+        event_blockchain: treasury_events::Blockchain::Arbitrum,
+        credits_blockchain: credits::Blockchain::Ethereum,
+        decimals: 18,
+        evm: true,
+        topic: "hub-nfts-arbitrum",
+        shared_evm_schema: true,
+    },
+    Chain {
+        asset_type: AssetType::Optimism,
+        name: "Optimism",
+        symbol: OPTIMISM,
+        asset_id: OPTIMISM_ETH,
+        test_asset_id: OPTIMISM_TEST,
+        blockchain: Blockchain::Optimism,
+        event_blockchain: treasury_events::Blockchain::Optimism,
+        credits_blockchain: credits::Blockchain::Ethereum,
+        decimals: 18,
+        evm: true,
+        topic: "hub-nfts-optimism",
+        shared_evm_schema: true,
+    },
+];
+
+/// A fungible token treasuries hold wallets of, such as USDC on Solana.
+#[derive(Debug)]
+pub struct Token {
+    /// The asset type of the token's wallets.
+    pub asset_type: AssetType,
+    /// The network agnostic symbol of the token, e.g. `USDC_SOL`.
+    pub symbol: &'static str,
+    /// The Fireblocks asset id of the token on mainnet.
+    pub asset_id: &'static str,
+    /// The Fireblocks asset id of the token on testnet.
+    pub test_asset_id: &'static str,
+    /// The asset type of the chain the token is issued on.
+    pub chain: AssetType,
+}
+
+impl Token {
+    /// The Fireblocks asset id of the token.
+    #[must_use]
+    pub fn fireblocks_asset_id(&self, test_mode: bool) -> &'static str {
+        if test_mode {
+            self.test_asset_id
+        } else {
+            self.asset_id
+        }
+    }
+}
+
+/// Every supported token.
+pub static TOKENS: [Token; 2] = [
+    Token {
+        asset_type: AssetType::UsdcSol,
+        symbol: USDC_SOL,
+        asset_id: SOL_USDC,
+        test_asset_id: SOL_USDC_TEST,
+        chain: AssetType::Solana,
+    },
+    Token {
+        asset_type: AssetType::UsdcPolygon,
+        symbol: USDC_POLYGON,
+        asset_id: USDC_POLYGON,
+        test_asset_id: USDC_POLYGON_TEST,
+        chain: AssetType::Matic,
+    },
+];
+
+/// Looks up the chain of an asset type. The chain of a token is the chain it is issued on.
+///
+/// # Panics
+/// Panics if the asset type has no entry in [`CHAINS`] or [`TOKENS`], which is a bug.
+#[must_use]
+pub fn chain(asset_type: AssetType) -> &'static Chain {
+    let asset_type = token(asset_type).map_or(asset_type, |token| token.chain);
+
+    CHAINS
+        .iter()
+        .find(|chain| chain.asset_type == asset_type)
+        .unwrap_or_else(|| panic!("{asset_type:?} is missing from the chain registry"))
+}
+
+/// Looks up a chain by the symbol of its native asset, e.g. `MATIC`.
+#[must_use]
+pub fn by_symbol(symbol: &str) -> Option<&'static Chain> {
+    CHAINS.iter().find(|chain| chain.symbol == symbol)
+}
+
+/// Looks up a chain by the Fireblocks asset id of its native asset on mainnet or testnet.
+#[must_use]
+pub fn by_asset_id(asset_id: &str) -> Option<&'static Chain> {
+    CHAINS
+        .iter()
+        .find(|chain| chain.asset_id == asset_id || chain.test_asset_id == asset_id)
+}
+
+/// Looks up a chain by the blockchain it is reported as in project wallet events.
+#[must_use]
+pub fn by_blockchain(blockchain: Blockchain) -> Option<&'static Chain> {
+    CHAINS.iter().find(|chain| chain.blockchain == blockchain)
+}
+
+/// Looks up the chain whose NFT service events in the shared EVM schema are consumed from `topic`.
+#[must_use]
+pub fn by_evm_topic(topic: &str) -> Option<&'static Chain> {
+    CHAINS
+        .iter()
+        .find(|chain| chain.shared_evm_schema && chain.topic == topic)
+}
+
+/// Looks up the token of an asset type, if it is one.
+#[must_use]
+pub fn token(asset_type: AssetType) -> Option<&'static Token> {
+    TOKENS.iter().find(|token| token.asset_type == asset_type)
+}
+
+/// Resolves the symbol of a native asset or token, e.g. `MATIC` or `USDC_SOL`, to its Fireblocks
+/// asset id.
+#[must_use]
+pub fn fireblocks_asset_id(symbol: &str, test_mode: bool) -> Option<&'static str> {
+    by_symbol(symbol)
+        .map(|chain| chain.fireblocks_asset_id(test_mode))
+        .or_else(|| {
+            TOKENS
+                .iter()
+                .find(|token| token.symbol == symbol)
+                .map(|token| token.fireblocks_asset_id(test_mode))
+        })
+}
+
+/// Looks up the asset type of a native asset or token by its Fireblocks asset id on mainnet or
+/// testnet.
+#[must_use]
+pub fn asset_type(asset_id: &str) -> Option<AssetType> {
+    by_asset_id(asset_id)
+        .map(|chain| chain.asset_type)
+        .or_else(|| {
+            TOKENS
+                .iter()
+                .find(|token| token.asset_id == asset_id || token.test_asset_id == asset_id)
+                .map(|token| token.asset_type)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fireblocks_asset_id_resolves_chains_and_tokens() {
+        assert_eq!(fireblocks_asset_id(MATIC, false), Some(MATIC_POLYGON));
+        assert_eq!(fireblocks_asset_id(MATIC, true), Some(MATIC_TEST));
+        assert_eq!(fireblocks_asset_id(BASE, false), Some(BASE_ETH));
+        assert_eq!(fireblocks_asset_id(USDC_SOL, false), Some(SOL_USDC));
+        assert_eq!(fireblocks_asset_id(USDC_SOL, true), Some(SOL_USDC_TEST));
+        assert_eq!(fireblocks_asset_id(MATIC_POLYGON, false), None);
+    }
+
+    #[test]
+    fn asset_type_resolves_mainnet_and_testnet_ids() {
+        assert_eq!(asset_type(SOL), Some(AssetType::Solana));
+        assert_eq!(asset_type(SOL_TEST), Some(AssetType::Solana));
+        assert_eq!(asset_type(ARBITRUM_TEST), Some(AssetType::Arbitrum));
+        assert_eq!(asset_type(USDC_POLYGON_TEST), Some(AssetType::UsdcPolygon));
+        assert_eq!(asset_type(SOL_USDC), Some(AssetType::UsdcSol));
+        assert_eq!(asset_type("BTC"), None);
+    }
+
+    #[test]
+    fn asset_type_inverts_fireblocks_asset_id() {
+        for test_mode in [false, true] {
+            for chain in &CHAINS {
+                let asset_id = fireblocks_asset_id(chain.symbol, test_mode).unwrap();
+
+                assert_eq!(asset_type(asset_id), Some(chain.asset_type));
+            }
+
+            for token in &TOKENS {
+                let asset_id = fireblocks_asset_id(token.symbol, test_mode).unwrap();
+
+                assert_eq!(asset_type(asset_id), Some(token.asset_type));
+            }
+        }
+    }
+
+    #[test]
+    fn tokens_are_issued_on_registered_chains() {
+        for token in &TOKENS {
+            assert_eq!(chain(token.asset_type).asset_type, token.chain);
+        }
+    }
+
+    #[test]
+    fn topics_include_every_chain() {
+        assert_eq!(&TOPICS[..2], ["hub-orgs", "hub-customers"]);
+
+        for chain in &CHAINS {
+            assert!(TOPICS.contains(&chain.topic));
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use fireblocks::{
+    objects::{
+        transaction::{
+            CreateTransactionResponse, DestinationTransferPeerPath, FeeLevel as FireblocksFeeLevel,
+            OneTimeAddress, SignedMessageResponse, StatusClass as FireblocksStatusClass,
+            TransactionDetails, TransactionStatus as FireblocksTransactionStatus,
+        },
+        vault::{
+            CreateVault, CreateVaultWallet, QueryVaultAccounts, VaultAccount,
+            VaultAccountsPagedResponse, VaultAsset,
+        },
+    },
+    Error as FireblocksError, Fireblocks, TransactionOutcome as FireblocksOutcome,
+};
+use hub_core::{anyhow::Result, prelude::*};
+
+use super::{
+    AssetBalance, Custody, Destination, FeeLevel, NewVault, Signature, StatusClass,
+    SubmittedTransaction, Transaction, TransactionOutcome, TransactionStatus, Vault, VaultPage,
+    VaultQuery, WaitPolicy,
+};
+use crate::chains;
+
+impl From<VaultAsset> for AssetBalance {
+    fn from(asset: VaultAsset) -> Self {
+        Self {
+            id: asset.id,
+            total: asset.total,
+            available: asset.available,
+            pending: asset.pending,
+            frozen: asset.frozen,
+            locked: asset.locked_amount,
+        }
+    }
+}
+
+impl From<VaultAccount> for Vault {
+    fn from(account: VaultAccount) -> Self {
+        Self {
+            id: account.id,
+            name: account.name,
+            customer_ref_id: account.customer_ref_id,
+            assets: account.assets.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<VaultAccountsPagedResponse> for VaultPage {
+    fn from(page: VaultAccountsPagedResponse) -> Self {
+        Self {
+            vaults: page.accounts.into_iter().map(Into::into).collect(),
+            after: page.paging.and_then(|paging| paging.after),
+        }
+    }
+}
+
+impl From<SignedMessageResponse> for Signature {
+    fn from(message: SignedMessageResponse) -> Self {
+        let signature = message.signature;
+
+        Self {
+            content: message.content,
+            full_sig: signature.full_sig,
+            r: signature.r,
+            s: signature.s,
+            v: signature.v,
+        }
+    }
+}
+
+impl From<TransactionDetails> for Transaction {
+    fn from(details: TransactionDetails) -> Self {
+        Self {
+            id: details.id,
+            asset_id: details.asset_id,
+            tx_hash: details.tx_hash,
+            status: details.status.into(),
+            sub_status: details.sub_status,
+            signatures: details
+                .signed_messages
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<CreateTransactionResponse> for SubmittedTransaction {
+    fn from(response: CreateTransactionResponse) -> Self {
+        Self {
+            id: response.id,
+            status: response.status.into(),
+        }
+    }
+}
+
+impl From<FireblocksStatusClass> for StatusClass {
+    fn from(class: FireblocksStatusClass) -> Self {
+        match class {
+            FireblocksStatusClass::Pending => Self::Pending,
+            FireblocksStatusClass::Success => Self::Success,
+            FireblocksStatusClass::Failure => Self::Failure,
+        }
+    }
+}
+
+impl From<FireblocksOutcome> for TransactionOutcome {
+    fn from(outcome: FireblocksOutcome) -> Self {
+        match outcome {
+            FireblocksOutcome::Completed(details) => Self::Completed(details.into()),
+            FireblocksOutcome::Failed(details) => Self::Failed(details.into()),
+            FireblocksOutcome::TimedOut { details, cancelled } => Self::TimedOut {
+                transaction: details.into(),
+                cancelled,
+            },
+        }
+    }
+}
+
+impl From<Destination> for DestinationTransferPeerPath {
+    fn from(destination: Destination) -> Self {
+        match destination {
+            Destination::Vault(id) => Self {
+                peer_type: "VAULT_ACCOUNT".to_string(),
+                id: Some(id),
+                one_time_address: None,
+            },
+            Destination::Address(address) => Self {
+                peer_type: "ONE_TIME_ADDRESS".to_string(),
+                id: None,
+                one_time_address: Some(OneTimeAddress { address, tag: None }),
+            },
+        }
+    }
+}
+
+impl From<FireblocksTransactionStatus> for TransactionStatus {
+    fn from(status: FireblocksTransactionStatus) -> Self {
+        match status {
+            FireblocksTransactionStatus::UNSPECIFIED => Self::Unspecified,
+            FireblocksTransactionStatus::SUBMITTED => Self::Submitted,
+            FireblocksTransactionStatus::QUEUED => Self::Queued,
+            FireblocksTransactionStatus::PENDING_AUTHORIZATION => Self::PendingAuthorization,
+            FireblocksTransactionStatus::PENDING_SIGNATURE => Self::PendingSignature,
+            FireblocksTransactionStatus::BROADCASTING => Self::Broadcasting,
+            FireblocksTransactionStatus::PENDING_3RD_PARTY_MANUAL_APPROVAL => {
+                Self::Pending3rdPartyManualApproval
+            },
+            FireblocksTransactionStatus::PENDING_3RD_PARTY => Self::Pending3rdParty,
+            FireblocksTransactionStatus::CONFIRMING => Self::Confirming,
+            FireblocksTransactionStatus::PARTIALLY_COMPLETED => Self::PartiallyCompleted,
+            FireblocksTransactionStatus::PENDING_AML_SCREENING => Self::PendingAmlScreening,
+            FireblocksTransactionStatus::COMPLETED => Self::Completed,
+            FireblocksTransactionStatus::CANCELLED => Self::Cancelled,
+            FireblocksTransactionStatus::REJECTED => Self::Rejected,
+            FireblocksTransactionStatus::BLOCKED => Self::Blocked,
+            FireblocksTransactionStatus::FAILED => Self::Failed,
+            FireblocksTransactionStatus::PENDING => Self::Pending,
+            FireblocksTransactionStatus::UNKNOWN => Self::Unknown,
+        }
+    }
+}
+
+impl From<TransactionStatus> for FireblocksTransactionStatus {
+    fn from(status: TransactionStatus) -> Self {
+        match status {
+            TransactionStatus::Unspecified => Self::UNSPECIFIED,
+            TransactionStatus::Submitted => Self::SUBMITTED,
+            TransactionStatus::Queued => Self::QUEUED,
+            TransactionStatus::PendingAuthorization => Self::PENDING_AUTHORIZATION,
+            TransactionStatus::PendingSignature => Self::PENDING_SIGNATURE,
+            TransactionStatus::Broadcasting => Self::BROADCASTING,
+            TransactionStatus::Pending3rdPartyManualApproval => {
+                Self::PENDING_3RD_PARTY_MANUAL_APPROVAL
+            },
+            TransactionStatus::Pending3rdParty => Self::PENDING_3RD_PARTY,
+            TransactionStatus::Confirming => Self::CONFIRMING,
+            TransactionStatus::PartiallyCompleted => Self::PARTIALLY_COMPLETED,
+            TransactionStatus::PendingAmlScreening => Self::PENDING_AML_SCREENING,
+            TransactionStatus::Completed => Self::COMPLETED,
+            TransactionStatus::Cancelled => Self::CANCELLED,
+            TransactionStatus::Rejected => Self::REJECTED,
+            TransactionStatus::Blocked => Self::BLOCKED,
+            TransactionStatus::Failed => Self::FAILED,
+            TransactionStatus::Pending => Self::PENDING,
+            TransactionStatus::Unknown => Self::UNKNOWN,
+        }
+    }
+}
+
+impl From<FeeLevel> for FireblocksFeeLevel {
+    fn from(value: FeeLevel) -> Self {
+        match value {
+            FeeLevel::Low => Self::LOW,
+            FeeLevel::Medium => Self::MEDIUM,
+            FeeLevel::High => Self::HIGH,
+        }
+    }
+}
+
+/// Fireblocks rejects transactions reusing an `externalTxId`.
+/// When a submission is rejected for reusing its id, or fails in a way that may have created the
+/// transaction regardless, the transaction created with the same id is returned instead. Other
+/// errors, and lookups failing for any reason but the transaction not existing, are returned.
+async fn find_submitted(
+    fireblocks: &Fireblocks,
+    created: Result<CreateTransactionResponse>,
+    external_tx_id: String,
+) -> Result<SubmittedTransaction> {
+    let e = match created {
+        Ok(created) => return Ok(created.into()),
+        Err(e) => e,
+    };
+
+    if let Some(rejection) = e.downcast_ref::<FireblocksError>() {
+        if !rejection.is_external_tx_id_taken() && !rejection.is_server_error() {
+            return Err(e);
+        }
+    }
+
+    match fireblocks
+        .client()
+        .read()
+        .transaction_by_external_id(external_tx_id.clone())
+        .await
+    {
+        Ok(details) => Ok(SubmittedTransaction {
+            id: details.id,
+            status: details.status.into(),
+        }),
+        Err(lookup)
+            if lookup
+                .downcast_ref::<FireblocksError>()
+                .map_or(false, FireblocksError::is_not_found) =>
+        {
+            Err(e)
+        },
+        Err(lookup) => Err(lookup.context(format!(
+            "failed to look up transaction {external_tx_id} after its submission failed: {e}"
+        ))),
+    }
+}
+
+#[async_trait]
+impl Custody for Fireblocks {
+    fn asset_id(&self, asset: &str) -> String {
+        chains::fireblocks_asset_id(asset, self.assets().test_mode())
+            .map_or_else(|| self.assets().id(asset), ToString::to_string)
+    }
+
+    fn asset_ids(&self) -> Vec<String> {
+        self.assets()
+            .symbols()
+            .iter()
+            .map(|symbol| self.asset_id(symbol))
+            .collect()
+    }
+
+    fn treasury_vault(&self) -> String {
+        Fireblocks::treasury_vault(self)
+    }
+
+    fn wait_policy(&self) -> WaitPolicy {
+        let policy = self.client().wait_policy();
+
+        WaitPolicy::new(policy.deadline, policy.cancel_on_timeout, |status| {
+            policy.classify(status.into()).into()
+        })
+    }
+
+    async fn create_vault(&self, vault: NewVault) -> Result<Vault> {
+        let NewVault {
+            name,
+            customer_ref_id,
+            auto_fuel,
+        } = vault;
+
+        let account = self
+            .client()
+            .create()
+            .vault(CreateVault {
+                name,
+                hidden_on_ui: None,
+                customer_ref_id,
+                auto_fuel: Some(auto_fuel),
+            })
+            .await?;
+
+        Ok(account.into())
+    }
+
+    async fn vault(&self, vault_id: String) -> Result<Vault> {
+        let account = self.client().read().vault(vault_id).await?;
+
+        Ok(account.into())
+    }
+
+    async fn vaults(&self, query: VaultQuery) -> Result<VaultPage> {
+        let VaultQuery {
+            name_prefix,
+            after,
+            limit,
+        } = query;
+
+        let page = self
+            .client()
+            .read()
+            .vaults(QueryVaultAccounts {
+                name_prefix,
+                after,
+                limit,
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(page.into())
+    }
+
+    async fn deposit_addresses(&self, vault_id: String, asset_id: String) -> Result<Vec<String>> {
+        let addresses = self.client().read().addresses(vault_id, asset_id).await?;
+
+        Ok(addresses.into_iter().map(|a| a.address).collect())
+    }
+
+    async fn create_wallet(&self, vault_id: String, asset_id: String) -> Result<String> {
+        let wallet = self
+            .client()
+            .create()
+            .wallet(vault_id, asset_id, CreateVaultWallet {
+                eos_account_name: None,
+            })
+            .await?;
+
+        Ok(wallet.address)
+    }
+
+    async fn sign_raw(
+        &self,
+        asset_id: String,
+        vault_id: String,
+        messages: Vec<Vec<u8>>,
+        note: String,
+        external_tx_id: String,
+    ) -> Result<SubmittedTransaction> {
+        let created = self
+            .client()
+            .create()
+            .raw_transaction(
+                asset_id,
+                vault_id,
+                messages,
+                note,
+                Some(external_tx_id.clone()),
+            )
+            .await;
+
+        find_submitted(self, created, external_tx_id).await
+    }
+
+    async fn contract_call(
+        &self,
+        data: Vec<u8>,
+        asset_id: String,
+        vault_id: String,
+        note: String,
+        external_tx_id: String,
+        contract_wallet_id: Option<String>,
+        fee_level: FeeLevel,
+    ) -> Result<SubmittedTransaction> {
+        let created = self
+            .client()
+            .create()
+            .contract_call(
+                data,
+                asset_id,
+                vault_id,
+                note,
+                Some(external_tx_id.clone()),
+                contract_wallet_id,
+                fee_level.into(),
+            )
+            .await;
+
+        find_submitted(self, created, external_tx_id).await
+    }
+
+    async fn transfer(
+        &self,
+        asset_id: String,
+        vault_id: String,
+        destination: Destination,
+        amount: String,
+        note: String,
+        external_tx_id: String,
+    ) -> Result<SubmittedTransaction> {
+        let created = self
+            .client()
+            .create()
+            .transfer(
+                asset_id,
+                vault_id,
+                destination.into(),
+                amount,
+                note,
+                Some(external_tx_id.clone()),
+            )
+            .await;
+
+        find_submitted(self, created, external_tx_id).await
+    }
+
+    async fn transaction(&self, id: String) -> Result<Transaction> {
+        let details = self.client().read().transaction(id).await?;
+
+        Ok(details.into())
+    }
+
+    async fn cancel_transaction(&self, id: String) -> Result<bool> {
+        let response = self.client().create().cancel(id).await?;
+
+        Ok(response.success)
+    }
+
+    async fn wait_on_transaction(&self, id: String) -> Result<TransactionOutcome> {
+        let outcome = self.client().wait_on_transaction_completion(id).await?;
+
+        Ok(outcome.into())
+    }
+}
@@ -0,0 +1,155 @@
+//! Custody backends holding treasury keys and signing on their behalf.
+
+mod fireblocks;
+mod types;
+
+use std::sync::Arc;
+
+use hub_core::{anyhow::Result, prelude::*};
+pub use types::{
+    AssetBalance, Destination, FeeLevel, NewVault, Signature, StatusClass, SubmittedTransaction,
+    Transaction, TransactionOutcome, TransactionStatus, Vault, VaultPage, VaultQuery, WaitPolicy,
+};
+
+/// Shared handle to the custody backend used by the event processor and the GraphQL API.
+pub type CustodyClient = Arc<dyn Custody>;
+
+/// A custodian of treasury vaults.
+///
+/// Implementations hold the keys for project and customer treasuries and sign or submit
+/// transactions for them. Fireblocks is the default implementation. The trait only exchanges the
+/// types of this module, so implementations convert them from and to the types of their custodian.
+#[async_trait]
+pub trait Custody: Send + Sync {
+    /// Resolves a network agnostic asset symbol (e.g. `SOL`, `MATIC`) to the asset id of the custodian.
+    fn asset_id(&self, asset: &str) -> String;
+
+    /// The custodian asset ids a wallet is created for on every new project treasury.
+    fn asset_ids(&self) -> Vec<String>;
+
+    /// The vault funding the fees of contract calls submitted on behalf of projects.
+    fn treasury_vault(&self) -> String;
+
+    /// Decides which transaction statuses are pending and how long a pending transaction is waited on.
+    fn wait_policy(&self) -> WaitPolicy;
+
+    /// Creates a new vault.
+    ///
+    /// # Errors
+    /// Returns an error if the custodian fails to create the vault.
+    async fn create_vault(&self, vault: NewVault) -> Result<Vault>;
+
+    /// Fetches a vault along with the balances of its wallets.
+    ///
+    /// # Errors
+    /// Returns an error if the vault could not be retrieved.
+    async fn vault(&self, vault_id: String) -> Result<Vault>;
+
+    /// Lists a page of vaults matching the filters along with the cursor of the next page.
+    ///
+    /// # Errors
+    /// Returns an error if the vaults could not be retrieved.
+    async fn vaults(&self, query: VaultQuery) -> Result<VaultPage>;
+
+    /// Fetches the deposit addresses of the wallet for `asset_id` within the vault.
+    ///
+    /// # Errors
+    /// Returns an error if the addresses could not be retrieved.
+    async fn deposit_addresses(&self, vault_id: String, asset_id: String) -> Result<Vec<String>>;
+
+    /// Derives a wallet for `asset_id` within the vault and returns its address.
+    ///
+    /// # Errors
+    /// Returns an error if the custodian fails to create the wallet.
+    async fn create_wallet(&self, vault_id: String, asset_id: String) -> Result<String>;
+
+    /// Returns the address of the vault's wallet for `asset_id`, creating the wallet unless the
+    /// vault already holds the asset.
+    ///
+    /// # Errors
+    /// Returns an error if the addresses could not be retrieved or the wallet could not be created.
+    async fn find_or_create_wallet(&self, vault: &Vault, asset_id: String) -> Result<String> {
+        if vault.assets.iter().any(|asset| asset.id == asset_id) {
+            let addresses = self
+                .deposit_addresses(vault.id.clone(), asset_id.clone())
+                .await?;
+
+            if let Some(address) = addresses.into_iter().next() {
+                return Ok(address);
+            }
+        }
+
+        self.create_wallet(vault.id.clone(), asset_id).await
+    }
+
+    /// Submits raw messages to be signed by the key of the vault for `asset_id`.
+    ///
+    /// Submitting again with the same `external_tx_id` returns the transaction created by the
+    /// first submission instead of signing twice.
+    ///
+    /// # Errors
+    /// Returns an error if the transaction could not be submitted.
+    async fn sign_raw(
+        &self,
+        asset_id: String,
+        vault_id: String,
+        messages: Vec<Vec<u8>>,
+        note: String,
+        external_tx_id: String,
+    ) -> Result<SubmittedTransaction>;
+
+    /// Submits a contract call paid for by the vault. The call goes to `contract_wallet_id` when
+    /// set and the configured whitelisted contract wallet otherwise.
+    ///
+    /// Submitting again with the same `external_tx_id` returns the transaction created by the
+    /// first submission instead of calling the contract twice.
+    ///
+    /// # Errors
+    /// Returns an error if the transaction could not be submitted.
+    async fn contract_call(
+        &self,
+        data: Vec<u8>,
+        asset_id: String,
+        vault_id: String,
+        note: String,
+        external_tx_id: String,
+        contract_wallet_id: Option<String>,
+        fee_level: FeeLevel,
+    ) -> Result<SubmittedTransaction>;
+
+    /// Submits a transfer of `amount` of `asset_id` from the vault to `destination`.
+    ///
+    /// Submitting again with the same `external_tx_id` returns the transaction created by the
+    /// first submission instead of transferring twice.
+    ///
+    /// # Errors
+    /// Returns an error if the transaction could not be submitted.
+    async fn transfer(
+        &self,
+        asset_id: String,
+        vault_id: String,
+        destination: Destination,
+        amount: String,
+        note: String,
+        external_tx_id: String,
+    ) -> Result<SubmittedTransaction>;
+
+    /// Fetches the current state of a submitted transaction.
+    ///
+    /// # Errors
+    /// Returns an error if the transaction could not be retrieved.
+    async fn transaction(&self, id: String) -> Result<Transaction>;
+
+    /// Cancels a transaction that has not been signed yet. Returns whether it was cancelled.
+    ///
+    /// # Errors
+    /// Returns an error if the custodian could not be reached.
+    async fn cancel_transaction(&self, id: String) -> Result<bool>;
+
+    /// Waits until a submitted transaction leaves the pending state or the deadline of the
+    /// [`WaitPolicy`] passes.
+    ///
+    /// # Errors
+    /// Returns an error if the state of the transaction could not be retrieved.
+    async fn wait_on_transaction(&self, id: String) -> Result<TransactionOutcome>;
+}
@@ -0,0 +1,213 @@
+//! Requests and responses exchanged with a [`Custody`](super::Custody) backend. Backends convert
+//! them from and to the wire types of their custodian.
+
+use std::{collections::HashMap, time::Duration};
+
+use sea_orm::{EnumIter, Iterable};
+
+/// A vault to create.
+#[derive(Debug, Clone)]
+pub struct NewVault {
+    /// The name of the vault, e.g. `project:<id>`.
+    pub name: String,
+    /// A reference to the owner of the vault kept by the custodian.
+    pub customer_ref_id: Option<String>,
+    /// Whether the custodian funds the network fees of the vault's transactions itself.
+    pub auto_fuel: bool,
+}
+
+/// A vault held by the custodian.
+#[derive(Debug, Clone)]
+pub struct Vault {
+    /// The ID of the vault in the custodian.
+    pub id: String,
+    /// The name of the vault.
+    pub name: String,
+    /// The reference to the owner of the vault, if one was set when it was created.
+    pub customer_ref_id: Option<String>,
+    /// The balances of the wallets of the vault.
+    pub assets: Vec<AssetBalance>,
+}
+
+/// The balance of a wallet of a vault.
+/// Amounts are decimal strings denominated in the asset, e.g. `1.5` SOL.
+#[derive(Debug, Clone)]
+pub struct AssetBalance {
+    /// The custodian asset ID of the wallet.
+    pub id: String,
+    /// The total amount held.
+    pub total: String,
+    /// The amount that can be spent.
+    pub available: String,
+    /// The amount of incoming transactions that are not confirmed yet.
+    pub pending: String,
+    /// The amount frozen by AML policies.
+    pub frozen: String,
+    /// The amount locked by pending outgoing transactions.
+    pub locked: String,
+}
+
+/// Filters a listing of vaults.
+#[derive(Debug, Clone, Default)]
+pub struct VaultQuery {
+    /// Only lists vaults whose name starts with the prefix.
+    pub name_prefix: Option<String>,
+    /// The cursor of the page to list, as returned with the previous page.
+    pub after: Option<String>,
+    /// The most vaults to list at once.
+    pub limit: Option<u64>,
+}
+
+/// A page of vaults.
+#[derive(Debug, Clone)]
+pub struct VaultPage {
+    /// The vaults of the page.
+    pub vaults: Vec<Vault>,
+    /// The cursor of the next page, unless this is the last one.
+    pub after: Option<String>,
+}
+
+/// The receiver of a transfer.
+#[derive(Debug, Clone)]
+pub enum Destination {
+    /// Another vault of the custodian, by ID.
+    Vault(String),
+    /// An address outside the custodian.
+    Address(String),
+}
+
+/// The status of a transaction in the custodian. The values match the transaction statuses
+/// reported in treasury events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum TransactionStatus {
+    Unspecified = 0,
+    Submitted = 1,
+    Queued = 2,
+    PendingAuthorization = 3,
+    PendingSignature = 4,
+    Broadcasting = 5,
+    Pending3rdPartyManualApproval = 6,
+    Pending3rdParty = 7,
+    Confirming = 8,
+    PartiallyCompleted = 9,
+    PendingAmlScreening = 10,
+    Completed = 11,
+    Cancelled = 12,
+    Rejected = 13,
+    Blocked = 14,
+    Failed = 15,
+    Pending = 16,
+    /// A status the custodian reported that the service does not know.
+    Unknown = 17,
+}
+
+/// How much a transaction pays in network fees to be included sooner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// A transaction accepted by the custodian.
+#[derive(Debug, Clone)]
+pub struct SubmittedTransaction {
+    /// The ID of the transaction in the custodian.
+    pub id: String,
+    /// The status of the transaction when it was accepted.
+    pub status: TransactionStatus,
+}
+
+/// The current state of a transaction.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    /// The ID of the transaction in the custodian.
+    pub id: String,
+    /// The custodian asset ID the transaction was submitted for.
+    pub asset_id: String,
+    /// The hash of the transaction on chain, empty until it is broadcast.
+    pub tx_hash: String,
+    /// The status of the transaction.
+    pub status: TransactionStatus,
+    /// The reason given by the custodian for the status, empty if none was given.
+    pub sub_status: String,
+    /// The signatures of the raw messages signed by the transaction, in the order they were submitted.
+    pub signatures: Vec<Signature>,
+}
+
+/// A signature of a raw message.
+#[derive(Debug, Clone)]
+pub struct Signature {
+    /// The hex encoded message that was signed.
+    pub content: String,
+    /// The hex encoded signature.
+    pub full_sig: String,
+    /// The hex encoded `r` of an ECDSA signature.
+    pub r: Option<String>,
+    /// The hex encoded `s` of an ECDSA signature.
+    pub s: Option<String>,
+    /// The recovery ID of an ECDSA signature.
+    pub v: Option<u64>,
+}
+
+/// How a transaction status is treated while waiting on a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusClass {
+    /// The transaction may still change state.
+    Pending,
+    /// The transaction completed.
+    Success,
+    /// The transaction will never complete.
+    Failure,
+}
+
+/// Controls how long a transaction is waited on and which statuses end the wait.
+#[derive(Debug, Clone)]
+pub struct WaitPolicy {
+    /// How long to wait on a pending transaction before giving up.
+    pub deadline: Duration,
+    /// Whether to cancel the transaction once the deadline passes.
+    pub cancel_on_timeout: bool,
+    classes: HashMap<TransactionStatus, StatusClass>,
+}
+
+impl WaitPolicy {
+    /// Builds a policy treating every status as `classify` classes it.
+    #[must_use]
+    pub fn new(
+        deadline: Duration,
+        cancel_on_timeout: bool,
+        classify: impl Fn(TransactionStatus) -> StatusClass,
+    ) -> Self {
+        Self {
+            deadline,
+            cancel_on_timeout,
+            classes: TransactionStatus::iter()
+                .map(|status| (status, classify(status)))
+                .collect(),
+        }
+    }
+
+    /// The class of `status` under this policy.
+    #[must_use]
+    pub fn classify(&self, status: TransactionStatus) -> StatusClass {
+        self.classes
+            .get(&status)
+            .copied()
+            .unwrap_or(StatusClass::Pending)
+    }
+}
+
+/// The state a transaction was left in once waiting on it ended.
+#[derive(Debug, Clone)]
+pub enum TransactionOutcome {
+    /// The transaction completed.
+    Completed(Transaction),
+    /// The transaction reached a status classified as a failure.
+    Failed(Transaction),
+    /// The transaction was still pending when the deadline passed.
+    TimedOut {
+        transaction: Transaction,
+        cancelled: bool,
+    },
+}
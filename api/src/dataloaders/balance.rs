@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use async_graphql::{dataloader::Loader as DataLoader, FieldError, Result};
+use hub_core::futures_util::{stream, StreamExt};
+use poem::async_trait;
+
+use crate::custody::{AssetBalance, CustodyClient};
+
+/// Vaults fetched from the custodian at once per batch.
+const BALANCE_CONCURRENCY: usize = 8;
+
+/// Vault balances fetched from the custodian, shared across requests for a short time so dashboards
+/// polling many treasuries do not hit the custodian on every request.
+#[derive(Debug, Clone)]
+pub struct VaultBalanceCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (Instant, Vec<AssetBalance>)>>>,
+}
+
+impl VaultBalanceCache {
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    fn get(&self, vault_id: &str) -> Option<Vec<AssetBalance>> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        entries
+            .get(vault_id)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.ttl)
+            .map(|(_, assets)| assets.clone())
+    }
+
+    fn insert(&self, vault_id: String, assets: Vec<AssetBalance>) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        entries.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.ttl);
+        entries.insert(vault_id, (Instant::now(), assets));
+    }
+}
+
+/// A struct that implements a `DataLoader` for loading the asset balances of vaults by their vault ID.
+/// Each vault is loaded on its own, so a vault that cannot be fetched only fails the fields reading it.
+#[derive(Clone)]
+pub struct VaultBalancesLoader {
+    pub custody: CustodyClient,
+    pub cache: VaultBalanceCache,
+}
+
+impl VaultBalancesLoader {
+    #[must_use]
+    pub fn new(custody: CustodyClient, cache: VaultBalanceCache) -> Self {
+        Self { custody, cache }
+    }
+}
+
+#[async_trait]
+impl DataLoader<String> for VaultBalancesLoader {
+    type Error = FieldError;
+    type Value = Result<Vec<AssetBalance>, FieldError>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let vaults = stream::iter(keys)
+            .map(|vault_id| async move {
+                if let Some(assets) = self.cache.get(vault_id) {
+                    return (vault_id.clone(), Ok(assets));
+                }
+
+                let assets = self.custody.vault(vault_id.clone()).await.map(|vault| {
+                    self.cache.insert(vault_id.clone(), vault.assets.clone());
+
+                    vault.assets
+                });
+
+                (vault_id.clone(), assets.map_err(Into::into))
+            })
+            .buffer_unordered(BALANCE_CONCURRENCY)
+            .collect::<HashMap<_, _>>()
+            .await;
+
+        Ok(vaults)
+    }
+}
@@ -33,7 +33,8 @@ impl DataLoader<Uuid> for WalletAddressesLoader {
             .filter(
                 customer_treasuries::Column::CustomerId
                     .is_in(keys.iter().map(ToOwned::to_owned))
-                    .and(wallets::Column::Address.is_not_null()),
+                    .and(wallets::Column::Address.is_not_null())
+                    .and(wallets::Column::RemovedAt.is_null()),
             )
             .join(
                 JoinType::InnerJoin,
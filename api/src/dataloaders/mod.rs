@@ -1,8 +1,12 @@
+mod balance;
 mod customer;
+mod transaction;
 mod treasury;
 mod wallet;
 
+pub use balance::{VaultBalanceCache, VaultBalancesLoader};
 pub use customer::WalletAddressesLoader as CustomerWalletAddressesLoader;
+pub use transaction::HistoryLoader as TransactionHistoryLoader;
 pub use treasury::{
     CustomerLoader as CustomerTreasuryLoader, Loader as TreasuryLoader,
     ProjectLoader as ProjectTreasuryLoader,
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use async_graphql::{dataloader::Loader as DataLoader, FieldError, Result};
+use poem::async_trait;
+use sea_orm::{prelude::*, QueryOrder};
+
+use crate::{db::Connection, entities::transaction_status_changes};
+
+/// Loads the statuses a transaction went through, oldest first, by its Fireblocks ID.
+#[derive(Debug, Clone)]
+pub struct HistoryLoader {
+    pub db: Connection,
+}
+
+impl HistoryLoader {
+    #[must_use]
+    pub fn new(db: Connection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl DataLoader<Uuid> for HistoryLoader {
+    type Error = FieldError;
+    type Value = Vec<transaction_status_changes::Model>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
+        let changes = transaction_status_changes::Entity::find()
+            .filter(
+                transaction_status_changes::Column::FireblocksId
+                    .is_in(keys.iter().map(ToOwned::to_owned)),
+            )
+            .order_by_asc(transaction_status_changes::Column::CreatedAt)
+            .all(self.db.get())
+            .await?;
+
+        Ok(changes.into_iter().fold(HashMap::new(), |mut acc, change| {
+            acc.entry(change.fireblocks_id)
+                .or_insert_with(Vec::new)
+                .push(change);
+
+            acc
+        }))
+    }
+}
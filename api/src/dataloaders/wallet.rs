@@ -33,15 +33,36 @@ impl DataLoader<String> for WalletLoader {
             .all(self.db.get())
             .await?;
 
-        Ok(wallets
-            .into_iter()
-            .map(|i| {
-                let address = i.address.clone().ok_or_else(|| {
-                    Self::Error::new(format!("Address is missing for wallet with ID {}", i.id))
-                })?;
-                Ok((address, i))
-            })
-            .collect::<Result<_>>()?)
+        let mut by_address = HashMap::new();
+
+        // An address is shared by an archived wallet and the wallet created again in its place.
+        // The active wallet wins, then the most recently archived one.
+        for wallet in wallets {
+            let address = wallet.address.clone().ok_or_else(|| {
+                Self::Error::new(format!(
+                    "Address is missing for wallet with ID {}",
+                    wallet.id
+                ))
+            })?;
+
+            match by_address.get(&address) {
+                Some(current) if preferred(current, &wallet) => {},
+                _ => {
+                    by_address.insert(address, wallet);
+                },
+            }
+        }
+
+        Ok(by_address)
+    }
+}
+
+/// Whether `a` is shown rather than `b` when both wallets have the same address.
+fn preferred(a: &wallets::Model, b: &wallets::Model) -> bool {
+    match (a.removed_at, b.removed_at) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(a), Some(b)) => a >= b,
     }
 }
 
@@ -0,0 +1,183 @@
+//! Dead-letter queue for messages that can never be processed.
+
+use hub_core::{
+    anyhow::{Context, Result},
+    chrono::Utc,
+    prelude::*,
+    producer::{self, Producer},
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, sea_query::Expr, Set};
+
+use crate::{db::Connection, entities::dead_letters};
+
+/// The topic dead letters are published to. It is kept apart from the topic of treasury events so
+/// their consumers never receive dead letters.
+pub const DEAD_LETTER_TOPIC: &str = "hub-treasuries-dlq";
+
+/// The producer configuration of the dead-letter topic, derived from that of treasury events.
+#[must_use]
+pub fn producer_config(events: &producer::Config) -> producer::Config {
+    let mut config = events.clone();
+    config.topic = DEAD_LETTER_TOPIC.to_string();
+
+    config
+}
+
+/// The key dead letters are published with.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeadLetterKey {
+    /// The ID of the dead letter.
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+/// A message that failed permanently, as published to the dead-letter topic.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeadLetter {
+    /// The topic the message was consumed from.
+    #[prost(string, tag = "1")]
+    pub topic: String,
+    /// The original message key.
+    #[prost(bytes = "vec", tag = "2")]
+    pub message_key: Vec<u8>,
+    /// The original message payload.
+    #[prost(bytes = "vec", tag = "3")]
+    pub message_payload: Vec<u8>,
+    #[prost(string, tag = "4")]
+    pub error: String,
+    #[prost(string, tag = "5")]
+    pub error_kind: String,
+}
+
+impl hub_core::producer::Message for DeadLetter {
+    type Key = DeadLetterKey;
+}
+
+impl From<&dead_letters::Model> for DeadLetter {
+    fn from(letter: &dead_letters::Model) -> Self {
+        Self {
+            topic: letter.topic.clone(),
+            message_key: letter.message_key.clone(),
+            message_payload: letter.message_payload.clone(),
+            error: letter.error.clone(),
+            error_kind: letter.error_kind.clone(),
+        }
+    }
+}
+
+/// Records messages that fail permanently and publishes them to the dead-letter topic so they can be
+/// inspected and replayed.
+#[derive(Clone)]
+pub struct DeadLetterQueue {
+    db: Connection,
+    producer: Producer<DeadLetter>,
+}
+
+impl DeadLetterQueue {
+    /// Publishes dead letters with a producer built from [`producer_config`].
+    #[must_use]
+    pub fn new(db: Connection, producer: Producer<DeadLetter>) -> Self {
+        Self { db, producer }
+    }
+
+    /// Stores a message that failed permanently and publishes it to the dead-letter topic.
+    ///
+    /// # Errors
+    /// Returns an error if the message cannot be stored or published.
+    pub async fn publish(
+        &self,
+        topic: &str,
+        message_key: Vec<u8>,
+        message_payload: Vec<u8>,
+        error: String,
+        error_kind: String,
+    ) -> Result<dead_letters::Model> {
+        let letter = dead_letters::ActiveModel {
+            topic: Set(topic.to_string()),
+            message_key: Set(message_key),
+            message_payload: Set(message_payload),
+            error: Set(error),
+            error_kind: Set(error_kind),
+            ..Default::default()
+        }
+        .insert(self.db.get())
+        .await?;
+
+        error!(
+            "{} message failed permanently, published as dead letter {}",
+            letter.topic, letter.id
+        );
+
+        let key = DeadLetterKey {
+            id: letter.id.to_string(),
+        };
+
+        self.producer
+            .send(Some(&DeadLetter::from(&letter)), Some(&key))
+            .await
+            .with_context(|| format!("failed to publish dead letter {}", letter.id))?;
+
+        Ok(letter)
+    }
+
+    /// Claims a dead letter for a replay by marking it replayed, so that it is replayed at most once.
+    /// Returns `None` if the dead letter does not exist or was already replayed.
+    ///
+    /// # Errors
+    /// Returns an error if the dead letter cannot be updated.
+    pub async fn claim_replay(&self, id: Uuid) -> Result<Option<dead_letters::Model>> {
+        let conn = self.db.get();
+
+        let claimed = dead_letters::Entity::update_many()
+            .col_expr(
+                dead_letters::Column::ReplayedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(dead_letters::Column::Id.eq(id))
+            .filter(dead_letters::Column::ReplayedAt.is_null())
+            .exec(conn)
+            .await?;
+
+        if claimed.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        let letter = dead_letters::Entity::find_by_id(id).one(conn).await?;
+
+        if let Some(letter) = &letter {
+            info!("dead letter {} replayed from {}", letter.id, letter.topic);
+        }
+
+        Ok(letter)
+    }
+
+    /// Releases the claim of a replay that failed, so the dead letter can be replayed again.
+    ///
+    /// # Errors
+    /// Returns an error if the dead letter cannot be updated.
+    pub async fn release_replay(&self, id: Uuid) -> Result<()> {
+        dead_letters::Entity::update_many()
+            .col_expr(
+                dead_letters::Column::ReplayedAt,
+                Expr::value(Option::<DateTimeWithTimeZone>::None),
+            )
+            .filter(dead_letters::Column::Id.eq(id))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chains::TOPICS, SERVICE_NAME};
+
+    #[test]
+    fn dead_letters_have_their_own_topic() {
+        assert_ne!(DEAD_LETTER_TOPIC, SERVICE_NAME);
+        assert!(!TOPICS.contains(&DEAD_LETTER_TOPIC));
+    }
+}
@@ -2,9 +2,20 @@
 
 pub mod prelude;
 
+pub mod auto_provisioned_assets;
 pub mod customer_treasuries;
+pub mod dead_letters;
+pub mod gas_station_fuelings;
+pub mod organization_credit_balances;
+pub mod pending_events;
+pub mod processed_events;
+pub mod project_balance_alerts;
+pub mod project_balance_thresholds;
+pub mod project_settings;
 pub mod project_treasuries;
 pub mod sea_orm_active_enums;
+pub mod transaction_status_changes;
 pub mod transactions;
 pub mod treasuries;
+pub mod wallet_creations;
 pub mod wallets;
@@ -1,5 +1,13 @@
 pub use super::{
-    customer_treasuries::Entity as CustomerTreasuries,
-    project_treasuries::Entity as ProjectTreasuries, transactions::Entity as Transactions,
-    treasuries::Entity as Treasuries, wallets::Entity as Wallets,
+    auto_provisioned_assets::Entity as AutoProvisionedAssets,
+    customer_treasuries::Entity as CustomerTreasuries, dead_letters::Entity as DeadLetters,
+    gas_station_fuelings::Entity as GasStationFuelings,
+    organization_credit_balances::Entity as OrganizationCreditBalances,
+    pending_events::Entity as PendingEvents, processed_events::Entity as ProcessedEvents,
+    project_balance_alerts::Entity as ProjectBalanceAlerts,
+    project_balance_thresholds::Entity as ProjectBalanceThresholds,
+    project_settings::Entity as ProjectSettings, project_treasuries::Entity as ProjectTreasuries,
+    transaction_status_changes::Entity as TransactionStatusChanges,
+    transactions::Entity as Transactions, treasuries::Entity as Treasuries,
+    wallet_creations::Entity as WalletCreations, wallets::Entity as Wallets,
 };
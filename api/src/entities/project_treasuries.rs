@@ -1,5 +1,6 @@
 use async_graphql::*;
-use sea_orm::entity::prelude::*;
+use hub_core::prelude::info;
+use sea_orm::{entity::prelude::*, sea_query::Expr};
 use serde::{Deserialize, Serialize};
 
 use super::treasuries;
@@ -13,6 +14,8 @@ pub struct Model {
     #[sea_orm(unique)]
     pub treasury_id: Uuid,
     pub created_at: DateTimeWithTimeZone,
+    /// The organization owning the project. Recorded from the project's `ProjectCreated` event.
+    pub organization_id: Option<Uuid>,
 }
 
 #[Object(name = "ProjectTreasury")]
@@ -61,3 +64,38 @@ impl Related<super::treasuries::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// Finds the treasury of a project owned by the organization. Projects of other organizations
+    /// are not found.
+    ///
+    /// Treasuries whose owner was not recorded before the organization column was added are
+    /// claimed by the first organization looking them up.
+    ///
+    /// # Errors
+    /// Returns an error if the treasury cannot be claimed or loaded.
+    pub async fn find_by_project_and_organization(
+        conn: &impl ConnectionTrait,
+        project_id: Uuid,
+        organization_id: Uuid,
+    ) -> Result<Option<(Model, Option<treasuries::Model>)>, DbErr> {
+        let claimed = Self::update_many()
+            .col_expr(Column::OrganizationId, Expr::value(organization_id))
+            .filter(Column::ProjectId.eq(project_id))
+            .filter(Column::OrganizationId.is_null())
+            .exec(conn)
+            .await?;
+
+        if claimed.rows_affected > 0 {
+            info!(
+                "project {project_id} had no organization recorded, claimed by organization {organization_id}"
+            );
+        }
+
+        Self::find_by_id(project_id)
+            .filter(Column::OrganizationId.eq(organization_id))
+            .find_also_related(treasuries::Entity)
+            .one(conn)
+            .await
+    }
+}
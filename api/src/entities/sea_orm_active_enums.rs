@@ -1,6 +1,10 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.0
+use async_graphql::Enum;
+use fireblocks::objects::transaction::TransactionStatus;
 use sea_orm::entity::prelude::*;
 
+use crate::custody;
+
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "tx_type")]
 pub enum TxType {
@@ -18,6 +22,10 @@ pub enum TxType {
     MintToCollection,
     #[sea_orm(string_value = "switch_collection")]
     SwitchCollection,
+    #[sea_orm(string_value = "transfer_native")]
+    TransferNative,
+    #[sea_orm(string_value = "fuel_vault")]
+    FuelVault,
 }
 
 impl From<TxType> for String {
@@ -30,6 +38,228 @@ impl From<TxType> for String {
             TxType::CreateCollection => "CreateCollection".to_string(),
             TxType::MintToCollection => "MintToCollection".to_string(),
             TxType::SwitchCollection => "SwitchCollection".to_string(),
+            TxType::TransferNative => "TransferNative".to_string(),
+            TxType::FuelVault => "FuelVault".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum TxStatus {
+    #[sea_orm(string_value = "UNSPECIFIED")]
+    Unspecified,
+    #[sea_orm(string_value = "SUBMITTED")]
+    Submitted,
+    #[sea_orm(string_value = "QUEUED")]
+    Queued,
+    #[sea_orm(string_value = "PENDING_AUTHORIZATION")]
+    PendingAuthorization,
+    #[sea_orm(string_value = "PENDING_SIGNATURE")]
+    PendingSignature,
+    #[sea_orm(string_value = "BROADCASTING")]
+    Broadcasting,
+    #[sea_orm(string_value = "PENDING_3RD_PARTY_MANUAL_APPROVAL")]
+    Pending3rdPartyManualApproval,
+    #[sea_orm(string_value = "PENDING_3RD_PARTY")]
+    Pending3rdParty,
+    #[sea_orm(string_value = "CONFIRMING")]
+    Confirming,
+    #[sea_orm(string_value = "PARTIALLY_COMPLETED")]
+    PartiallyCompleted,
+    #[sea_orm(string_value = "PENDING_AML_SCREENING")]
+    PendingAmlScreening,
+    #[sea_orm(string_value = "COMPLETED")]
+    Completed,
+    #[sea_orm(string_value = "CANCELLED")]
+    Cancelled,
+    #[sea_orm(string_value = "REJECTED")]
+    Rejected,
+    #[sea_orm(string_value = "BLOCKED")]
+    Blocked,
+    #[sea_orm(string_value = "FAILED")]
+    Failed,
+    #[sea_orm(string_value = "PENDING")]
+    Pending,
+    #[sea_orm(string_value = "UNKNOWN")]
+    Unknown,
+}
+
+impl From<TransactionStatus> for TxStatus {
+    fn from(value: TransactionStatus) -> Self {
+        match value {
+            TransactionStatus::UNSPECIFIED => Self::Unspecified,
+            TransactionStatus::SUBMITTED => Self::Submitted,
+            TransactionStatus::QUEUED => Self::Queued,
+            TransactionStatus::PENDING_AUTHORIZATION => Self::PendingAuthorization,
+            TransactionStatus::PENDING_SIGNATURE => Self::PendingSignature,
+            TransactionStatus::BROADCASTING => Self::Broadcasting,
+            TransactionStatus::PENDING_3RD_PARTY_MANUAL_APPROVAL => {
+                Self::Pending3rdPartyManualApproval
+            },
+            TransactionStatus::PENDING_3RD_PARTY => Self::Pending3rdParty,
+            TransactionStatus::CONFIRMING => Self::Confirming,
+            TransactionStatus::PARTIALLY_COMPLETED => Self::PartiallyCompleted,
+            TransactionStatus::PENDING_AML_SCREENING => Self::PendingAmlScreening,
+            TransactionStatus::COMPLETED => Self::Completed,
+            TransactionStatus::CANCELLED => Self::Cancelled,
+            TransactionStatus::REJECTED => Self::Rejected,
+            TransactionStatus::BLOCKED => Self::Blocked,
+            TransactionStatus::FAILED => Self::Failed,
+            TransactionStatus::PENDING => Self::Pending,
+            TransactionStatus::UNKNOWN => Self::Unknown,
+        }
+    }
+}
+
+impl From<TxStatus> for TransactionStatus {
+    fn from(value: TxStatus) -> Self {
+        match value {
+            TxStatus::Unspecified => Self::UNSPECIFIED,
+            TxStatus::Submitted => Self::SUBMITTED,
+            TxStatus::Queued => Self::QUEUED,
+            TxStatus::PendingAuthorization => Self::PENDING_AUTHORIZATION,
+            TxStatus::PendingSignature => Self::PENDING_SIGNATURE,
+            TxStatus::Broadcasting => Self::BROADCASTING,
+            TxStatus::Pending3rdPartyManualApproval => Self::PENDING_3RD_PARTY_MANUAL_APPROVAL,
+            TxStatus::Pending3rdParty => Self::PENDING_3RD_PARTY,
+            TxStatus::Confirming => Self::CONFIRMING,
+            TxStatus::PartiallyCompleted => Self::PARTIALLY_COMPLETED,
+            TxStatus::PendingAmlScreening => Self::PENDING_AML_SCREENING,
+            TxStatus::Completed => Self::COMPLETED,
+            TxStatus::Cancelled => Self::CANCELLED,
+            TxStatus::Rejected => Self::REJECTED,
+            TxStatus::Blocked => Self::BLOCKED,
+            TxStatus::Failed => Self::FAILED,
+            TxStatus::Pending => Self::PENDING,
+            TxStatus::Unknown => Self::UNKNOWN,
+        }
+    }
+}
+
+impl From<custody::TransactionStatus> for TxStatus {
+    fn from(value: custody::TransactionStatus) -> Self {
+        use custody::TransactionStatus;
+
+        match value {
+            TransactionStatus::Unspecified => Self::Unspecified,
+            TransactionStatus::Submitted => Self::Submitted,
+            TransactionStatus::Queued => Self::Queued,
+            TransactionStatus::PendingAuthorization => Self::PendingAuthorization,
+            TransactionStatus::PendingSignature => Self::PendingSignature,
+            TransactionStatus::Broadcasting => Self::Broadcasting,
+            TransactionStatus::Pending3rdPartyManualApproval => Self::Pending3rdPartyManualApproval,
+            TransactionStatus::Pending3rdParty => Self::Pending3rdParty,
+            TransactionStatus::Confirming => Self::Confirming,
+            TransactionStatus::PartiallyCompleted => Self::PartiallyCompleted,
+            TransactionStatus::PendingAmlScreening => Self::PendingAmlScreening,
+            TransactionStatus::Completed => Self::Completed,
+            TransactionStatus::Cancelled => Self::Cancelled,
+            TransactionStatus::Rejected => Self::Rejected,
+            TransactionStatus::Blocked => Self::Blocked,
+            TransactionStatus::Failed => Self::Failed,
+            TransactionStatus::Pending => Self::Pending,
+            TransactionStatus::Unknown => Self::Unknown,
         }
     }
 }
+
+impl From<TxStatus> for custody::TransactionStatus {
+    fn from(value: TxStatus) -> Self {
+        match value {
+            TxStatus::Unspecified => Self::Unspecified,
+            TxStatus::Submitted => Self::Submitted,
+            TxStatus::Queued => Self::Queued,
+            TxStatus::PendingAuthorization => Self::PendingAuthorization,
+            TxStatus::PendingSignature => Self::PendingSignature,
+            TxStatus::Broadcasting => Self::Broadcasting,
+            TxStatus::Pending3rdPartyManualApproval => Self::Pending3rdPartyManualApproval,
+            TxStatus::Pending3rdParty => Self::Pending3rdParty,
+            TxStatus::Confirming => Self::Confirming,
+            TxStatus::PartiallyCompleted => Self::PartiallyCompleted,
+            TxStatus::PendingAmlScreening => Self::PendingAmlScreening,
+            TxStatus::Completed => Self::Completed,
+            TxStatus::Cancelled => Self::Cancelled,
+            TxStatus::Rejected => Self::Rejected,
+            TxStatus::Blocked => Self::Blocked,
+            TxStatus::Failed => Self::Failed,
+            TxStatus::Pending => Self::Pending,
+            TxStatus::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Outcome of a gas station check on a vault about to sign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum FuelingOutcome {
+    /// A top up was submitted from the treasury vault.
+    #[sea_orm(string_value = "submitted")]
+    Submitted,
+    /// The top up was skipped because the project reached its daily cap.
+    #[sea_orm(string_value = "capped")]
+    Capped,
+}
+
+/// Step a customer wallet creation has completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum WalletCreationStatus {
+    /// The credits for the wallet are reserved by a pending deduction.
+    #[sea_orm(string_value = "reserved")]
+    Reserved,
+    /// The custodian holds an address for the wallet.
+    #[sea_orm(string_value = "provisioned")]
+    Provisioned,
+    /// The wallet is recorded in the customer's treasury.
+    #[sea_orm(string_value = "recorded")]
+    Recorded,
+    /// The deduction for the wallet is confirmed.
+    #[sea_orm(string_value = "charged")]
+    Charged,
+    /// The wallet was announced to the other services.
+    #[sea_orm(string_value = "completed")]
+    Completed,
+    /// The wallet could not be created. Its deduction, if any, is never confirmed and is left pending.
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+/// Fee level of the transactions submitted for a project.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum FeeLevel {
+    #[sea_orm(string_value = "low")]
+    Low,
+    #[sea_orm(string_value = "medium")]
+    Medium,
+    #[default]
+    #[sea_orm(string_value = "high")]
+    High,
+}
+
+impl From<FeeLevel> for custody::FeeLevel {
+    fn from(value: FeeLevel) -> Self {
+        match value {
+            FeeLevel::Low => Self::Low,
+            FeeLevel::Medium => Self::Medium,
+            FeeLevel::High => Self::High,
+        }
+    }
+}
+
+/// How the vaults of a project are kept funded to pay for their transactions.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum AutoFuelPolicy {
+    /// Vaults are topped up by the gas station of the service, when it is enabled.
+    #[default]
+    #[sea_orm(string_value = "gas_station")]
+    GasStation,
+    /// Vaults created for the project are funded by the Fireblocks auto fuel feature.
+    #[sea_orm(string_value = "fireblocks")]
+    Fireblocks,
+    /// Vaults are never topped up.
+    #[sea_orm(string_value = "disabled")]
+    Disabled,
+}
@@ -2,16 +2,26 @@
 
 use sea_orm::entity::prelude::*;
 
-use super::sea_orm_active_enums::TxType;
+use super::sea_orm_active_enums::{TxStatus, TxType};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "transactions")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub fireblocks_id: Uuid,
-    pub signature: String,
     pub tx_type: TxType,
     pub created_at: DateTimeWithTimeZone,
+    pub signatures: Vec<String>,
+    pub event_id: String,
+    pub project_id: Uuid,
+    pub vault_id: String,
+    pub asset_id: String,
+    pub status: TxStatus,
+    pub tx_hash: Option<String>,
+    pub updated_at: Option<DateTimeWithTimeZone>,
+    pub external_tx_id: Option<String>,
+    pub sub_status: Option<String>,
+    pub event_kind: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -5,7 +5,7 @@ use sea_orm::{entity::prelude::*, FromQueryResult, SelectTwo};
 use serde::{Deserialize, Serialize};
 
 use super::{project_treasuries, wallets};
-use crate::AppContext;
+use crate::{objects::WalletBalance, AppContext};
 
 /// A collection of wallets assigned to different entities in the Holaplex ecosystem.
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, SimpleObject)]
@@ -27,21 +27,33 @@ pub struct Model {
 #[ComplexObject]
 
 impl Model {
-    /// The treasury's associated wallets.
-    async fn wallets(&self, ctx: &Context<'_>) -> Result<Option<Vec<wallets::Model>>> {
+    /// The treasury's associated wallets. Archived wallets are only included when `includeArchived` is set.
+    async fn wallets(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = false)] include_archived: bool,
+    ) -> Result<Option<Vec<wallets::Model>>> {
         let AppContext {
             treasury_wallets_loader,
             ..
         } = ctx.data::<AppContext>()?;
 
-        treasury_wallets_loader.load_one(self.id).await
+        let wallets = treasury_wallets_loader.load_one(self.id).await?;
+
+        Ok(wallets.map(|wallets| {
+            wallets
+                .into_iter()
+                .filter(|wallet| include_archived || !wallet.is_archived())
+                .collect()
+        }))
     }
 
-    /// Lookup a wallet based on its `asset_type`.
+    /// Lookup a wallet based on its `asset_type`. The active wallet is preferred over archived wallets, which are only returned when `includeArchived` is set.
     async fn wallet(
         &self,
         ctx: &Context<'_>,
         asset_type: wallets::AssetType,
+        #[graphql(default = false)] include_archived: bool,
     ) -> Result<Option<wallets::Model>> {
         let AppContext {
             treasury_wallets_loader,
@@ -55,7 +67,25 @@ impl Model {
 
         Ok(wallets
             .into_iter()
-            .find(|wallet| wallet.asset_id == asset_type))
+            .filter(|wallet| wallet.asset_id == asset_type)
+            .filter(|wallet| include_archived || !wallet.is_archived())
+            .min_by_key(|wallet| (wallet.is_archived(), std::cmp::Reverse(wallet.removed_at))))
+    }
+
+    /// The balances of the assets held in the treasury's vault.
+    async fn balances(&self, ctx: &Context<'_>) -> Result<Vec<WalletBalance>> {
+        let AppContext {
+            vault_balances_loader,
+            ..
+        } = ctx.data::<AppContext>()?;
+
+        let assets = vault_balances_loader
+            .load_one(self.vault_id.clone())
+            .await?
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(assets.into_iter().map(Into::into).collect())
     }
 }
 
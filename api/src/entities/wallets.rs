@@ -1,12 +1,19 @@
 use std::str::FromStr;
 
-use async_graphql::{Enum, Result, SimpleObject};
-use fireblocks::assets::{ETH, ETH_TEST, MATIC, MATIC_POLYGON, MATIC_TEST, SOL, SOL_TEST};
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject};
 use hub_core::{credits::Blockchain, thiserror};
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, Iterable};
+
+use crate::{
+    chains::{self, Chain, Token},
+    custody::CustodyClient,
+    objects::WalletBalance,
+    AppContext,
+};
 
 /// Fireblocks-defined blockchain identifiers.
-#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+/// Each asset type is described in the [chain registry](crate::chains).
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "i32", db_type = "Integer")]
 pub enum AssetType {
     /// Mainnet Solana
@@ -21,25 +28,50 @@ pub enum AssetType {
     #[graphql(name = "ETH")]
     #[sea_orm(num_value = 5)]
     Eth,
+    /// Base Mainnet
+    #[graphql(name = "BASE")]
+    #[sea_orm(num_value = 6)]
+    Base,
+    /// Arbitrum One
+    #[graphql(name = "ARBITRUM")]
+    #[sea_orm(num_value = 7)]
+    Arbitrum,
+    /// OP Mainnet
+    #[graphql(name = "OPTIMISM")]
+    #[sea_orm(num_value = 8)]
+    Optimism,
+    /// USDC on Solana
+    #[graphql(name = "USDC_SOL")]
+    #[sea_orm(num_value = 9)]
+    UsdcSol,
+    /// USDC on Polygon
+    #[graphql(name = "USDC_POLYGON")]
+    #[sea_orm(num_value = 10)]
+    UsdcPolygon,
 }
 
 impl AssetType {
+    /// The chain of the asset type in the registry. The chain of a token is the chain it is issued on.
+    #[must_use]
+    pub fn chain(self) -> &'static Chain {
+        chains::chain(self)
+    }
+
+    /// The token of the asset type, unless it is the native asset of a chain.
+    #[must_use]
+    pub fn token(self) -> Option<&'static Token> {
+        chains::token(self)
+    }
+
     pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::Solana => SOL,
-            Self::Matic => MATIC,
-            Self::Eth => ETH,
-        }
+        self.token()
+            .map_or_else(|| self.chain().symbol, |token| token.symbol)
     }
 }
 
 impl From<AssetType> for Blockchain {
     fn from(value: AssetType) -> Self {
-        match value {
-            AssetType::Solana => Blockchain::Solana,
-            AssetType::Matic => Blockchain::Polygon,
-            AssetType::Eth => Blockchain::Ethereum,
-        }
+        value.chain().credits_blockchain
     }
 }
 
@@ -51,11 +83,7 @@ impl From<AssetType> for String {
 
 impl From<AssetType> for i32 {
     fn from(value: AssetType) -> Self {
-        match value {
-            AssetType::Solana => 1,
-            AssetType::Matic => 2,
-            AssetType::Eth => 3,
-        }
+        value.chain().blockchain as i32
     }
 }
 
@@ -63,16 +91,37 @@ impl From<AssetType> for i32 {
 #[error("Invalid asset type {0:?}")]
 pub struct TryIntoAssetTypeError(String);
 
+/// An amount of an asset given on the command line as `ASSET=AMOUNT`, e.g. `SOL=0.5`.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetAmount {
+    pub asset_type: AssetType,
+    pub amount: f64,
+}
+
+impl FromStr for AssetAmount {
+    type Err = hub_core::anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (asset, amount) = s
+            .split_once('=')
+            .ok_or_else(|| hub_core::anyhow::anyhow!("expected ASSET=AMOUNT, got {s:?}"))?;
+
+        let asset_type = AssetType::iter()
+            .find(|asset_type| asset_type.as_str() == asset.trim())
+            .ok_or_else(|| hub_core::anyhow::anyhow!("unknown asset {asset:?}"))?;
+
+        Ok(Self {
+            asset_type,
+            amount: amount.trim().parse()?,
+        })
+    }
+}
+
 impl FromStr for AssetType {
     type Err = TryIntoAssetTypeError;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
-            SOL | SOL_TEST => Ok(Self::Solana),
-            MATIC_POLYGON | MATIC_TEST => Ok(Self::Matic),
-            ETH | ETH_TEST => Ok(Self::Eth),
-            s => Err(TryIntoAssetTypeError(s.into())),
-        }
+        chains::asset_type(s).ok_or_else(|| TryIntoAssetTypeError(s.into()))
     }
 }
 
@@ -81,7 +130,7 @@ impl FromStr for AssetType {
 /// A blockchain wallet is a digital wallet that allows users to securely store, manage, and transfer their cryptocurrencies or other digital assets on a blockchain network.
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, SimpleObject)]
 #[sea_orm(table_name = "wallets")]
-#[graphql(concrete(name = "Wallet", params()))]
+#[graphql(complex, concrete(name = "Wallet", params()))]
 pub struct Model {
     pub treasury_id: Uuid,
     /// The wallet address.
@@ -96,6 +145,44 @@ pub struct Model {
     pub deduction_id: Option<Uuid>,
 }
 
+impl Model {
+    /// Whether the wallet was archived by its owner.
+    #[must_use]
+    pub fn is_archived(&self) -> bool {
+        self.removed_at.is_some()
+    }
+}
+
+#[ComplexObject]
+impl Model {
+    /// The balance of the wallet's asset, as reported by the custodian.
+    /// This field returns null when the custodian holds no balance for the wallet.
+    async fn balance(&self, ctx: &Context<'_>) -> Result<Option<WalletBalance>> {
+        let AppContext {
+            treasury_loader,
+            vault_balances_loader,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let custody = ctx.data::<CustodyClient>()?;
+
+        let Some(treasury) = treasury_loader.load_one(self.treasury_id).await? else {
+            return Ok(None);
+        };
+
+        let asset_id = custody.asset_id(self.asset_id.as_str());
+        let assets = vault_balances_loader
+            .load_one(treasury.vault_id)
+            .await?
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(assets
+            .into_iter()
+            .find(|asset| asset.id == asset_id)
+            .map(Into::into))
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
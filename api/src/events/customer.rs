@@ -1,60 +1,79 @@
-use fireblocks::objects::vault::CreateVault;
-use hub_core::{prelude::*, uuid::Uuid};
-use sea_orm::{prelude::*, Set};
+use std::collections::HashSet;
 
-use super::{Processor, Result};
+use hub_core::{credits::TransactionId, prelude::*, uuid::Uuid};
+use sea_orm::{prelude::*, Set, TransactionTrait};
+
+use super::{vault::vault_treasury, Processor, ProcessorError, Result};
 use crate::{
-    entities::{customer_treasuries, treasuries},
-    events::ProcessorError,
+    custody::NewVault,
+    entities::{
+        auto_provisioned_assets, customer_treasuries, organization_credit_balances,
+        sea_orm_active_enums::AutoFuelPolicy, treasuries, wallets,
+    },
     proto::{
         treasury_events::{self, CustomerTreasury},
         Customer, CustomerEventKey, TreasuryEventKey, TreasuryEvents,
     },
+    provision::WalletRequest,
+    Actions,
 };
 
 impl Processor {
+    /// Creates the treasury of a customer and the wallets its project provisions automatically.
+    ///
+    /// A redelivery of the event after a partial failure reuses the vault named after the customer
+    /// and the treasury recorded for it, then fills in the wallets that are still missing.
+    /// `CustomerTreasuryCreated` is published on every delivery, since the failed one may have
+    /// stopped before publishing it.
     pub(super) async fn create_treasury(
         &self,
         key: CustomerEventKey,
         customer: Customer,
     ) -> Result<()> {
         let conn = self.db.get();
-        let create_vault = CreateVault {
-            name: format!("customer:{}", key.id.clone()),
-            hidden_on_ui: None,
-            customer_ref_id: None,
-            auto_fuel: Some(false),
-        };
+        let project_id = Uuid::from_str(&customer.project_id)?;
+        let customer_id = Uuid::parse_str(&key.id)?;
 
-        let vault = self
-            .fireblocks
-            .client()
-            .create()
-            .vault(create_vault)
-            .await
-            .map_err(ProcessorError::Fireblocks)?;
+        let recorded = customer_treasuries::Entity::find()
+            .filter(customer_treasuries::Column::CustomerId.eq(customer_id))
+            .find_also_related(treasuries::Entity)
+            .one(conn)
+            .await?;
 
-        info!("vault created {:?}", vault);
+        let treasury = if let Some((_, Some(treasury))) = recorded {
+            treasury
+        } else {
+            let settings = self.project_settings(project_id).await?;
 
-        let treasury = treasuries::ActiveModel {
-            vault_id: Set(vault.id.clone()),
-            ..Default::default()
-        };
+            let vault = self
+                .find_or_create_vault(NewVault {
+                    name: format!("customer:{}", key.id.clone()),
+                    customer_ref_id: None,
+                    auto_fuel: settings.auto_fuel == AutoFuelPolicy::Fireblocks,
+                })
+                .await?;
 
-        let treasury: treasuries::Model = treasury.clone().insert(conn).await?;
+            info!("vault for customer {:?}: {:?}", key.id, vault);
 
-        let project_id = Uuid::from_str(&customer.project_id)?;
+            let txn = conn.begin().await?;
 
-        let customer_am = customer_treasuries::ActiveModel {
-            customer_id: Set(Uuid::parse_str(&key.id)?),
-            treasury_id: Set(treasury.id),
-            project_id: Set(project_id),
-            ..Default::default()
-        };
+            let treasury = vault_treasury(&txn, vault.id).await?;
 
-        customer_am.insert(conn).await?;
+            let customer_am = customer_treasuries::ActiveModel {
+                customer_id: Set(customer_id),
+                treasury_id: Set(treasury.id),
+                project_id: Set(project_id),
+                ..Default::default()
+            };
 
-        info!("treasury created for customer {:?}", key.id);
+            customer_am.insert(&txn).await?;
+
+            txn.commit().await?;
+
+            info!("treasury created for customer {:?}", key.id);
+
+            treasury
+        };
 
         let event = TreasuryEvents {
             event: Some(treasury_events::Event::CustomerTreasuryCreated(
@@ -73,6 +92,121 @@ impl Processor {
 
         self.producer.send(Some(&event), Some(&key)).await?;
 
-        Ok(())
+        self.provision_wallets(customer_id, project_id, treasury.id)
+            .await
+    }
+
+    /// Creates a wallet for each asset type the project provisions automatically.
+    /// Asset types the treasury already has or had a wallet for are skipped.
+    ///
+    /// The wallets are charged against the credit balance last reported for the organization of
+    /// the asset. A wallet whose credits cannot be reserved, including while no balance has been
+    /// reported, is recorded as a failed wallet creation instead of failing the event.
+    ///
+    /// # Errors
+    /// Returns the first error of the wallets that could not be created once every asset type has
+    /// been attempted, so that the event is redelivered and the missing wallets are created then.
+    async fn provision_wallets(
+        &self,
+        customer_id: Uuid,
+        project_id: Uuid,
+        treasury_id: Uuid,
+    ) -> Result<()> {
+        let conn = self.db.get();
+
+        let assets = auto_provisioned_assets::Entity::find()
+            .filter(auto_provisioned_assets::Column::ProjectId.eq(project_id))
+            .all(conn)
+            .await?;
+
+        let recorded = wallets::Entity::find()
+            .filter(wallets::Column::TreasuryId.eq(treasury_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|wallet| wallet.asset_id)
+            .collect::<HashSet<_>>();
+
+        let saga = self.wallet_saga();
+        let mut failed = None;
+
+        for asset in assets
+            .into_iter()
+            .filter(|asset| !recorded.contains(&asset.asset_id))
+        {
+            let created = async {
+                let request = WalletRequest::find(conn, customer_id, asset.asset_id).await?;
+
+                let balance = organization_credit_balances::Entity::last_reported(
+                    conn,
+                    asset.organization_id,
+                )
+                .await?;
+
+                let deduction = match balance {
+                    Some(balance) => self
+                        .credits
+                        .submit_pending_deduction(
+                            asset.organization_id,
+                            asset.created_by,
+                            Actions::CreateWallet,
+                            asset.asset_id.into(),
+                            balance,
+                        )
+                        .await
+                        .map_err(|e| format!("credits could not be reserved: {e}")),
+                    None => Err("credit balance of the organization unavailable".to_string()),
+                };
+
+                let TransactionId(credits_deduction_id) = match deduction {
+                    Ok(deduction) => deduction,
+                    Err(reason) => {
+                        info!(
+                            "skipping {} wallet for customer {customer_id}: {reason}",
+                            asset.asset_id
+                        );
+
+                        return saga
+                            .skip(
+                                request,
+                                asset.organization_id,
+                                asset.created_by,
+                                None,
+                                reason,
+                            )
+                            .await
+                            .map(|()| None);
+                    },
+                };
+
+                saga.create(
+                    request,
+                    asset.organization_id,
+                    asset.created_by,
+                    credits_deduction_id,
+                )
+                .await
+                .map(Some)
+            }
+            .await;
+
+            match created {
+                Ok(Some(wallet)) => info!(
+                    "{} wallet {} provisioned for customer {customer_id}",
+                    asset.asset_id, wallet.id
+                ),
+                Ok(None) => (),
+                Err(e) => {
+                    error!(
+                        "failed to provision {} wallet for customer {customer_id}: {e:?}",
+                        asset.asset_id
+                    );
+
+                    failed.get_or_insert(e);
+                },
+            }
+        }
+
+        failed.map_or(Ok(()), |e| Err(ProcessorError::WalletProvisioning(e)))
     }
 }
@@ -0,0 +1,152 @@
+//! Ethereum has a schema of its own for its NFT service events. Its requests are signed by the
+//! shared [`Evm`] signer.
+
+use super::evm::{self, EventKind, Evm, Outcome, Request};
+use crate::{
+    chains::Chain,
+    proto::{
+        ethereum_nft_events::Event as EthereumNftEvent,
+        treasury_events::{
+            EcdsaSignature, EthereumPermitHashSignature, EthereumTransactionResult, Event,
+        },
+        EthereumNftEventKey, EthereumNftEvents, EthereumPermitArgsHash, EthereumTokenTransferTxns,
+        EthereumTransaction, TreasuryEventKey,
+    },
+};
+
+/// The signer of Ethereum.
+pub type Ethereum<'a> = Evm<'a, Schema>;
+
+/// The schema of Ethereum's NFT service.
+pub struct Schema;
+
+impl evm::Schema for Schema {
+    type Events = EthereumNftEvents;
+    type Key = EthereumNftEventKey;
+    type PermitArgsHash = EthereumPermitArgsHash;
+    type TokenTransferTxns = EthereumTokenTransferTxns;
+    type Transaction = EthereumTransaction;
+    type TransactionResult = EthereumTransactionResult;
+
+    fn request(events: EthereumNftEvents) -> Option<Request<Self>> {
+        Some(match events.event? {
+            EthereumNftEvent::SubmitCreateDropTxn(payload) => {
+                Request::Submit(EventKind::CreateDrop, payload)
+            },
+            EthereumNftEvent::SubmitRetryCreateDropTxn(payload) => {
+                Request::Submit(EventKind::RetryCreateDrop, payload)
+            },
+            EthereumNftEvent::SubmitMintDropTxn(payload) => {
+                Request::Submit(EventKind::MintDrop, payload)
+            },
+            EthereumNftEvent::SubmitUpdateDropTxn(payload) => {
+                Request::Submit(EventKind::UpdateDrop, payload)
+            },
+            EthereumNftEvent::SubmitRetryMintDropTxn(payload) => {
+                Request::Submit(EventKind::RetryMintDrop, payload)
+            },
+            EthereumNftEvent::SignPermitTokenTransferHash(payload) => {
+                Request::SignPermitTokenTransferHash(payload)
+            },
+            EthereumNftEvent::SubmitTransferAssetTxns(payload) => {
+                Request::SubmitTransferAssetTxns(payload)
+            },
+            EthereumNftEvent::UpdateMintsOwner(_) => return None,
+        })
+    }
+
+    fn call_data(txn: &EthereumTransaction) -> Vec<u8> {
+        txn.data.clone()
+    }
+
+    fn transaction_result(
+        _chain: &Chain,
+        txn: EthereumTransaction,
+        Outcome {
+            hash,
+            status,
+            failure,
+        }: Outcome,
+    ) -> EthereumTransactionResult {
+        let EthereumTransaction {
+            contract_address,
+            edition_id,
+            ..
+        } = txn;
+
+        EthereumTransactionResult {
+            hash,
+            status,
+            contract_address,
+            edition_id,
+            failure,
+        }
+    }
+
+    fn permit_hash(args: &EthereumPermitArgsHash) -> (String, Vec<u8>) {
+        (args.owner.clone(), args.data.clone())
+    }
+
+    fn permit_hash_signed(
+        _chain: &Chain,
+        args: EthereumPermitArgsHash,
+        signature: EcdsaSignature,
+    ) -> Event {
+        let EthereumPermitArgsHash {
+            owner,
+            spender,
+            recipient,
+            edition_id,
+            amount,
+            ..
+        } = args;
+
+        Event::EthereumPermitTransferTokenHashSigned(EthereumPermitHashSignature {
+            signature: Some(signature),
+            owner,
+            spender,
+            recipient,
+            edition_id,
+            amount,
+        })
+    }
+
+    fn transfer_txns(
+        txns: EthereumTokenTransferTxns,
+    ) -> (Option<EthereumTransaction>, Option<EthereumTransaction>) {
+        (txns.permit_token_transfer_txn, txns.safe_transfer_from_txn)
+    }
+}
+
+impl evm::Submitted for EthereumTransactionResult {
+    fn into_event(self, kind: EventKind) -> Event {
+        match kind {
+            EventKind::CreateDrop => Event::EthereumCreateDropTxnSubmitted(self),
+            EventKind::RetryCreateDrop => Event::EthereumRetryCreateDropSubmitted(self),
+            EventKind::UpdateDrop => Event::EthereumUpdateDropSubmitted(self),
+            EventKind::MintDrop => Event::EthereumMintDropSubmitted(self),
+            EventKind::RetryMintDrop => Event::EthereumRetryMintDropSubmitted(self),
+            EventKind::TransferAsset => Event::EthereumTransferAssetSubmitted(self),
+        }
+    }
+
+    fn status(&self) -> i32 {
+        self.status
+    }
+}
+
+impl From<EthereumNftEventKey> for TreasuryEventKey {
+    fn from(
+        EthereumNftEventKey {
+            id,
+            user_id,
+            project_id,
+        }: EthereumNftEventKey,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            project_id,
+        }
+    }
+}
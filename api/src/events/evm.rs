@@ -0,0 +1,512 @@
+use std::{marker::PhantomData, time::Instant};
+
+use hub_core::{metrics::KeyValue, prelude::*};
+
+use super::{
+    ledger::LedgerEntry,
+    signer::{find_vault_id_by_wallet_address, sign_message, submit_transaction, Sign, TxContext},
+    EcdsaSignatureScalar, Processor, ProcessorError, Result,
+};
+use crate::{
+    chains::Chain,
+    custody::{Signature, Transaction},
+    entities::{sea_orm_active_enums::TxType, wallets::AssetType},
+    proto::{
+        evm_nft_events::Event as EvmNftEvent,
+        treasury_events::{
+            EcdsaSignature, Event, EvmPermitHashSignature, EvmTransactionResult,
+            TransactionFailure, TransactionStatus,
+        },
+        EvmNftEventKey, EvmNftEvents, EvmPermitArgsHash, EvmTokenTransferTxns, EvmTransaction,
+        TreasuryEventKey, TreasuryEvents,
+    },
+};
+
+/// Submits a contract call with the signing vault, contract wallet and fee level of the project.
+async fn send_contract_call(
+    processor: &Processor,
+    ctx: &TxContext,
+    asset_type: AssetType,
+    note: String,
+    data: Vec<u8>,
+) -> Result<Transaction> {
+    let custody = &processor.custody;
+    let asset_id = custody.asset_id(asset_type.as_str());
+
+    let settings = processor
+        .enabled_project_settings(ctx.project_id, asset_type)
+        .await?;
+    let vault = settings
+        .signing_vault_id
+        .unwrap_or_else(|| custody.treasury_vault());
+    let external_tx_id = ctx.external_tx_id(&vault, &data);
+
+    // The contract wallet override of a project only applies to Polygon. Calls on other chains
+    // always go to the configured contract wallet, which whitelists their contracts.
+    let contract_wallet = settings
+        .polygon_contract_wallet_id
+        .filter(|_| asset_type == AssetType::Matic);
+
+    let submit = |external_tx_id| {
+        custody.contract_call(
+            data,
+            asset_id.clone(),
+            vault.clone(),
+            note,
+            external_tx_id,
+            contract_wallet,
+            settings.fee_level.into(),
+        )
+    };
+
+    submit_transaction(
+        processor,
+        ctx,
+        vault.clone(),
+        asset_id.clone(),
+        external_tx_id,
+        true,
+        submit,
+    )
+    .await
+}
+
+/// The hash, status and failure reported for a submitted contract call.
+///
+/// # Errors
+/// Returns [`ProcessorError::TransactionPending`] so that the event is deferred, other errors are
+/// reported as a failed transaction.
+fn contract_call_outcome(
+    kind: impl fmt::Debug,
+    submitted: Result<Transaction>,
+) -> Result<(Option<String>, i32, Option<TransactionFailure>)> {
+    match submitted {
+        Ok(details) => Ok((Some(details.tx_hash), details.status as i32, None)),
+        Err(e @ ProcessorError::TransactionPending(_)) => Err(e),
+        Err(e) => {
+            error!("Error submitting {kind:?}: {e:?}");
+
+            Ok((None, TransactionStatus::Failed as i32, Some(e.failure())))
+        },
+    }
+}
+
+/// Signs a permit hash with the vault holding the wallet of `owner`.
+async fn sign_permit_hash<G: Sign<Signature = Signature> + Sync>(
+    signer: &G,
+    ctx: &TxContext,
+    owner: String,
+    data: Vec<u8>,
+) -> Result<EcdsaSignature> {
+    let processor = signer.processor();
+
+    processor
+        .enabled_project_settings(ctx.project_id, signer.asset_type())
+        .await?;
+    let vault_id = find_vault_id_by_wallet_address(processor.db.get(), owner).await?;
+    let signature = signer
+        .sign_message(ctx, String::new(), data, vault_id)
+        .await?;
+
+    Ok(EcdsaSignature {
+        r: hex::decode(signature.r.ok_or(ProcessorError::IncompleteEcdsaSignature(
+            EcdsaSignatureScalar::R,
+        ))?)?,
+        s: hex::decode(signature.s.ok_or(ProcessorError::IncompleteEcdsaSignature(
+            EcdsaSignatureScalar::S,
+        ))?)?,
+        v: (signature.v.ok_or(ProcessorError::IncompleteEcdsaSignature(
+            EcdsaSignatureScalar::V,
+        ))? + 27)
+            .try_into()
+            .map_err(ProcessorError::InvalidEcdsaPubkeyRecovery)?,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EventKind {
+    CreateDrop,
+    RetryCreateDrop,
+    UpdateDrop,
+    MintDrop,
+    RetryMintDrop,
+    TransferAsset,
+}
+
+impl<T: Submitted> super::signer::EventKind<T> for EventKind {
+    fn to_event(&self, txn: T) -> Event {
+        txn.into_event(*self)
+    }
+
+    fn tx_type(&self) -> TxType {
+        match self {
+            EventKind::CreateDrop | EventKind::RetryCreateDrop => TxType::CreateDrop,
+            EventKind::UpdateDrop => TxType::UpdateMetadata,
+            EventKind::MintDrop | EventKind::RetryMintDrop => TxType::MintEdition,
+            EventKind::TransferAsset => TxType::TransferMint,
+        }
+    }
+}
+
+/// The result of a contract call submitted for an NFT service.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub hash: Option<String>,
+    pub status: i32,
+    pub failure: Option<TransactionFailure>,
+}
+
+/// The result reported for a submitted contract call, in the schema of a chain's NFT service.
+pub trait Submitted: Clone + Send + Sync {
+    /// The event reporting the result for a request of `kind`.
+    fn into_event(self, kind: EventKind) -> Event;
+
+    /// The [`TransactionStatus`] of the contract call.
+    fn status(&self) -> i32;
+}
+
+/// A request of a chain's NFT service.
+pub enum Request<S: Schema> {
+    Submit(EventKind, S::Transaction),
+    SignPermitTokenTransferHash(S::PermitArgsHash),
+    SubmitTransferAssetTxns(S::TokenTransferTxns),
+}
+
+/// The protobuf schema of an EVM chain's NFT service: the requests it sends and the events they
+/// are answered with. Polygon and Ethereum have schemas of their own, the other EVM chains share
+/// [`EvmSchema`]. Everything else about signing for them is the same and lives in [`Evm`].
+pub trait Schema: Send + Sync + Sized + 'static {
+    type Key: Clone + Into<TreasuryEventKey> + Send + Sync;
+    type Events: Send;
+    type Transaction: Message + Send;
+    type PermitArgsHash: Message + Send;
+    type TokenTransferTxns: Message + Send;
+    type TransactionResult: Submitted;
+
+    /// The request carried by an event, if it is one treasuries handle.
+    fn request(events: Self::Events) -> Option<Request<Self>>;
+
+    /// The calldata of a contract call.
+    fn call_data(txn: &Self::Transaction) -> Vec<u8>;
+
+    /// The result reported for a submitted contract call.
+    fn transaction_result(
+        chain: &Chain,
+        txn: Self::Transaction,
+        outcome: Outcome,
+    ) -> Self::TransactionResult;
+
+    /// The owner of the permit and the hash to sign.
+    fn permit_hash(args: &Self::PermitArgsHash) -> (String, Vec<u8>);
+
+    /// The event reporting the signature of a permit hash.
+    fn permit_hash_signed(
+        chain: &Chain,
+        args: Self::PermitArgsHash,
+        signature: EcdsaSignature,
+    ) -> Event;
+
+    /// The permit and transfer transactions of an asset transfer.
+    fn transfer_txns(
+        txns: Self::TokenTransferTxns,
+    ) -> (Option<Self::Transaction>, Option<Self::Transaction>);
+}
+
+/// Signer of the EVM chains. `S` is the schema of the chain's NFT service.
+pub struct Evm<'a, S> {
+    processor: &'a Processor,
+    chain: &'static Chain,
+    schema: PhantomData<S>,
+}
+
+impl<'a, S: Schema> Evm<'a, S> {
+    #[inline]
+    #[must_use]
+    pub fn new(processor: &'a Processor, chain: &'static Chain) -> Self {
+        Self {
+            processor,
+            chain,
+            schema: PhantomData,
+        }
+    }
+
+    /// Processes the events of the chain's NFT service.
+    pub async fn process(&self, key: S::Key, events: S::Events) -> Result<()> {
+        match S::request(events) {
+            Some(Request::Submit(kind, payload)) => {
+                self.send_and_notify(kind, key, payload).await?;
+            },
+            Some(Request::SignPermitTokenTransferHash(payload)) => {
+                self.sign_permit_token_transfer_hash(key, payload).await?;
+            },
+            Some(Request::SubmitTransferAssetTxns(payload)) => {
+                self.submit_transfer_asset_txns(key, payload).await?;
+            },
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    async fn sign_permit_token_transfer_hash(
+        &self,
+        key: S::Key,
+        payload: S::PermitArgsHash,
+    ) -> Result<()> {
+        let entry = LedgerEntry::new(key.clone(), "SignPermitTokenTransferHash", &payload);
+
+        if self.processor.replay(&entry).await? {
+            return Ok(());
+        }
+
+        let (owner, data) = S::permit_hash(&payload);
+
+        let ctx = TxContext::new(&entry, key.clone(), TxType::TransferMint)?;
+        let signature = sign_permit_hash(self, &ctx, owner, data).await?;
+
+        let event = TreasuryEvents {
+            event: Some(S::permit_hash_signed(self.chain, payload, signature)),
+        };
+
+        self.processor.emit(&entry, vec![(key.into(), event)]).await
+    }
+
+    async fn submit_transfer_asset_txns(
+        &self,
+        key: S::Key,
+        payload: S::TokenTransferTxns,
+    ) -> Result<()> {
+        let entry = LedgerEntry::new(key.clone(), "SubmitTransferAssetTxns", &payload);
+
+        if self.processor.replay(&entry).await? {
+            return Ok(());
+        }
+
+        let ctx = TxContext::new(&entry, key.clone(), TxType::TransferMint)?;
+
+        let (permit_token_transfer_txn, safe_transfer_from_txn) = S::transfer_txns(payload);
+        let permit_txn_data =
+            permit_token_transfer_txn.ok_or(ProcessorError::MissingPermitTokenTransferTxn)?;
+        let safe_txn_data =
+            safe_transfer_from_txn.ok_or(ProcessorError::MissingSafeTransferFromTxn)?;
+
+        self.send_transaction(&ctx, EventKind::TransferAsset, key.clone(), permit_txn_data)
+            .await?;
+
+        let txn = self
+            .send_transaction(&ctx, EventKind::TransferAsset, key.clone(), safe_txn_data)
+            .await?;
+
+        self.notify(&entry, EventKind::TransferAsset, key, txn)
+            .await
+    }
+}
+
+#[async_trait]
+impl<'a, S: Schema> Sign for Evm<'a, S> {
+    type EventKind = EventKind;
+    type Key = S::Key;
+    type Payload = S::Transaction;
+    type Signature = Signature;
+    type Transaction = S::TransactionResult;
+
+    #[inline]
+    fn processor(&self) -> &Processor {
+        self.processor
+    }
+
+    #[inline]
+    fn asset_type(&self) -> AssetType {
+        self.chain.asset_type
+    }
+
+    fn succeeded(txn: &S::TransactionResult) -> bool {
+        txn.status() == TransactionStatus::Completed as i32
+    }
+
+    async fn sign_message(
+        &self,
+        ctx: &TxContext,
+        note: String,
+        message: Vec<u8>,
+        vault_id: String,
+    ) -> Result<Signature> {
+        let start = Instant::now();
+
+        let sig = sign_message(self, ctx, note, message, vault_id, false).await;
+
+        let elapsed = i64::try_from(start.elapsed().as_millis()).unwrap_or(0);
+        self.processor
+            .metrics
+            .sign_duration_ms_bucket
+            .record(elapsed, &[KeyValue::new("blockchain", self.chain.name)]);
+
+        sig
+    }
+
+    async fn send_transaction(
+        &self,
+        ctx: &TxContext,
+        kind: EventKind,
+        key: S::Key,
+        payload: S::Transaction,
+    ) -> Result<S::TransactionResult> {
+        let TreasuryEventKey {
+            user_id,
+            project_id,
+            ..
+        } = key.into();
+        let note = format!(
+            "{kind:?} on {} by {user_id:?} for project {project_id:?}",
+            self.chain.name,
+        );
+
+        let data = S::call_data(&payload);
+        let submitted =
+            send_contract_call(self.processor, ctx, self.chain.asset_type, note, data).await;
+        let (hash, status, failure) = contract_call_outcome(kind, submitted)?;
+
+        Ok(S::transaction_result(self.chain, payload, Outcome {
+            hash,
+            status,
+            failure,
+        }))
+    }
+}
+
+/// The schema shared by the EVM chains without one of their own, e.g. Base. Their events are told
+/// apart by the topic they are consumed from.
+pub struct EvmSchema;
+
+impl Schema for EvmSchema {
+    type Events = EvmNftEvents;
+    type Key = EvmNftEventKey;
+    type PermitArgsHash = EvmPermitArgsHash;
+    type TokenTransferTxns = EvmTokenTransferTxns;
+    type Transaction = EvmTransaction;
+    type TransactionResult = EvmTransactionResult;
+
+    fn request(events: EvmNftEvents) -> Option<Request<Self>> {
+        Some(match events.event? {
+            EvmNftEvent::SubmitCreateDropTxn(payload) => {
+                Request::Submit(EventKind::CreateDrop, payload)
+            },
+            EvmNftEvent::SubmitRetryCreateDropTxn(payload) => {
+                Request::Submit(EventKind::RetryCreateDrop, payload)
+            },
+            EvmNftEvent::SubmitMintDropTxn(payload) => {
+                Request::Submit(EventKind::MintDrop, payload)
+            },
+            EvmNftEvent::SubmitUpdateDropTxn(payload) => {
+                Request::Submit(EventKind::UpdateDrop, payload)
+            },
+            EvmNftEvent::SubmitRetryMintDropTxn(payload) => {
+                Request::Submit(EventKind::RetryMintDrop, payload)
+            },
+            EvmNftEvent::SignPermitTokenTransferHash(payload) => {
+                Request::SignPermitTokenTransferHash(payload)
+            },
+            EvmNftEvent::SubmitTransferAssetTxns(payload) => {
+                Request::SubmitTransferAssetTxns(payload)
+            },
+            EvmNftEvent::UpdateMintsOwner(_) => return None,
+        })
+    }
+
+    fn call_data(txn: &EvmTransaction) -> Vec<u8> {
+        txn.data.clone()
+    }
+
+    fn transaction_result(
+        chain: &Chain,
+        txn: EvmTransaction,
+        Outcome {
+            hash,
+            status,
+            failure,
+        }: Outcome,
+    ) -> EvmTransactionResult {
+        let EvmTransaction {
+            contract_address,
+            edition_id,
+            ..
+        } = txn;
+
+        EvmTransactionResult {
+            hash,
+            status,
+            contract_address,
+            edition_id,
+            failure,
+            blockchain: chain.event_blockchain as i32,
+        }
+    }
+
+    fn permit_hash(args: &EvmPermitArgsHash) -> (String, Vec<u8>) {
+        (args.owner.clone(), args.data.clone())
+    }
+
+    fn permit_hash_signed(
+        chain: &Chain,
+        args: EvmPermitArgsHash,
+        signature: EcdsaSignature,
+    ) -> Event {
+        let EvmPermitArgsHash {
+            owner,
+            spender,
+            recipient,
+            edition_id,
+            amount,
+            ..
+        } = args;
+
+        Event::EvmPermitTransferTokenHashSigned(EvmPermitHashSignature {
+            signature: Some(signature),
+            owner,
+            spender,
+            recipient,
+            edition_id,
+            amount,
+            blockchain: chain.event_blockchain as i32,
+        })
+    }
+
+    fn transfer_txns(
+        txns: EvmTokenTransferTxns,
+    ) -> (Option<EvmTransaction>, Option<EvmTransaction>) {
+        (txns.permit_token_transfer_txn, txns.safe_transfer_from_txn)
+    }
+}
+
+impl Submitted for EvmTransactionResult {
+    fn into_event(self, kind: EventKind) -> Event {
+        match kind {
+            EventKind::CreateDrop => Event::EvmCreateDropTxnSubmitted(self),
+            EventKind::RetryCreateDrop => Event::EvmRetryCreateDropSubmitted(self),
+            EventKind::UpdateDrop => Event::EvmUpdateDropSubmitted(self),
+            EventKind::MintDrop => Event::EvmMintDropSubmitted(self),
+            EventKind::RetryMintDrop => Event::EvmRetryMintDropSubmitted(self),
+            EventKind::TransferAsset => Event::EvmTransferAssetSubmitted(self),
+        }
+    }
+
+    fn status(&self) -> i32 {
+        self.status
+    }
+}
+
+impl From<EvmNftEventKey> for TreasuryEventKey {
+    fn from(
+        EvmNftEventKey {
+            id,
+            user_id,
+            project_id,
+        }: EvmNftEventKey,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            project_id,
+        }
+    }
+}
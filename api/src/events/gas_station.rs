@@ -0,0 +1,306 @@
+//! Tops up vaults from the treasury vault so they can pay the fees of the transactions they sign.
+
+use std::collections::HashMap;
+
+use hub_core::{
+    chrono::{self, Utc},
+    clap,
+    prelude::*,
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use super::{
+    signer::{deadline_passed, refresh_transaction, TxContext},
+    Processor, ProcessorError, Result,
+};
+use crate::{
+    custody::{Destination, StatusClass},
+    entities::{
+        gas_station_fuelings,
+        sea_orm_active_enums::{AutoFuelPolicy, FuelingOutcome, TxType},
+        transaction_status_changes, transactions,
+        wallets::{AssetAmount, AssetType},
+    },
+};
+
+#[derive(Debug, clap::Args)]
+pub struct GasStationArgs {
+    /// Top up vaults from the treasury vault before they sign transactions
+    #[arg(long, env, default_value = "false")]
+    pub gas_station_enabled: bool,
+
+    /// Estimated fee per asset. Vaults holding less are topped up, e.g. `SOL=0.01,MATIC=0.1`
+    #[arg(long, env, value_delimiter = ',')]
+    pub gas_station_min_balances: Vec<AssetAmount>,
+
+    /// Amount per asset sent to a vault holding less than the estimated fee, e.g. `SOL=0.05,MATIC=1`
+    #[arg(long, env, value_delimiter = ',')]
+    pub gas_station_top_ups: Vec<AssetAmount>,
+
+    /// Most sent per asset to the vaults of a single project in 24 hours, e.g. `SOL=1,MATIC=20`
+    #[arg(long, env, value_delimiter = ',')]
+    pub gas_station_daily_caps: Vec<AssetAmount>,
+}
+
+/// Decides when and by how much vaults are topped up.
+/// Assets without both a minimum balance and a top up amount are never topped up.
+#[derive(Debug, Clone, Default)]
+pub struct GasStation {
+    enabled: bool,
+    min_balances: HashMap<AssetType, f64>,
+    top_ups: HashMap<AssetType, f64>,
+    daily_caps: HashMap<AssetType, f64>,
+}
+
+impl GasStation {
+    #[must_use]
+    pub fn new(args: GasStationArgs) -> Self {
+        let GasStationArgs {
+            gas_station_enabled,
+            gas_station_min_balances,
+            gas_station_top_ups,
+            gas_station_daily_caps,
+        } = args;
+
+        let by_asset = |amounts: Vec<AssetAmount>| {
+            amounts
+                .into_iter()
+                .map(|a| (a.asset_type, a.amount))
+                .collect()
+        };
+
+        Self {
+            enabled: gas_station_enabled,
+            min_balances: by_asset(gas_station_min_balances),
+            top_ups: by_asset(gas_station_top_ups),
+            daily_caps: by_asset(gas_station_daily_caps),
+        }
+    }
+}
+
+impl Processor {
+    /// Tops up a vault from the treasury vault when its available balance of `asset_id` is below the
+    /// estimated fee, unless the project's auto fuel policy leaves funding to Fireblocks or turns
+    /// it off. Every check that leads to a top up, or to one skipped because of the project's daily
+    /// cap, is recorded in the gas station audit log.
+    ///
+    /// # Errors
+    /// Returns [`ProcessorError::TransactionPending`] while the top up awaits completion, so the event
+    /// is deferred and signs once the vault is funded.
+    pub(crate) async fn fuel(
+        &self,
+        ctx: &TxContext,
+        vault_id: &str,
+        asset_id: &str,
+        external_tx_id: &str,
+    ) -> Result<()> {
+        let gas_station = &self.gas_station;
+        let treasury_vault = self.custody.treasury_vault();
+
+        if !gas_station.enabled || vault_id == treasury_vault {
+            return Ok(());
+        }
+
+        let Ok(asset_type) = AssetType::from_str(asset_id) else {
+            return Ok(());
+        };
+
+        let (Some(min_balance), Some(top_up)) = (
+            gas_station.min_balances.get(&asset_type).copied(),
+            gas_station.top_ups.get(&asset_type).copied(),
+        ) else {
+            return Ok(());
+        };
+
+        let settings = self.project_settings(ctx.project_id).await?;
+
+        if settings.auto_fuel != AutoFuelPolicy::GasStation {
+            return Ok(());
+        }
+
+        let conn = self.db.get();
+        let fuel_tx_id = format!("fuel:{external_tx_id}");
+
+        let fueling = gas_station_fuelings::Entity::find()
+            .filter(gas_station_fuelings::Column::ExternalTxId.eq(fuel_tx_id.clone()))
+            .one(conn)
+            .await?;
+
+        if let Some(fueling) = fueling {
+            return match fueling.fireblocks_id {
+                Some(fireblocks_id) => self.check_fueling(fireblocks_id).await,
+                None => Ok(()),
+            };
+        }
+
+        let vault = self
+            .custody
+            .vault(vault_id.to_string())
+            .await
+            .map_err(ProcessorError::Custody)?;
+
+        let balance = vault
+            .assets
+            .iter()
+            .find(|asset| asset.id == asset_id)
+            .and_then(|asset| asset.available.parse::<f64>().ok())
+            .unwrap_or_default();
+
+        if balance >= min_balance {
+            return Ok(());
+        }
+
+        let fueled = gas_station_fuelings::Entity::find()
+            .filter(
+                gas_station_fuelings::Column::ProjectId
+                    .eq(ctx.project_id)
+                    .and(gas_station_fuelings::Column::AssetId.eq(asset_id))
+                    .and(gas_station_fuelings::Column::Outcome.eq(FuelingOutcome::Submitted))
+                    .and(
+                        gas_station_fuelings::Column::CreatedAt
+                            .gte(Utc::now() - chrono::Duration::days(1)),
+                    ),
+            )
+            .all(conn)
+            .await?
+            .iter()
+            .map(|fueling| fueling.amount)
+            .sum::<f64>();
+
+        let capped = gas_station
+            .daily_caps
+            .get(&asset_type)
+            .map_or(false, |cap| fueled + top_up > *cap);
+
+        let fireblocks_id = if capped {
+            error!(
+                "project {} reached its daily gas station cap for {asset_id}, vault {vault_id} not topped up",
+                ctx.project_id
+            );
+
+            None
+        } else {
+            Some(
+                self.submit_fueling(ctx, vault_id, asset_id, top_up, fuel_tx_id.clone())
+                    .await?,
+            )
+        };
+
+        let active_model = gas_station_fuelings::ActiveModel {
+            project_id: Set(ctx.project_id),
+            vault_id: Set(vault_id.to_string()),
+            asset_id: Set(asset_id.to_string()),
+            balance: Set(balance),
+            amount: Set(if capped { 0.0 } else { top_up }),
+            outcome: Set(if capped {
+                FuelingOutcome::Capped
+            } else {
+                FuelingOutcome::Submitted
+            }),
+            external_tx_id: Set(fuel_tx_id),
+            fireblocks_id: Set(fireblocks_id),
+            ..Default::default()
+        };
+
+        gas_station_fuelings::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(gas_station_fuelings::Column::ExternalTxId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
+
+        match fireblocks_id {
+            Some(fireblocks_id) => self.check_fueling(fireblocks_id).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn submit_fueling(
+        &self,
+        ctx: &TxContext,
+        vault_id: &str,
+        asset_id: &str,
+        amount: f64,
+        fuel_tx_id: String,
+    ) -> Result<Uuid> {
+        let treasury_vault = self.custody.treasury_vault();
+
+        info!(
+            "topping up vault {vault_id} with {amount} {asset_id} for project {}",
+            ctx.project_id
+        );
+
+        let transaction = self
+            .custody
+            .transfer(
+                asset_id.to_string(),
+                treasury_vault.clone(),
+                Destination::Vault(vault_id.to_string()),
+                amount.to_string(),
+                format!("Gas station top up for project {}", ctx.project_id),
+                fuel_tx_id.clone(),
+            )
+            .await
+            .map_err(ProcessorError::Custody)?;
+
+        let fireblocks_id = Uuid::from_str(&transaction.id)?;
+
+        let active_model = transactions::ActiveModel {
+            fireblocks_id: Set(fireblocks_id),
+            tx_type: Set(TxType::FuelVault),
+            signatures: Set(Vec::new()),
+            event_id: Set(ctx.entry.event_id.clone()),
+            project_id: Set(ctx.project_id),
+            vault_id: Set(treasury_vault),
+            asset_id: Set(asset_id.to_string()),
+            status: Set(transaction.status.into()),
+            external_tx_id: Set(Some(fuel_tx_id)),
+            event_kind: Set(Some(ctx.entry.event_kind.clone())),
+            ..Default::default()
+        };
+
+        transactions::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(transactions::Column::FireblocksId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        transaction_status_changes::record(
+            self.db.get(),
+            fireblocks_id,
+            transaction.status.into(),
+            None,
+        )
+        .await?;
+
+        Ok(fireblocks_id)
+    }
+
+    /// Waits on a top up without blocking. A top up that fails or stays pending past the wait
+    /// deadline is logged and the vault signs without it.
+    async fn check_fueling(&self, fireblocks_id: Uuid) -> Result<()> {
+        let (record, details) = refresh_transaction(self, fireblocks_id).await?;
+        let policy = self.custody.wait_policy();
+
+        match policy.classify(details.status) {
+            StatusClass::Success => Ok(()),
+            StatusClass::Pending if !deadline_passed(&policy, &record) => {
+                Err(ProcessorError::TransactionPending(fireblocks_id))
+            },
+            StatusClass::Pending | StatusClass::Failure => {
+                error!(
+                    "gas station top up {fireblocks_id} did not complete, status {:?}",
+                    details.status
+                );
+
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use hub_core::prelude::*;
+use sea_orm::{prelude::*, sea_query::OnConflict, QueryOrder, Set};
+use sha2::{Digest, Sha256};
+
+use super::{Processor, Result};
+use crate::{
+    entities::processed_events,
+    proto::{TreasuryEventKey, TreasuryEvents},
+};
+
+/// Hashes `parts` into a hex digest. Every part is length prefixed so that
+/// moving bytes from one part to the next changes the digest.
+pub(crate) fn digest<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> String {
+    let mut hasher = Sha256::new();
+
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// Identifies an event within the processed events ledger.
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub event_id: String,
+    pub event_kind: String,
+    pub payload_digest: String,
+}
+
+impl LedgerEntry {
+    pub(crate) fn new(
+        key: impl Into<TreasuryEventKey>,
+        event_kind: impl Into<String>,
+        payload: &impl Message,
+    ) -> Self {
+        Self {
+            event_id: key.into().id,
+            event_kind: event_kind.into(),
+            payload_digest: digest([payload.encode_to_vec().as_slice()]),
+        }
+    }
+}
+
+impl Processor {
+    /// Emits again the messages stored for an event that was already processed.
+    /// Returns `false` if the event has not been processed before.
+    pub(crate) async fn replay(&self, entry: &LedgerEntry) -> Result<bool> {
+        let messages = processed_events::Entity::find()
+            .filter(
+                processed_events::Column::EventId
+                    .eq(entry.event_id.clone())
+                    .and(processed_events::Column::EventKind.eq(entry.event_kind.clone()))
+                    .and(processed_events::Column::PayloadDigest.eq(entry.payload_digest.clone())),
+            )
+            .order_by_asc(processed_events::Column::Position)
+            .all(self.db.get())
+            .await?;
+
+        if messages.is_empty() {
+            return Ok(false);
+        }
+
+        info!(
+            "{} {} already processed, replaying {} messages",
+            entry.event_kind,
+            entry.event_id,
+            messages.len()
+        );
+
+        for message in messages {
+            let key = TreasuryEventKey::decode(message.message_key.as_slice())?;
+            let event = TreasuryEvents::decode(message.message_payload.as_slice())?;
+
+            self.producer.send(Some(&event), Some(&key)).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Stores the messages resulting from processing an event in the ledger and emits them.
+    pub(crate) async fn emit(
+        &self,
+        entry: &LedgerEntry,
+        messages: Vec<(TreasuryEventKey, TreasuryEvents)>,
+    ) -> Result<()> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let active_models =
+            (0..)
+                .zip(&messages)
+                .map(|(position, (key, event))| processed_events::ActiveModel {
+                    event_id: Set(entry.event_id.clone()),
+                    event_kind: Set(entry.event_kind.clone()),
+                    payload_digest: Set(entry.payload_digest.clone()),
+                    position: Set(position),
+                    message_key: Set(key.encode_to_vec()),
+                    message_payload: Set(event.encode_to_vec()),
+                    ..Default::default()
+                });
+
+        processed_events::Entity::insert_many(active_models)
+            .on_conflict(
+                OnConflict::columns([
+                    processed_events::Column::EventId,
+                    processed_events::Column::EventKind,
+                    processed_events::Column::PayloadDigest,
+                    processed_events::Column::Position,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        for (key, event) in messages {
+            self.producer.send(Some(&event), Some(&key)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::digest;
+
+    #[test]
+    fn digest_is_stable() {
+        let parts: [&[u8]; 2] = [b"ProjectCreated", b"payload"];
+
+        assert_eq!(digest(parts), digest(parts));
+        assert_eq!(digest(parts).len(), 64);
+    }
+
+    #[test]
+    fn digest_separates_parts() {
+        let joined: [&[u8]; 2] = [b"ab", b"c"];
+        let split: [&[u8]; 2] = [b"a", b"bc"];
+        let concatenated: [&[u8]; 1] = [b"abc"];
+
+        assert_ne!(digest(joined), digest(split));
+        assert_ne!(digest(joined), digest(concatenated));
+        assert_ne!(digest([b"".as_slice()]), digest(std::iter::empty()));
+    }
+}
@@ -1,8 +1,18 @@
 pub mod customer;
+pub mod ethereum;
+pub mod evm;
+mod gas_station;
+mod ledger;
 pub mod organization;
 pub mod polygon;
 mod processor;
+mod settings;
 mod signer;
 pub mod solana;
+mod tracker;
+mod vault;
+mod webhook;
 
+pub use gas_station::{GasStation, GasStationArgs};
 pub use processor::*;
+pub use tracker::{Tracker, TrackerArgs};
@@ -1,14 +1,14 @@
-use fireblocks::{
-    assets::{ETH, ETH_TEST, MATIC, MATIC_TEST, SOL, SOL_TEST},
-    objects::vault::{CreateVault, CreateVaultWallet},
-};
 use hub_core::{prelude::*, uuid::Uuid};
-use sea_orm::{prelude::*, Set};
+use sea_orm::{prelude::*, Set, TransactionTrait};
 
-use super::{Processor, ProcessorError, Result};
+use super::{vault::vault_treasury, Processor, ProcessorError, Result};
 use crate::{
+    chains,
+    custody::NewVault,
     entities::{
-        project_treasuries, treasuries,
+        project_treasuries,
+        sea_orm_active_enums::AutoFuelPolicy,
+        treasuries,
         wallets::{self, AssetType},
     },
     proto::{
@@ -19,6 +19,12 @@ use crate::{
 };
 
 impl Processor {
+    /// Creates the treasury of a project along with a wallet for every asset of the custodian.
+    ///
+    /// Each step checks what an earlier delivery of the event already did, so a redelivery after a
+    /// partial failure resumes where it stopped: the vault named after the project is reused, the
+    /// treasury is only recorded once and only the missing wallets are created. Wallets already
+    /// recorded are announced again, since the failed delivery may have stopped before announcing them.
     pub(super) async fn create_project_treasury(
         &self,
         key: OrganizationEventKey,
@@ -26,67 +32,101 @@ impl Processor {
     ) -> Result<()> {
         let conn = self.db.get();
         let user_id = Uuid::from_str(&key.user_id)?;
+        let project_id = Uuid::parse_str(&project.id)?;
+        let organization_id = Uuid::parse_str(&project.organization_id)?;
+
+        let recorded = project_treasuries::Entity::find_by_id(project_id)
+            .find_also_related(treasuries::Entity)
+            .one(conn)
+            .await?;
+
+        let treasury = if let Some((project_treasury, Some(treasury))) = recorded {
+            if project_treasury.organization_id.is_none() {
+                let mut active_model: project_treasuries::ActiveModel = project_treasury.into();
+                active_model.organization_id = Set(Some(organization_id));
+                active_model.update(conn).await?;
+            }
+
+            treasury
+        } else {
+            let settings = self.project_settings(project_id).await?;
+
+            let vault = self
+                .find_or_create_vault(NewVault {
+                    name: format!("project:{}", project.id.clone()),
+                    customer_ref_id: Some(key.user_id),
+                    auto_fuel: settings.auto_fuel == AutoFuelPolicy::Fireblocks,
+                })
+                .await?;
 
-        let create_vault = CreateVault {
-            name: format!("project:{}", project.id.clone()),
-            hidden_on_ui: None,
-            customer_ref_id: Some(key.user_id),
-            auto_fuel: Some(false),
-        };
+            let txn = conn.begin().await?;
 
-        let vault = self
-            .fireblocks
-            .client()
-            .create()
-            .vault(create_vault)
-            .await
-            .map_err(ProcessorError::Fireblocks)?;
+            let treasury = vault_treasury(&txn, vault.id).await?;
 
-        let treasury = treasuries::ActiveModel {
-            vault_id: Set(vault.id.clone()),
-            ..Default::default()
-        };
+            let project_treasuries_active_model = project_treasuries::ActiveModel {
+                project_id: Set(project_id),
+                treasury_id: Set(treasury.id),
+                organization_id: Set(Some(organization_id)),
+                ..Default::default()
+            };
 
-        let treasury: treasuries::Model = treasury.clone().insert(conn).await?;
+            project_treasuries_active_model.insert(&txn).await?;
 
-        let project_treasuries_active_model = project_treasuries::ActiveModel {
-            project_id: Set(Uuid::parse_str(&project.id)?),
-            treasury_id: Set(treasury.id),
-            ..Default::default()
+            txn.commit().await?;
+
+            treasury
         };
 
-        project_treasuries_active_model.insert(conn).await?;
+        let vault = self
+            .custody
+            .vault(treasury.vault_id.clone())
+            .await
+            .map_err(ProcessorError::Custody)?;
 
-        for id in self.fireblocks.assets().ids() {
-            let asset_type = AssetType::from_str(&id)?;
+        let recorded_wallets = wallets::Entity::find()
+            .filter(wallets::Column::TreasuryId.eq(treasury.id))
+            .all(conn)
+            .await?;
 
-            let vault_asset = self
-                .fireblocks
-                .client()
-                .create()
-                .wallet(treasury.vault_id.clone(), id, CreateVaultWallet {
-                    eos_account_name: None,
-                })
-                .await
-                .map_err(ProcessorError::Fireblocks)?;
+        for id in self.custody.asset_ids() {
+            let asset_type = AssetType::from_str(&id)?;
 
-            let active_model = wallets::ActiveModel {
-                treasury_id: Set(treasury.id),
-                asset_id: Set(asset_type),
-                address: Set(Some(vault_asset.address.clone())),
-                created_by: Set(user_id),
-                deduction_id: Set(None),
-                ..Default::default()
+            let address = if let Some(wallet) = recorded_wallets
+                .iter()
+                .find(|wallet| wallet.asset_id == asset_type)
+            {
+                wallet.address.clone()
+            } else {
+                let address = self
+                    .custody
+                    .find_or_create_wallet(&vault, id)
+                    .await
+                    .map_err(ProcessorError::Custody)?;
+
+                let active_model = wallets::ActiveModel {
+                    treasury_id: Set(treasury.id),
+                    asset_id: Set(asset_type),
+                    address: Set(Some(address.clone())),
+                    created_by: Set(user_id),
+                    deduction_id: Set(None),
+                    ..Default::default()
+                };
+
+                active_model.insert(conn).await?;
+
+                Some(address)
             };
 
-            active_model.insert(conn).await?;
+            let Some(address) = address else {
+                continue;
+            };
 
             let proto_blockchain_enum: proto::Blockchain = asset_type.into();
-            let project_id = project.id.to_string();
+            let project_id = project_id.to_string();
 
             let event = treasury_events::Event::ProjectWalletCreated(ProjectWallet {
                 project_id: project_id.clone(),
-                wallet_address: vault_asset.address,
+                wallet_address: address,
                 blockchain: proto_blockchain_enum as i32,
             });
 
@@ -108,22 +148,16 @@ impl FromStr for Blockchain {
     type Err = ProcessorError;
 
     fn from_str(value: &str) -> Result<Self> {
-        match value {
-            SOL | SOL_TEST => Ok(Blockchain::Solana),
-            MATIC | MATIC_TEST => Ok(Blockchain::Polygon),
-            ETH | ETH_TEST => Ok(Blockchain::Ethereum),
-            v => Err(ProcessorError::InvalidBlockchain(v.into())),
-        }
+        chains::by_symbol(value)
+            .or_else(|| chains::by_asset_id(value))
+            .map(|chain| chain.blockchain)
+            .ok_or_else(|| ProcessorError::InvalidBlockchain(value.into()))
     }
 }
 
 impl From<AssetType> for Blockchain {
     fn from(value: AssetType) -> Self {
-        match value {
-            AssetType::Solana => Blockchain::Solana,
-            AssetType::Matic => Blockchain::Polygon,
-            AssetType::Eth => Blockchain::Ethereum,
-        }
+        value.chain().blockchain
     }
 }
 
@@ -131,11 +165,8 @@ impl TryFrom<Blockchain> for AssetType {
     type Error = ProcessorError;
 
     fn try_from(value: Blockchain) -> Result<Self> {
-        match value {
-            Blockchain::Solana => Ok(AssetType::Solana),
-            Blockchain::Polygon => Ok(AssetType::Matic),
-            Blockchain::Ethereum => Ok(AssetType::Eth),
-            v @ Blockchain::Unspecified => Err(ProcessorError::InvalidBlockchain(format!("{v:?}"))),
-        }
+        chains::by_blockchain(value)
+            .map(|chain| chain.asset_type)
+            .ok_or_else(|| ProcessorError::InvalidBlockchain(format!("{value:?}")))
     }
 }
@@ -1,236 +1,137 @@
-use std::time::Instant;
-
-use fireblocks::objects::transaction::SignatureResponse;
-use hub_core::{metrics::KeyValue, prelude::*, producer::Producer};
-
-use super::{
-    signer::{find_vault_id_by_wallet_address, sign_message, Sign},
-    EcdsaSignatureScalar, Processor, ProcessorError, Result,
-};
-use crate::proto::{
-    polygon_nft_events::Event as PolygonNftEvent,
-    treasury_events::{
-        EcdsaSignature, Event, PolygonPermitHashSignature, PolygonTransactionResult,
-        TransactionStatus,
+//! Polygon has a schema of its own for its NFT service events. Its requests are signed by the
+//! shared [`Evm`] signer.
+
+use super::evm::{self, EventKind, Evm, Outcome, Request};
+use crate::{
+    chains::Chain,
+    proto::{
+        polygon_nft_events::Event as PolygonNftEvent,
+        treasury_events::{
+            EcdsaSignature, Event, PolygonPermitHashSignature, PolygonTransactionResult,
+        },
+        PermitArgsHash, PolygonNftEventKey, PolygonNftEvents, PolygonTokenTransferTxns,
+        PolygonTransaction, TreasuryEventKey,
     },
-    PermitArgsHash, PolygonNftEventKey, PolygonNftEvents, PolygonTokenTransferTxns,
-    PolygonTransaction, TreasuryEventKey, TreasuryEvents,
 };
 
-#[derive(Debug, Clone, Copy)]
-pub enum EventKind {
-    CreateDrop,
-    RetryCreateDrop,
-    UpdateDrop,
-    MintDrop,
-    RetryMintDrop,
-    TransferAsset,
-}
+/// The signer of Polygon.
+pub type Polygon<'a> = Evm<'a, Schema>;
 
-impl super::signer::EventKind<PolygonTransactionResult> for EventKind {
-    fn to_event(&self, txn: PolygonTransactionResult) -> Event {
-        match self {
-            EventKind::CreateDrop => Event::PolygonCreateDropTxnSubmitted(txn),
-            EventKind::RetryCreateDrop => Event::PolygonRetryCreateDropSubmitted(txn),
-            EventKind::UpdateDrop => Event::PolygonUpdateDropSubmitted(txn),
-            EventKind::MintDrop => Event::PolygonMintDropSubmitted(txn),
-            EventKind::RetryMintDrop => Event::PolygonRetryMintDropSubmitted(txn),
-            EventKind::TransferAsset => Event::PolygonTransferAssetSubmitted(txn),
-        }
-    }
-}
+/// The schema of Polygon's NFT service.
+pub struct Schema;
 
-pub struct Polygon<'a>(&'a Processor);
-
-impl<'a> Polygon<'a> {
-    #[inline]
-    #[must_use]
-    pub fn new(processor: &'a Processor) -> Self {
-        Self(processor)
-    }
-
-    /// Processes Polygon service events.
-    pub async fn process(&self, key: PolygonNftEventKey, e: PolygonNftEvents) -> Result<()> {
-        match e.event {
-            Some(PolygonNftEvent::SubmitCreateDropTxn(payload)) => {
-                self.send_and_notify(EventKind::CreateDrop, key, payload)
-                    .await?;
+impl evm::Schema for Schema {
+    type Events = PolygonNftEvents;
+    type Key = PolygonNftEventKey;
+    type PermitArgsHash = PermitArgsHash;
+    type TokenTransferTxns = PolygonTokenTransferTxns;
+    type Transaction = PolygonTransaction;
+    type TransactionResult = PolygonTransactionResult;
+
+    fn request(events: PolygonNftEvents) -> Option<Request<Self>> {
+        Some(match events.event? {
+            PolygonNftEvent::SubmitCreateDropTxn(payload) => {
+                Request::Submit(EventKind::CreateDrop, payload)
             },
-            Some(PolygonNftEvent::SubmitRetryCreateDropTxn(payload)) => {
-                self.send_and_notify(EventKind::RetryCreateDrop, key, payload)
-                    .await?;
+            PolygonNftEvent::SubmitRetryCreateDropTxn(payload) => {
+                Request::Submit(EventKind::RetryCreateDrop, payload)
             },
-            Some(PolygonNftEvent::SubmitMintDropTxn(payload)) => {
-                self.send_and_notify(EventKind::MintDrop, key, payload)
-                    .await?;
+            PolygonNftEvent::SubmitMintDropTxn(payload) => {
+                Request::Submit(EventKind::MintDrop, payload)
             },
-            Some(PolygonNftEvent::SubmitUpdateDropTxn(payload)) => {
-                self.send_and_notify(EventKind::UpdateDrop, key, payload)
-                    .await?;
+            PolygonNftEvent::SubmitUpdateDropTxn(payload) => {
+                Request::Submit(EventKind::UpdateDrop, payload)
             },
-
-            Some(PolygonNftEvent::SubmitRetryMintDropTxn(payload)) => {
-                self.send_and_notify(EventKind::RetryMintDrop, key, payload)
-                    .await?;
+            PolygonNftEvent::SubmitRetryMintDropTxn(payload) => {
+                Request::Submit(EventKind::RetryMintDrop, payload)
             },
-            Some(PolygonNftEvent::SignPermitTokenTransferHash(payload)) => {
-                self.sign_permit_token_transfer_hash(key, payload).await?;
+            PolygonNftEvent::SignPermitTokenTransferHash(payload) => {
+                Request::SignPermitTokenTransferHash(payload)
             },
-            Some(PolygonNftEvent::SubmitTransferAssetTxns(payload)) => {
-                self.submit_transfer_asset_txns(key, payload).await?;
+            PolygonNftEvent::SubmitTransferAssetTxns(payload) => {
+                Request::SubmitTransferAssetTxns(payload)
             },
-            Some(PolygonNftEvent::UpdateMintsOwner(_)) | None => (),
+            PolygonNftEvent::UpdateMintsOwner(_) => return None,
+        })
+    }
+
+    fn call_data(txn: &PolygonTransaction) -> Vec<u8> {
+        txn.data.clone()
+    }
+
+    fn transaction_result(
+        _chain: &Chain,
+        txn: PolygonTransaction,
+        Outcome {
+            hash,
+            status,
+            failure,
+        }: Outcome,
+    ) -> PolygonTransactionResult {
+        let PolygonTransaction {
+            contract_address,
+            edition_id,
+            ..
+        } = txn;
+
+        PolygonTransactionResult {
+            hash,
+            status,
+            contract_address,
+            edition_id,
+            failure,
         }
+    }
 
-        Ok(())
+    fn permit_hash(args: &PermitArgsHash) -> (String, Vec<u8>) {
+        (args.owner.clone(), args.data.clone())
     }
 
-    async fn sign_permit_token_transfer_hash(
-        &self,
-        key: PolygonNftEventKey,
-        payload: PermitArgsHash,
-    ) -> Result<()> {
+    fn permit_hash_signed(
+        _chain: &Chain,
+        args: PermitArgsHash,
+        signature: EcdsaSignature,
+    ) -> Event {
         let PermitArgsHash {
-            data,
             owner,
             spender,
             recipient,
             edition_id,
             amount,
-        } = payload;
-
-        let vault_id = find_vault_id_by_wallet_address(self.0.db.get(), owner.clone()).await?;
-        let signature = self.sign_message(String::new(), data, vault_id).await?;
+            ..
+        } = args;
 
-        let (r, s, v) = (
-            hex::decode(signature.r.ok_or(ProcessorError::IncompleteEcdsaSignature(
-                EcdsaSignatureScalar::R,
-            ))?)?,
-            hex::decode(signature.s.ok_or(ProcessorError::IncompleteEcdsaSignature(
-                EcdsaSignatureScalar::S,
-            ))?)?,
-            (signature.v.ok_or(ProcessorError::IncompleteEcdsaSignature(
-                EcdsaSignatureScalar::V,
-            ))? + 27)
-                .try_into()
-                .map_err(ProcessorError::InvalidEcdsaPubkeyRecovery)?,
-        );
-
-        let event = TreasuryEvents {
-            event: Some(Event::PolygonPermitTransferTokenHashSigned(
-                PolygonPermitHashSignature {
-                    signature: Some(EcdsaSignature { r, s, v }),
-                    owner,
-                    spender,
-                    recipient,
-                    edition_id,
-                    amount,
-                },
-            )),
-        };
-
-        self.0
-            .producer
-            .send(Some(&event), Some(&key.into()))
-            .await
-            .map_err(Into::into)
+        Event::PolygonPermitTransferTokenHashSigned(PolygonPermitHashSignature {
+            signature: Some(signature),
+            owner,
+            spender,
+            recipient,
+            edition_id,
+            amount,
+        })
     }
 
-    async fn submit_transfer_asset_txns(
-        &self,
-        key: PolygonNftEventKey,
-        payload: PolygonTokenTransferTxns,
-    ) -> Result<PolygonTransactionResult> {
-        let PolygonTokenTransferTxns {
-            permit_token_transfer_txn,
-            safe_transfer_from_txn,
-        } = payload;
-        let permit_txn_data =
-            permit_token_transfer_txn.ok_or(ProcessorError::MissingPermitTokenTransferTxn)?;
-        let safe_txn_data =
-            safe_transfer_from_txn.ok_or(ProcessorError::MissingSafeTransferFromTxn)?;
-
-        self.send_transaction(EventKind::TransferAsset, key.clone(), permit_txn_data)
-            .await?;
-
-        self.send_and_notify(EventKind::TransferAsset, key, safe_txn_data)
-            .await
+    fn transfer_txns(
+        txns: PolygonTokenTransferTxns,
+    ) -> (Option<PolygonTransaction>, Option<PolygonTransaction>) {
+        (txns.permit_token_transfer_txn, txns.safe_transfer_from_txn)
     }
 }
 
-#[async_trait]
-impl<'a> Sign for Polygon<'a> {
-    type EventKind = EventKind;
-    type Key = PolygonNftEventKey;
-    type Payload = PolygonTransaction;
-    type Signature = SignatureResponse;
-    type Transaction = PolygonTransactionResult;
-
-    const ASSET_ID: &'static str = "MATIC";
-
-    #[inline]
-    fn producer(&self) -> &Producer<TreasuryEvents> {
-        &self.0.producer
-    }
-
-    async fn sign_message(
-        &self,
-        note: String,
-        message: Vec<u8>,
-        vault_id: String,
-    ) -> Result<SignatureResponse> {
-        let start = Instant::now();
-
-        let sig = sign_message::<Self>(&self.0.fireblocks, note, message, vault_id).await;
-
-        let elapsed = i64::try_from(start.elapsed().as_millis()).unwrap_or(0);
-        self.0
-            .metrics
-            .sign_duration_ms_bucket
-            .record(elapsed, &[KeyValue::new("blockchain", "Polygon")]);
-
-        sig
+impl evm::Submitted for PolygonTransactionResult {
+    fn into_event(self, kind: EventKind) -> Event {
+        match kind {
+            EventKind::CreateDrop => Event::PolygonCreateDropTxnSubmitted(self),
+            EventKind::RetryCreateDrop => Event::PolygonRetryCreateDropSubmitted(self),
+            EventKind::UpdateDrop => Event::PolygonUpdateDropSubmitted(self),
+            EventKind::MintDrop => Event::PolygonMintDropSubmitted(self),
+            EventKind::RetryMintDrop => Event::PolygonRetryMintDropSubmitted(self),
+            EventKind::TransferAsset => Event::PolygonTransferAssetSubmitted(self),
+        }
     }
 
-    async fn send_transaction(
-        &self,
-        kind: EventKind,
-        key: PolygonNftEventKey,
-        payload: PolygonTransaction,
-    ) -> Result<PolygonTransactionResult> {
-        let note = format!(
-            "{kind:?} by {:?} for project {:?}",
-            key.user_id, key.project_id,
-        );
-        let vault = self.0.fireblocks.treasury_vault();
-        let asset_id = self.0.fireblocks.assets().id(Self::ASSET_ID);
-
-        let transaction = self
-            .0
-            .fireblocks
-            .client()
-            .create()
-            .contract_call(payload.data, asset_id, vault, note)
-            .await
-            .map_err(ProcessorError::Fireblocks)?;
-
-        let (hash, status) = self
-            .0
-            .fireblocks
-            .client()
-            .wait_on_transaction_completion(transaction.id)
-            .await
-            .map_or_else(
-                |_| (None, TransactionStatus::Failed as i32),
-                |details| (Some(details.tx_hash), details.status as i32),
-            );
-
-        Ok(PolygonTransactionResult {
-            hash,
-            status,
-            contract_address: payload.contract_address,
-            edition_id: payload.edition_id,
-        })
+    fn status(&self) -> i32 {
+        self.status
     }
 }
 
@@ -1,21 +1,37 @@
-use fireblocks::Fireblocks;
 use hub_core::{
+    consumer::RecvError,
+    credits::CreditsClient,
     prelude::*,
     producer::{Producer, SendError},
-    thiserror, uuid,
+    thiserror,
+    triage::{Severity, Triage},
+    uuid::{self, Uuid},
 };
 use sea_orm::DbErr;
 
-use super::{polygon::Polygon, solana::Solana};
+use super::{
+    ethereum::Ethereum,
+    evm::{Evm, EvmSchema},
+    polygon::Polygon,
+    solana::Solana,
+    GasStation,
+};
 use crate::{
+    chains::{self, Chain},
+    custody::{CustodyClient, TransactionStatus},
     db::Connection,
-    entities::wallets::TryIntoAssetTypeError,
+    dlq::DeadLetterQueue,
+    entities::{
+        dead_letters,
+        wallets::{AssetType, TryIntoAssetTypeError},
+    },
     metrics::Metrics,
     proto::{
         customer_events::Event as CustomerEvent, organization_events::Event as OrganizationEvent,
-        TreasuryEvents,
+        treasury_events::TransactionFailure, TreasuryEvents,
     },
-    Services,
+    provision::WalletSaga,
+    Actions, Services,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -35,7 +51,7 @@ impl fmt::Display for EcdsaSignatureScalar {
     }
 }
 
-#[derive(Debug, thiserror::Error, Triage)]
+#[derive(Debug, thiserror::Error, Triage, strum::IntoStaticStr)]
 pub enum ProcessorError {
     #[error("No treasury found for wallet address {0:?}")]
     InvalidWalletAddress(String),
@@ -51,18 +67,37 @@ pub enum ProcessorError {
     MissingSignedMessage,
     #[error("Invalid number of signer pubkeys")]
     InvalidNumberOfSigners,
+    #[error("{0:?} is not enabled for the project")]
+    #[permanent]
+    ChainDisabled(AssetType),
+
+    #[error("Transaction {0} is awaiting completion")]
+    TransactionPending(Uuid),
+    #[error("Transaction {0} ended with status {1:?} {2:?}")]
+    TransactionFailed(Uuid, TransactionStatus, String),
+    #[error("Transaction {0} was still pending when the wait deadline passed")]
+    TransactionTimedOut(Uuid),
 
     #[error("Invalid ECDSA pubkey recovery scalar")]
     #[permanent]
     InvalidEcdsaPubkeyRecovery(#[source] std::num::TryFromIntError),
-    #[error("Fireblocks error")]
+    #[error("Custody error")]
+    #[transient]
+    Custody(#[source] Error),
+    #[error("Error provisioning customer wallet")]
     #[transient]
-    Fireblocks(#[source] Error),
+    WalletProvisioning(#[source] Error),
     #[error("Invalid UUID")]
     InvalidUuid(#[from] uuid::Error),
     #[error("Invalid hex string")]
     #[permanent]
     InvalidHex(#[from] hex::FromHexError),
+    #[error("Invalid message stored in the processed events ledger")]
+    #[permanent]
+    InvalidStoredMessage(#[from] prost::DecodeError),
+    #[error("Invalid deferred message")]
+    #[permanent]
+    InvalidDeferredMessage(#[from] RecvError),
     #[error("Invalid asset type")]
     #[permanent]
     InvalidAssetType(#[from] TryIntoAssetTypeError),
@@ -70,16 +105,66 @@ pub enum ProcessorError {
     DbError(#[from] DbErr),
     #[error("Error sending message")]
     SendError(#[from] SendError),
+    #[error("Error recording dead letter")]
+    #[transient]
+    DeadLetter(#[source] Error),
 }
 
 pub type Result<T> = std::result::Result<T, ProcessorError>;
 
+impl ProcessorError {
+    /// Whether submitting the signing request again may succeed.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::TransactionPending(_)
+            | Self::TransactionTimedOut(_)
+            | Self::Custody(_)
+            | Self::DbError(_)
+            | Self::SendError(_) => true,
+            Self::TransactionFailed(_, status, _) => *status == TransactionStatus::Failed,
+            _ => false,
+        }
+    }
+
+    /// Whether processing the message again can never succeed, as triaged by the `#[permanent]`
+    /// attributes of the error. Messages failing with a permanent error are moved to the
+    /// dead-letter queue.
+    #[must_use]
+    pub fn is_permanent(&self) -> bool {
+        matches!(self.triage(), Severity::Permanent)
+    }
+
+    /// Describes the error to the services waiting on the result of a signing request.
+    #[must_use]
+    pub fn failure(&self) -> TransactionFailure {
+        let (fireblocks_tx_id, sub_status) = match self {
+            Self::TransactionPending(id) | Self::TransactionTimedOut(id) => (Some(id), None),
+            Self::TransactionFailed(id, _, sub_status) => {
+                (Some(id), Some(sub_status.clone()).filter(|s| !s.is_empty()))
+            },
+            _ => (None, None),
+        };
+
+        TransactionFailure {
+            error_kind: <&'static str>::from(self).to_string(),
+            message: self.to_string(),
+            sub_status,
+            fireblocks_tx_id: fireblocks_tx_id.map(ToString::to_string),
+            retryable: self.is_retryable(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Processor {
     pub db: Connection,
-    pub fireblocks: Fireblocks,
+    pub custody: CustodyClient,
     pub producer: Producer<TreasuryEvents>,
+    pub credits: CreditsClient<Actions>,
     pub metrics: Metrics,
+    pub dlq: DeadLetterQueue,
+    pub gas_station: GasStation,
 }
 
 impl Processor {
@@ -87,21 +172,92 @@ impl Processor {
     pub fn new(
         db: Connection,
         producer: Producer<TreasuryEvents>,
-        fireblocks: Fireblocks,
+        custody: CustodyClient,
+        credits: CreditsClient<Actions>,
         metrics: Metrics,
+        dlq: DeadLetterQueue,
+        gas_station: GasStation,
     ) -> Self {
         Self {
             db,
-            fireblocks,
+            custody,
             producer,
+            credits,
             metrics,
+            dlq,
+            gas_station,
         }
     }
 
     /// Processes a message from the event stream.
+    /// Messages waiting on a custody transaction are deferred to the tracker so the consumer is not blocked.
+    /// Messages that can never be processed are moved to the dead-letter queue.
     /// # Errors
     /// Returns an error if the message cannot be processed.
     pub async fn process(&self, msg: Services) -> Result<()> {
+        match self.dispatch(msg.clone()).await {
+            Err(ProcessorError::TransactionPending(fireblocks_id)) => {
+                self.defer(&msg, fireblocks_id).await
+            },
+            Err(e) if e.is_permanent() => self.dead_letter(&msg, &e).await,
+            res => res,
+        }
+    }
+
+    /// Publishes a message that failed permanently to the dead-letter queue.
+    pub(crate) async fn dead_letter(&self, msg: &Services, e: &ProcessorError) -> Result<()> {
+        let (message_key, message_payload) = msg.encode();
+
+        self.dlq
+            .publish(
+                msg.topic(),
+                message_key,
+                message_payload,
+                format!("{e:?}"),
+                <&'static str>::from(e).to_string(),
+            )
+            .await
+            .map_err(ProcessorError::DeadLetter)?;
+
+        Ok(())
+    }
+
+    /// Marks a dead letter as replayed and processes its message again. A dead letter is replayed at
+    /// most once: `None` is returned if it does not exist or was already replayed. A message that
+    /// fails permanently again is recorded as a new dead letter.
+    ///
+    /// # Errors
+    /// Returns an error if the message cannot be decoded, fails with an error that is not
+    /// permanent or the dead letter cannot be updated. The dead letter can be replayed again then.
+    pub async fn replay_dead_letter(&self, id: Uuid) -> Result<Option<dead_letters::Model>> {
+        let Some(letter) = self
+            .dlq
+            .claim_replay(id)
+            .await
+            .map_err(ProcessorError::DeadLetter)?
+        else {
+            return Ok(None);
+        };
+
+        let processed =
+            match Services::decode(&letter.topic, &letter.message_key, &letter.message_payload) {
+                Ok(msg) => self.process(msg).await,
+                Err(e) => Err(e.into()),
+            };
+
+        if let Err(e) = processed {
+            self.dlq
+                .release_replay(id)
+                .await
+                .map_err(ProcessorError::DeadLetter)?;
+
+            return Err(e);
+        }
+
+        Ok(Some(letter))
+    }
+
+    pub(crate) async fn dispatch(&self, msg: Services) -> Result<()> {
         // match topics
         match msg {
             Services::Customers(key, e) => match e.event {
@@ -115,10 +271,23 @@ impl Processor {
                 Some(_) | None => Ok(()),
             },
             Services::Polygon(key, e) => self.polygon().process(key, e).await,
+            Services::Ethereum(key, e) => self.ethereum().process(key, e).await,
+            Services::Evm(chain, key, e) => self.evm(chain).process(key, e).await,
             Services::Solana(key, e) => self.solana().process(key, e).await,
         }
     }
 
+    /// The saga creating customer wallets with the clients of the processor.
+    #[must_use]
+    pub fn wallet_saga(&self) -> WalletSaga {
+        WalletSaga::new(
+            self.db.clone(),
+            self.custody.clone(),
+            self.credits.clone(),
+            self.producer.clone(),
+        )
+    }
+
     #[inline]
     fn solana(&self) -> Solana {
         Solana::new(self)
@@ -126,6 +295,16 @@ impl Processor {
 
     #[inline]
     fn polygon(&self) -> Polygon {
-        Polygon::new(self)
+        Polygon::new(self, chains::chain(AssetType::Matic))
+    }
+
+    #[inline]
+    fn ethereum(&self) -> Ethereum {
+        Ethereum::new(self, chains::chain(AssetType::Eth))
+    }
+
+    #[inline]
+    fn evm(&self, chain: &'static Chain) -> Evm<EvmSchema> {
+        Evm::new(self, chain)
     }
 }
@@ -0,0 +1,37 @@
+use hub_core::uuid::Uuid;
+use sea_orm::prelude::*;
+
+use super::{Processor, ProcessorError, Result};
+use crate::entities::{project_settings, wallets::AssetType};
+
+impl Processor {
+    /// Loads the custody settings of a project, falling back to the defaults of the service.
+    pub(crate) async fn project_settings(
+        &self,
+        project_id: Uuid,
+    ) -> Result<project_settings::Model> {
+        let settings = project_settings::Entity::find_by_id(project_id)
+            .one(self.db.get())
+            .await?;
+
+        Ok(settings.unwrap_or_else(|| project_settings::Model::defaults(project_id)))
+    }
+
+    /// Loads the custody settings of a project that is about to transact with `asset_type`.
+    ///
+    /// # Errors
+    /// Returns [`ProcessorError::ChainDisabled`] if the project has not enabled the asset's chain.
+    pub(crate) async fn enabled_project_settings(
+        &self,
+        project_id: Uuid,
+        asset_type: AssetType,
+    ) -> Result<project_settings::Model> {
+        let settings = self.project_settings(project_id).await?;
+
+        if settings.is_enabled(asset_type) {
+            Ok(settings)
+        } else {
+            Err(ProcessorError::ChainDisabled(asset_type))
+        }
+    }
+}
@@ -1,15 +1,71 @@
-use fireblocks::{objects::transaction::SignatureResponse, Fireblocks};
-use hub_core::{prelude::*, producer::Producer};
-use sea_orm::{prelude::*, DatabaseConnection, JoinType, QueryFilter, QuerySelect, RelationTrait};
+use std::future::Future;
 
-use super::{ProcessorError, Result};
+use hub_core::{anyhow, chrono::Utc, prelude::*, uuid::Uuid};
+use sea_orm::{
+    prelude::*, sea_query::OnConflict, Condition, DatabaseConnection, JoinType, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, Set, Statement, TransactionTrait,
+};
+
+use super::{
+    ledger::{digest, LedgerEntry},
+    Processor, ProcessorError, Result,
+};
 use crate::{
-    entities::{treasuries, wallets},
+    custody::{Signature, StatusClass, SubmittedTransaction, Transaction, WaitPolicy},
+    entities::{
+        sea_orm_active_enums::TxType,
+        transaction_status_changes, transactions, treasuries,
+        wallets::{self, AssetType},
+    },
     proto::{treasury_events::Event, TreasuryEventKey, TreasuryEvents},
 };
 
 pub trait EventKind<T>: Copy {
     fn to_event(&self, txn: T) -> Event;
+
+    fn tx_type(&self) -> TxType;
+}
+
+/// The event a custody transaction is submitted for.
+#[derive(Debug, Clone)]
+pub struct TxContext {
+    pub entry: LedgerEntry,
+    pub project_id: Uuid,
+    pub tx_type: TxType,
+}
+
+impl TxContext {
+    pub(crate) fn new(
+        entry: &LedgerEntry,
+        key: impl Into<TreasuryEventKey>,
+        tx_type: TxType,
+    ) -> Result<Self> {
+        let TreasuryEventKey { project_id, .. } = key.into();
+
+        Ok(Self {
+            entry: entry.clone(),
+            project_id: Uuid::from_str(&project_id)?,
+            tx_type,
+        })
+    }
+
+    /// The id sent to the custodian with a transaction so that redelivered events never
+    /// submit the same transaction twice.
+    pub(crate) fn external_tx_id(&self, vault_id: &str, content: &[u8]) -> String {
+        let LedgerEntry {
+            event_id,
+            event_kind,
+            payload_digest,
+        } = &self.entry;
+
+        digest([
+            event_id.as_bytes(),
+            event_kind.as_bytes(),
+            payload_digest.as_bytes(),
+            vault_id.as_bytes(),
+            content,
+        ])
+    }
 }
 
 #[async_trait]
@@ -17,15 +73,22 @@ pub trait Sign {
     type EventKind: fmt::Debug + Send + EventKind<Self::Transaction>;
     type Signature;
     type Key: Clone + Into<TreasuryEventKey> + Send;
-    type Payload: Send;
+    type Payload: Message + Send;
     type Transaction: Clone + Send;
 
-    const ASSET_ID: &'static str;
+    fn processor(&self) -> &Processor;
+
+    /// The asset type of the wallets the signer signs with.
+    fn asset_type(&self) -> AssetType;
 
-    fn producer(&self) -> &Producer<TreasuryEvents>;
+    /// Whether the transaction completed. Only completed transactions are recorded in the
+    /// processed events ledger, failed ones are submitted again as a new attempt when the event is
+    /// redelivered.
+    fn succeeded(txn: &Self::Transaction) -> bool;
 
     async fn sign_message(
         &self,
+        ctx: &TxContext,
         note: String,
         message: Vec<u8>,
         vault_id: String,
@@ -35,57 +98,97 @@ pub trait Sign {
         &self,
         kind: Self::EventKind,
         key: Self::Key,
-        txn: Self::Payload,
-    ) -> Result<Self::Transaction> {
-        let k = key.clone();
-        let txn = self.send_transaction(kind, k, txn).await?;
-
-        let evt = kind.to_event(txn.clone());
-        self.producer()
-            .send(
-                Some(&TreasuryEvents { event: Some(evt) }),
-                Some(&key.into()),
-            )
+        payload: Self::Payload,
+    ) -> Result<()> {
+        let entry = LedgerEntry::new(key.clone(), format!("{kind:?}"), &payload);
+
+        if self.processor().replay(&entry).await? {
+            return Ok(());
+        }
+
+        let ctx = TxContext::new(&entry, key.clone(), kind.tx_type())?;
+        let txn = self
+            .send_transaction(&ctx, kind, key.clone(), payload)
             .await?;
 
-        Ok(txn)
+        self.notify(&entry, kind, key, txn).await
+    }
+
+    /// Emits the event for a signed transaction, recording it in the ledger if it succeeded.
+    async fn notify(
+        &self,
+        entry: &LedgerEntry,
+        kind: Self::EventKind,
+        key: Self::Key,
+        txn: Self::Transaction,
+    ) -> Result<()> {
+        let succeeded = Self::succeeded(&txn);
+        let event = TreasuryEvents {
+            event: Some(kind.to_event(txn)),
+        };
+
+        if succeeded {
+            self.processor()
+                .emit(entry, vec![(key.into(), event)])
+                .await
+        } else {
+            self.processor()
+                .producer
+                .send(Some(&event), Some(&key.into()))
+                .await
+                .map_err(Into::into)
+        }
     }
 
     async fn send_transaction(
         &self,
+        ctx: &TxContext,
         kind: Self::EventKind,
         key: Self::Key,
         payload: Self::Payload,
     ) -> Result<Self::Transaction>;
 }
 
-pub(crate) async fn sign_message<G: Sign>(
-    fireblocks: &Fireblocks,
+/// Signs a raw message with the vault. `pays_fees` is set when the vault pays the network fee of
+/// the signed transaction, such as the fee payer of a Solana transaction, so that it is fueled first.
+pub(crate) async fn sign_message<G: Sign + Sync>(
+    signer: &G,
+    ctx: &TxContext,
     note: String,
     message: Vec<u8>,
     vault_id: String,
-) -> Result<SignatureResponse> {
-    let asset_id = fireblocks.assets().id(G::ASSET_ID);
+    pays_fees: bool,
+) -> Result<Signature> {
+    let processor = signer.processor();
+    let asset_id = processor.custody.asset_id(signer.asset_type().as_str());
+    let external_tx_id = ctx.external_tx_id(&vault_id, &message);
 
-    let transaction = fireblocks
-        .client()
-        .create()
-        .raw_transaction(asset_id, vault_id, vec![message], note)
-        .await
-        .map_err(ProcessorError::Fireblocks)?;
+    let submit = |external_tx_id| {
+        processor.custody.sign_raw(
+            asset_id.clone(),
+            vault_id.clone(),
+            vec![message],
+            note,
+            external_tx_id,
+        )
+    };
 
-    let details = fireblocks
-        .client()
-        .wait_on_transaction_completion(transaction.id)
-        .await
-        .map_err(ProcessorError::Fireblocks)?;
+    let details = submit_transaction(
+        processor,
+        ctx,
+        vault_id.clone(),
+        asset_id.clone(),
+        external_tx_id,
+        pays_fees,
+        submit,
+    )
+    .await?;
 
     Ok(details
-        .signed_messages
+        .signatures
         .get(0)
         .ok_or(ProcessorError::MissingSignedMessage)?
-        .clone()
-        .signature)
+        .clone())
 }
 
 pub(crate) async fn find_vault_id_by_wallet_address(
@@ -101,3 +204,203 @@ pub(crate) async fn find_vault_id_by_wallet_address(
 
     Ok(treasury.vault_id)
 }
+
+/// Submits a transaction to the custodian unless one was already recorded for `external_tx_id`
+/// and checks on its state without waiting for it to complete.
+///
+/// A recorded transaction that failed, was rejected or was cancelled is not reused. The
+/// transaction is submitted again as a new attempt, with the number of earlier attempts appended
+/// to the external transaction id since the custodian rejects ids it has seen before. `submit`
+/// receives the external transaction id of the attempt. Custody backends return the transaction
+/// an id was already used for instead of failing, so an attempt submitted by a process that stopped
+/// before recording it is recorded by the next one.
+///
+/// The vault is fueled by the gas station before submitting when `pays_fees` is set. Signing
+/// requests whose fee is paid by another account, such as permit hashes, leave the vault as is.
+///
+/// # Errors
+/// Returns [`ProcessorError::TransactionPending`] while the transaction awaits approval or
+/// signing. The event is then deferred and processed again by the tracker once the transaction
+/// completes. Returns [`ProcessorError::TransactionTimedOut`] once it has been pending for longer
+/// than the deadline of the custody [`WaitPolicy`], cancelling it first if the policy says so.
+pub(crate) async fn submit_transaction<F>(
+    processor: &Processor,
+    ctx: &TxContext,
+    vault_id: String,
+    asset_id: String,
+    external_tx_id: String,
+    pays_fees: bool,
+    submit: impl FnOnce(String) -> F + Send,
+) -> Result<Transaction>
+where
+    F: Future<Output = anyhow::Result<SubmittedTransaction>> + Send,
+{
+    let conn = processor.db.get();
+    let policy = processor.custody.wait_policy();
+
+    // Attempts for the same external transaction id are counted and submitted by one replica at a
+    // time, so that concurrent deliveries agree on the id of the next attempt. The lock is released
+    // when the transaction holding it ends, including on errors.
+    let lock = conn.begin().await?;
+
+    lock.execute(Statement::from_sql_and_values(
+        lock.get_database_backend(),
+        "SELECT pg_advisory_xact_lock(hashtext($1))",
+        [external_tx_id.clone().into()],
+    ))
+    .await?;
+
+    let attempts = transactions::Entity::find()
+        .filter(
+            Condition::any()
+                .add(transactions::Column::ExternalTxId.eq(external_tx_id.clone()))
+                .add(transactions::Column::ExternalTxId.like(format!("{external_tx_id}:%"))),
+        )
+        .order_by_desc(transactions::Column::CreatedAt)
+        .all(conn)
+        .await?;
+
+    let latest = match attempts.first() {
+        Some(recorded) => {
+            let (record, details) = refresh_transaction(processor, recorded.fireblocks_id).await?;
+
+            if matches!(policy.classify(details.status), StatusClass::Failure) {
+                info!(
+                    "transaction {} ended as {:?}, submitting it again",
+                    recorded.fireblocks_id, details.status
+                );
+
+                None
+            } else {
+                Some((record, details))
+            }
+        },
+        None => None,
+    };
+
+    let (record, details) = if let Some(latest) = latest {
+        latest
+    } else {
+        let external_tx_id = if attempts.is_empty() {
+            external_tx_id
+        } else {
+            format!("{external_tx_id}:{}", attempts.len())
+        };
+
+        if pays_fees {
+            processor
+                .fuel(ctx, &vault_id, &asset_id, &external_tx_id)
+                .await?;
+        }
+
+        let transaction = submit(external_tx_id.clone())
+            .await
+            .map_err(ProcessorError::Custody)?;
+        let fireblocks_id = Uuid::from_str(&transaction.id)?;
+
+        let active_model = transactions::ActiveModel {
+            fireblocks_id: Set(fireblocks_id),
+            tx_type: Set(ctx.tx_type.clone()),
+            signatures: Set(Vec::new()),
+            event_id: Set(ctx.entry.event_id.clone()),
+            project_id: Set(ctx.project_id),
+            vault_id: Set(vault_id),
+            asset_id: Set(asset_id),
+            status: Set(transaction.status.into()),
+            external_tx_id: Set(Some(external_tx_id)),
+            event_kind: Set(Some(ctx.entry.event_kind.clone())),
+            ..Default::default()
+        };
+
+        transactions::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(transactions::Column::FireblocksId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
+
+        transaction_status_changes::record(conn, fireblocks_id, transaction.status.into(), None)
+            .await?;
+
+        refresh_transaction(processor, fireblocks_id).await?
+    };
+
+    lock.commit().await?;
+
+    let fireblocks_id = record.fireblocks_id;
+
+    match policy.classify(details.status) {
+        StatusClass::Success => Ok(details),
+        StatusClass::Failure => Err(ProcessorError::TransactionFailed(
+            fireblocks_id,
+            details.status,
+            details.sub_status,
+        )),
+        StatusClass::Pending if deadline_passed(&policy, &record) => {
+            if policy.cancel_on_timeout {
+                let cancelled = processor
+                    .custody
+                    .cancel_transaction(fireblocks_id.to_string())
+                    .await
+                    .map_err(ProcessorError::Custody)?;
+
+                info!("transaction {fireblocks_id} timed out, cancelled: {cancelled}");
+            }
+
+            Err(ProcessorError::TransactionTimedOut(fireblocks_id))
+        },
+        StatusClass::Pending => Err(ProcessorError::TransactionPending(fireblocks_id)),
+    }
+}
+
+/// Whether a transaction has been pending for longer than the deadline of the wait policy.
+pub(crate) fn deadline_passed(policy: &WaitPolicy, record: &transactions::Model) -> bool {
+    let elapsed = Utc::now() - record.created_at.with_timezone(&Utc);
+
+    elapsed.to_std().unwrap_or_default() >= policy.deadline
+}
+
+/// Fetches the latest state of a transaction from the custodian and records its status, hash
+/// and signatures, adding the status to the transaction's history when it changed.
+pub(crate) async fn refresh_transaction(
+    processor: &Processor,
+    fireblocks_id: Uuid,
+) -> Result<(transactions::Model, Transaction)> {
+    let details = processor
+        .custody
+        .transaction(fireblocks_id.to_string())
+        .await
+        .map_err(ProcessorError::Custody)?;
+
+    let sub_status = Some(details.sub_status.clone()).filter(|sub_status| !sub_status.is_empty());
+
+    let active_model = transactions::ActiveModel {
+        fireblocks_id: Set(fireblocks_id),
+        status: Set(details.status.into()),
+        sub_status: Set(sub_status.clone()),
+        tx_hash: Set(Some(details.tx_hash.clone()).filter(|hash| !hash.is_empty())),
+        signatures: Set(details
+            .signatures
+            .iter()
+            .map(|signature| signature.full_sig.clone())
+            .collect()),
+        updated_at: Set(Some(Utc::now().into())),
+        ..Default::default()
+    };
+
+    let record = transactions::Entity::update(active_model)
+        .exec(processor.db.get())
+        .await?;
+
+    transaction_status_changes::record(
+        processor.db.get(),
+        fireblocks_id,
+        details.status.into(),
+        sub_status,
+    )
+    .await?;
+
+    Ok((record, details))
+}
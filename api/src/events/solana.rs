@@ -1,20 +1,21 @@
 use std::{collections::HashMap, time::Instant};
 
 use hex::FromHex;
-use hub_core::{
-    bs58, futures_util::future, metrics::KeyValue, prelude::*, producer::Producer,
-    util::ValidateAddress,
-};
+use hub_core::{bs58, futures_util::future, metrics::KeyValue, prelude::*, util::ValidateAddress};
 
 use super::{
-    signer::{find_vault_id_by_wallet_address, sign_message, Sign},
+    ledger::LedgerEntry,
+    signer::{find_vault_id_by_wallet_address, sign_message, submit_transaction, Sign, TxContext},
     Processor, ProcessorError, Result,
 };
-use crate::proto::{
-    solana_nft_events::Event as SolanaNftEvent,
-    treasury_events::{Event, SolanaTransactionResult, TransactionStatus},
-    SolanaMintPendingTransactions, SolanaMintTransaction, SolanaNftEventKey, SolanaNftEvents,
-    SolanaPendingTransaction, TreasuryEventKey, TreasuryEvents,
+use crate::{
+    entities::{sea_orm_active_enums::TxType, wallets::AssetType},
+    proto::{
+        solana_nft_events::Event as SolanaNftEvent,
+        treasury_events::{Event, SolanaTransactionResult, TransactionStatus},
+        SolanaMintPendingTransactions, SolanaMintTransaction, SolanaNftEventKey, SolanaNftEvents,
+        SolanaPendingTransaction, TreasuryEventKey, TreasuryEvents,
+    },
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -66,6 +67,32 @@ impl super::signer::EventKind<SolanaTransactionResult> for EventKind {
             EventKind::RetryMintOpenDrop => Event::SolanaRetryMintOpenDropSigned(txn),
         }
     }
+
+    fn tx_type(&self) -> TxType {
+        match self {
+            EventKind::CreateEditionDrop
+            | EventKind::RetryCreateEditionDrop
+            | EventKind::CreateOpenDrop
+            | EventKind::RetryCreateOpenDrop => TxType::CreateDrop,
+            EventKind::UpdateEditionDrop
+            | EventKind::UpdateCollection
+            | EventKind::UpdateCollectionMint
+            | EventKind::RetryUpdateCollectionMint
+            | EventKind::UpdateOpenDrop => TxType::UpdateMetadata,
+            EventKind::MintEditionDrop
+            | EventKind::RetryMintEditionDrop
+            | EventKind::MintOpenDrop
+            | EventKind::RetryMintOpenDrop => TxType::MintEdition,
+            EventKind::TransferAsset => TxType::TransferMint,
+            EventKind::CreateCollection | EventKind::RetryCreateCollection => {
+                TxType::CreateCollection
+            },
+            EventKind::MintToCollection | EventKind::RetryMintToCollection => {
+                TxType::MintToCollection
+            },
+            EventKind::SwitchCollection => TxType::SwitchCollection,
+        }
+    }
 }
 
 pub struct Solana<'a>(&'a Processor);
@@ -164,19 +191,51 @@ impl<'a> Solana<'a> {
         Ok(())
     }
 
+    /// Signs a message with the vault, fueling the vault first when it is the fee payer.
+    async fn sign(
+        &self,
+        ctx: &TxContext,
+        note: String,
+        message: Vec<u8>,
+        vault_id: String,
+        pays_fees: bool,
+    ) -> Result<String> {
+        let start = Instant::now();
+
+        let sig = sign_message(self, ctx, note, message, vault_id, pays_fees).await?;
+        let sig = <[u8; 64]>::from_hex(sig.full_sig)?;
+        let sig = bs58::encode(sig).into_string();
+
+        let elapsed = i64::try_from(start.elapsed().as_millis()).unwrap_or(0);
+        self.0
+            .metrics
+            .sign_duration_ms_bucket
+            .record(elapsed, &[KeyValue::new("blockchain", "Solana")]);
+
+        Ok(sig)
+    }
+
     pub async fn sign_mint_batch(
         &self,
         key: SolanaNftEventKey,
         payload: SolanaMintPendingTransactions,
     ) -> Result<()> {
         let conn = self.0.db.get();
-        let fireblocks = &self.0.fireblocks;
+        let custody = &self.0.custody;
         let pubkeys = payload.signers_pubkeys.clone();
 
         if pubkeys.len() != 2 {
             return Err(ProcessorError::InvalidNumberOfSigners);
         }
 
+        let entry = LedgerEntry::new(key.clone(), "MintOpenDropBatched", &payload);
+
+        if self.0.replay(&entry).await? {
+            return Ok(());
+        }
+
+        let ctx = &TxContext::new(&entry, key.clone(), TxType::MintEdition)?;
+
         let note = &format!(
             "Mint batch signing for collection {:?} by {:?} for project {:?}",
             key.id, key.user_id, key.project_id,
@@ -189,44 +248,62 @@ impl<'a> Solana<'a> {
             .map(|m| m.serialized_message)
             .collect::<Vec<_>>();
 
-        let tx = |vault: String| async move {
-            let asset_id = fireblocks.assets().id("SOL");
-
-            let transaction = fireblocks
-                .client()
-                .create()
-                .raw_transaction(asset_id, vault, messages.clone(), note.to_string())
-                .await
-                .map_err(ProcessorError::Fireblocks)?;
+        let tx = |vault: String, pays_fees: bool| async move {
+            let asset_id = custody.asset_id(AssetType::Solana.as_str());
+            let external_tx_id = ctx.external_tx_id(&vault, &messages.concat());
+
+            let submit = |external_tx_id| {
+                custody.sign_raw(
+                    asset_id.clone(),
+                    vault.clone(),
+                    messages.clone(),
+                    note.to_string(),
+                    external_tx_id,
+                )
+            };
+
+            submit_transaction(
+                self.0,
+                ctx,
+                vault.clone(),
+                asset_id.clone(),
+                external_tx_id,
+                pays_fees,
+                submit,
+            )
+            .await
+        };
 
-            let details = fireblocks
-                .client()
-                .wait_on_transaction_completion(transaction.id)
-                .await
-                .map_err(ProcessorError::Fireblocks)?;
+        // The first signer of the batched transactions pays their fees.
+        let futures = pubkeys
+            .into_iter()
+            .enumerate()
+            .map(|(i, req_sig)| async move {
+                let vault_id = find_vault_id_by_wallet_address(conn, req_sig).await?;
 
-            Result::<_>::Ok(details)
-        };
+                tx(vault_id, i == 0).await
+            });
 
-        let mut futures = Vec::new();
+        let futs_result = async {
+            self.0
+                .enabled_project_settings(ctx.project_id, AssetType::Solana)
+                .await?;
 
-        for req_sig in pubkeys.clone() {
-            let vault_id = find_vault_id_by_wallet_address(conn, req_sig).await?;
-            futures.push(tx(vault_id));
+            future::join_all(futures)
+                .await
+                .into_iter()
+                .map(|r| r.map(|d| d.signatures))
+                .collect::<Result<Vec<_>>>()
         }
-
-        let futs_result = future::join_all(futures)
-            .await
-            .into_iter()
-            .map(|r| r.map(|d| d.signed_messages))
-            .collect::<Result<Vec<_>>>();
+        .await;
 
         match futs_result {
             Ok(results) => {
+                let mut events = Vec::new();
                 let mut hashmap = HashMap::new();
                 for messages in results {
                     for msg in messages {
-                        let bytes = <[u8; 64]>::from_hex(msg.signature.full_sig)?;
+                        let bytes = <[u8; 64]>::from_hex(msg.full_sig)?;
                         let signature = bs58::encode(bytes).into_string();
 
                         hashmap
@@ -261,22 +338,24 @@ impl<'a> Solana<'a> {
                         serialized_message: Some(serialized_message),
                         signed_message_signatures: signatures,
                         status: TransactionStatus::Completed.into(),
+                        failure: None,
                     };
 
                     let evt = Event::SolanaMintOpenDropSigned(txn);
-                    self.producer()
-                        .send(
-                            Some(&TreasuryEvents { event: Some(evt) }),
-                            Some(&TreasuryEventKey {
-                                id: mint_id,
-                                user_id: key.user_id,
-                                project_id: key.project_id,
-                            }),
-                        )
-                        .await?;
+                    events.push((
+                        TreasuryEventKey {
+                            id: mint_id,
+                            user_id: key.user_id,
+                            project_id: key.project_id,
+                        },
+                        TreasuryEvents { event: Some(evt) },
+                    ));
                 }
+
+                self.0.emit(&entry, events).await?;
             },
 
+            Err(e @ ProcessorError::TransactionPending(_)) => return Err(e),
             Err(e) => {
                 error!("Error signing mint batch: {:?}", e);
 
@@ -284,12 +363,14 @@ impl<'a> Solana<'a> {
                     serialized_message: None,
                     signed_message_signatures: vec![],
                     status: TransactionStatus::Failed.into(),
+                    failure: Some(e.failure()),
                 };
 
                 for tx in payload.mint_transactions {
                     let evt = Event::SolanaMintOpenDropSigned(txn.clone());
                     let key = key.clone();
-                    self.producer()
+                    self.0
+                        .producer
                         .send(
                             Some(&TreasuryEvents { event: Some(evt) }),
                             Some(&TreasuryEventKey {
@@ -315,36 +396,33 @@ impl<'a> Sign for Solana<'a> {
     type Signature = String;
     type Transaction = SolanaTransactionResult;
 
-    const ASSET_ID: &'static str = "SOL";
+    #[inline]
+    fn processor(&self) -> &Processor {
+        self.0
+    }
 
     #[inline]
-    fn producer(&self) -> &Producer<TreasuryEvents> {
-        &self.0.producer
+    fn asset_type(&self) -> AssetType {
+        AssetType::Solana
+    }
+
+    fn succeeded(txn: &SolanaTransactionResult) -> bool {
+        txn.status == TransactionStatus::Completed as i32
     }
 
     async fn sign_message(
         &self,
+        ctx: &TxContext,
         note: String,
         message: Vec<u8>,
         vault_id: String,
     ) -> Result<String> {
-        let start = Instant::now();
-
-        let sig = sign_message::<Self>(&self.0.fireblocks, note, message, vault_id).await?;
-        let sig = <[u8; 64]>::from_hex(sig.full_sig)?;
-        let sig = bs58::encode(sig).into_string();
-
-        let elapsed = i64::try_from(start.elapsed().as_millis()).unwrap_or(0);
-        self.0
-            .metrics
-            .sign_duration_ms_bucket
-            .record(elapsed, &[KeyValue::new("blockchain", "Solana")]);
-
-        Ok(sig)
+        self.sign(ctx, note, message, vault_id, false).await
     }
 
     async fn send_transaction(
         &self,
+        ctx: &TxContext,
         kind: EventKind,
         key: SolanaNftEventKey,
         SolanaPendingTransaction {
@@ -360,12 +438,17 @@ impl<'a> Sign for Solana<'a> {
 
         let mut fireblocks_requests = Vec::new();
 
-        for req_sig in signatures_or_signers_public_keys {
+        // The first signer of a Solana transaction pays its fee.
+        for (i, req_sig) in signatures_or_signers_public_keys.into_iter().enumerate() {
             if ValidateAddress::is_solana_address(&req_sig) {
-                let vault_id = find_vault_id_by_wallet_address(conn, req_sig).await?;
+                let note = note.clone();
+                let message = serialized_message.clone();
 
-                let fireblocks_request: future::BoxFuture<Result<String>> =
-                    Box::pin(self.sign_message(note.clone(), serialized_message.clone(), vault_id));
+                let fireblocks_request: future::BoxFuture<Result<String>> = Box::pin(async move {
+                    let vault_id = find_vault_id_by_wallet_address(conn, req_sig).await?;
+
+                    self.sign(ctx, note, message, vault_id, i == 0).await
+                });
 
                 fireblocks_requests.push(fireblocks_request);
             } else {
@@ -373,19 +456,34 @@ impl<'a> Sign for Solana<'a> {
             }
         }
 
-        let solana_transaction_result =
-            future::try_join_all(fireblocks_requests).await.map_or_else(
-                |_| SolanaTransactionResult {
+        let signed = async {
+            self.0
+                .enabled_project_settings(ctx.project_id, AssetType::Solana)
+                .await?;
+
+            future::try_join_all(fireblocks_requests).await
+        }
+        .await;
+
+        let solana_transaction_result = match signed {
+            Ok(signed_message_signatures) => SolanaTransactionResult {
+                serialized_message: Some(serialized_message),
+                signed_message_signatures,
+                status: TransactionStatus::Completed.into(),
+                failure: None,
+            },
+            Err(e @ ProcessorError::TransactionPending(_)) => return Err(e),
+            Err(e) => {
+                error!("Error signing {kind:?}: {e:?}");
+
+                SolanaTransactionResult {
                     serialized_message: None,
                     signed_message_signatures: vec![],
                     status: TransactionStatus::Failed.into(),
-                },
-                |signed_message_signatures| SolanaTransactionResult {
-                    serialized_message: Some(serialized_message),
-                    signed_message_signatures,
-                    status: TransactionStatus::Completed.into(),
-                },
-            );
+                    failure: Some(e.failure()),
+                }
+            },
+        };
 
         Ok(solana_transaction_result)
     }
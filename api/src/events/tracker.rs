@@ -0,0 +1,259 @@
+use hub_core::{chrono::Utc, clap, prelude::*, tokio::time, uuid::Uuid};
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, LockBehavior, LockType, OnConflict},
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+
+use super::{
+    ledger::digest,
+    signer::{deadline_passed, refresh_transaction},
+    Processor, ProcessorError, Result,
+};
+use crate::{custody::StatusClass, entities::pending_events, Services};
+
+impl Processor {
+    /// Stores a message until the custody transaction it is waiting on completes.
+    pub(crate) async fn defer(&self, msg: &Services, fireblocks_id: Uuid) -> Result<()> {
+        let topic = msg.topic();
+        let (message_key, message_payload) = msg.encode();
+
+        info!("{topic} message deferred until transaction {fireblocks_id} completes");
+
+        let active_model = pending_events::ActiveModel {
+            id: Set(digest([
+                topic.as_bytes(),
+                message_key.as_slice(),
+                message_payload.as_slice(),
+            ])),
+            topic: Set(topic.to_string()),
+            message_key: Set(message_key),
+            message_payload: Set(message_payload),
+            fireblocks_id: Set(fireblocks_id),
+            updated_at: Set(Utc::now().into()),
+            attempts: Set(0),
+            locked_until: Set(Utc::now().into()),
+            ..Default::default()
+        };
+
+        pending_events::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(pending_events::Column::Id)
+                    .update_columns([
+                        pending_events::Column::FireblocksId,
+                        pending_events::Column::UpdatedAt,
+                        pending_events::Column::Attempts,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Processes a deferred message again once the transaction it waits on leaves the pending state or
+    /// its wait deadline passes.
+    pub(crate) async fn resume(&self, event: pending_events::Model) -> Result<()> {
+        let (record, details) = refresh_transaction(self, event.fireblocks_id).await?;
+        let policy = self.custody.wait_policy();
+
+        if policy.classify(details.status) == StatusClass::Pending
+            && !deadline_passed(&policy, &record)
+        {
+            return Ok(());
+        }
+
+        let msg = Services::decode(&event.topic, &event.message_key, &event.message_payload)?;
+
+        match self.dispatch(msg.clone()).await {
+            Err(e) if e.is_permanent() => {
+                self.dead_letter(&msg, &e).await?;
+
+                pending_events::Entity::delete_by_id(event.id)
+                    .exec(self.db.get())
+                    .await?;
+
+                Ok(())
+            },
+            Ok(()) => {
+                pending_events::Entity::delete_by_id(event.id)
+                    .exec(self.db.get())
+                    .await?;
+
+                Ok(())
+            },
+            Err(ProcessorError::TransactionPending(fireblocks_id)) => {
+                self.defer(&msg, fireblocks_id).await
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Moves a deferred message that failed to resume too many times to the dead-letter queue.
+    pub(crate) async fn abandon(
+        &self,
+        event: pending_events::Model,
+        e: &ProcessorError,
+    ) -> Result<()> {
+        self.dlq
+            .publish(
+                &event.topic,
+                event.message_key,
+                event.message_payload,
+                format!("{e:?}"),
+                <&'static str>::from(e).to_string(),
+            )
+            .await
+            .map_err(ProcessorError::DeadLetter)?;
+
+        pending_events::Entity::delete_by_id(event.id)
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Claims the deferred messages due for a check that no tracker or notification holds, the ones
+    /// checked least recently first. With `fireblocks_id` set only the messages waiting on that
+    /// transaction are claimed. Claimed messages are held for [`LEASE_SECS`] unless released.
+    pub(crate) async fn claim_pending(
+        &self,
+        fireblocks_id: Option<Uuid>,
+        limit: Option<u64>,
+    ) -> Result<Vec<pending_events::Model>> {
+        let now = Utc::now();
+        let txn = self.db.get().begin().await?;
+
+        let mut query = pending_events::Entity::find()
+            .filter(pending_events::Column::LockedUntil.lte(now))
+            .order_by_asc(pending_events::Column::LockedUntil)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked);
+
+        if let Some(fireblocks_id) = fireblocks_id {
+            query = query.filter(pending_events::Column::FireblocksId.eq(fireblocks_id));
+        }
+
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+
+        let pending = query.all(&txn).await?;
+
+        let locked_until = now + hub_core::chrono::Duration::seconds(LEASE_SECS);
+
+        pending_events::Entity::update_many()
+            .col_expr(
+                pending_events::Column::LockedUntil,
+                Expr::value(locked_until),
+            )
+            .filter(pending_events::Column::Id.is_in(pending.iter().map(|event| event.id.clone())))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(pending)
+    }
+
+    /// Releases a claimed message so it is checked again after the messages checked before it,
+    /// recording how many times in a row resuming it failed. Successful attempts reset the count.
+    pub(crate) async fn release_pending(&self, id: &str, attempts: i32) -> Result<()> {
+        pending_events::Entity::update_many()
+            .col_expr(pending_events::Column::LockedUntil, Expr::value(Utc::now()))
+            .col_expr(pending_events::Column::Attempts, Expr::value(attempts))
+            .filter(pending_events::Column::Id.eq(id))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct TrackerArgs {
+    /// How often the tracker checks on transactions deferred events are waiting on
+    #[arg(long, env, default_value_t = 2500)]
+    pub tracker_interval_ms: u64,
+
+    /// The most deferred events checked on per interval
+    #[arg(long, env, default_value_t = 100)]
+    pub tracker_page_size: u64,
+
+    /// Failed attempts to resume a deferred event after which it is moved to the dead-letter queue
+    #[arg(long, env, default_value_t = 10)]
+    pub tracker_max_attempts: i32,
+}
+
+/// Seconds a tracker or notification holds the deferred events it claimed. Events claimed by a
+/// replica that stopped are checked again once their claim expires.
+const LEASE_SECS: i64 = 300;
+
+/// Completes deferred messages once the custody transactions they wait on leave the pending state.
+pub struct Tracker {
+    processor: Processor,
+    interval: Duration,
+    page_size: u64,
+    max_attempts: i32,
+}
+
+impl Tracker {
+    #[must_use]
+    pub fn new(processor: Processor, args: TrackerArgs) -> Self {
+        let TrackerArgs {
+            tracker_interval_ms,
+            tracker_page_size,
+            tracker_max_attempts,
+        } = args;
+
+        Self {
+            processor,
+            interval: Duration::from_millis(tracker_interval_ms),
+            page_size: tracker_page_size,
+            max_attempts: tracker_max_attempts,
+        }
+    }
+
+    /// Checks on deferred messages every interval until the process exits.
+    pub async fn run(self) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.tick().await {
+                error!("Error loading deferred messages: {e:?}");
+            }
+        }
+    }
+
+    /// Claims a page of deferred messages, the ones checked least recently first, and resumes them.
+    /// A message that failed to resume `max_attempts` times in a row is dead-lettered.
+    async fn tick(&self) -> Result<()> {
+        let pending = self
+            .processor
+            .claim_pending(None, Some(self.page_size))
+            .await?;
+
+        for event in pending {
+            let id = event.id.clone();
+            let attempts = event.attempts.saturating_add(1);
+
+            match self.processor.resume(event.clone()).await {
+                Ok(()) => self.processor.release_pending(&id, 0).await?,
+                Err(e) if attempts >= self.max_attempts => {
+                    error!("Deferred message {id} failed {attempts} times, dead-lettering: {e:?}");
+
+                    self.processor.abandon(event, &e).await?;
+                },
+                Err(e) => {
+                    error!("Error resuming deferred message {id}: {e:?}");
+
+                    self.processor.release_pending(&id, attempts).await?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use hub_core::prelude::*;
+use sea_orm::{prelude::*, Set};
+
+use super::{Processor, ProcessorError, Result};
+use crate::{
+    custody::{NewVault, Vault, VaultQuery},
+    entities::treasuries,
+};
+
+impl Processor {
+    /// Finds the vault named `vault.name`, such as one created by an earlier delivery of the same
+    /// event that failed before recording it, and creates the vault otherwise.
+    /// When several vaults share the name, the one with the same customer reference is used.
+    pub(super) async fn find_or_create_vault(&self, vault: NewVault) -> Result<Vault> {
+        let page = self
+            .custody
+            .vaults(VaultQuery {
+                name_prefix: Some(vault.name.clone()),
+                ..Default::default()
+            })
+            .await
+            .map_err(ProcessorError::Custody)?;
+
+        let mut existing = page
+            .vaults
+            .into_iter()
+            .filter(|account| account.name == vault.name)
+            .collect::<Vec<_>>();
+
+        existing.sort_by_key(|account| account.customer_ref_id != vault.customer_ref_id);
+
+        if let Some(account) = existing.into_iter().next() {
+            info!("found existing vault {} named {}", account.id, account.name);
+
+            return Ok(account);
+        }
+
+        self.custody
+            .create_vault(vault)
+            .await
+            .map_err(ProcessorError::Custody)
+    }
+}
+
+/// Loads the treasury of the vault, inserting it unless an earlier attempt already recorded it.
+pub(super) async fn vault_treasury(
+    conn: &impl ConnectionTrait,
+    vault_id: String,
+) -> Result<treasuries::Model> {
+    let treasury = treasuries::Entity::find()
+        .filter(treasuries::Column::VaultId.eq(vault_id.clone()))
+        .one(conn)
+        .await?;
+
+    if let Some(treasury) = treasury {
+        return Ok(treasury);
+    }
+
+    let treasury = treasuries::ActiveModel {
+        vault_id: Set(vault_id),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+
+    Ok(treasury)
+}
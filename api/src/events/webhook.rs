@@ -0,0 +1,85 @@
+use fireblocks::objects::webhook::TransactionNotification;
+use hub_core::{chrono::Utc, prelude::*, uuid::Uuid};
+use sea_orm::{prelude::*, Set};
+
+use super::{Processor, Result};
+use crate::{
+    custody::StatusClass,
+    entities::{sea_orm_active_enums::TxStatus, transaction_status_changes, transactions},
+};
+
+impl Processor {
+    /// Records the new status of a tracked transaction and resumes the messages deferred on it once it
+    /// leaves the pending state, including transactions cancelled or rejected from the Fireblocks console.
+    /// # Errors
+    /// Returns an error if the status cannot be recorded, in which case Fireblocks delivers the notification again.
+    pub async fn transaction_status_updated(
+        &self,
+        transaction: TransactionNotification,
+    ) -> Result<()> {
+        let conn = self.db.get();
+        let fireblocks_id = Uuid::from_str(&transaction.id)?;
+        let status = TxStatus::from(transaction.status);
+
+        let Some(record) = transactions::Entity::find_by_id(fireblocks_id)
+            .one(conn)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let policy = self.custody.wait_policy();
+        let pending = policy.classify(status.into()) == StatusClass::Pending;
+
+        // notifications may arrive out of order, a final status is never reverted
+        if policy.classify(record.status.into()) != StatusClass::Pending && pending {
+            return Ok(());
+        }
+
+        info!(
+            "transaction {fireblocks_id} is {status:?} ({:?})",
+            transaction.sub_status
+        );
+
+        let mut active_model: transactions::ActiveModel = record.into();
+
+        active_model.status = Set(status);
+        if let Some(sub_status) = transaction.sub_status.clone().filter(|s| !s.is_empty()) {
+            active_model.sub_status = Set(Some(sub_status));
+        }
+        if let Some(hash) = transaction.tx_hash.filter(|hash| !hash.is_empty()) {
+            active_model.tx_hash = Set(Some(hash));
+        }
+        active_model.updated_at = Set(Some(Utc::now().into()));
+
+        active_model.update(conn).await?;
+
+        transaction_status_changes::record(conn, fireblocks_id, status, transaction.sub_status)
+            .await?;
+
+        if pending {
+            return Ok(());
+        }
+
+        // deferred messages are claimed so that a tracker does not resume them at the same time
+        let deferred = self.claim_pending(Some(fireblocks_id), None).await?;
+        let mut result = Ok(());
+
+        for event in deferred {
+            let id = event.id.clone();
+            let attempts = event.attempts.saturating_add(1);
+
+            match self.resume(event).await {
+                Ok(()) => self.release_pending(&id, 0).await?,
+                Err(e) => {
+                    error!("Error resuming deferred message {id}: {e:?}");
+
+                    self.release_pending(&id, attempts).await?;
+                    result = result.and(Err(e));
+                },
+            }
+        }
+
+        result
+    }
+}
@@ -1,17 +1,20 @@
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
+use fireblocks::{objects::webhook::WebhookNotification, webhook::SIGNATURE_HEADER};
 use hub_core::{
     anyhow::Result,
     metrics::{Encoder, TextEncoder},
+    prelude::*,
+    serde_json,
 };
 use poem::{
     handler,
-    http::StatusCode,
-    web::{Data, Html},
+    http::{HeaderMap, StatusCode},
+    web::{Bytes, Data, Html},
     IntoResponse,
 };
 
-use crate::{AppContext, AppState, Balance, Metrics, OrganizationId, UserID};
+use crate::{AppContext, AppState, Balance, Metrics, OrganizationId, UserID, WebhookState};
 
 #[handler]
 pub fn health() -> StatusCode {
@@ -31,14 +34,21 @@ pub async fn graphql_handler(
     balance: Balance,
     req: GraphQLRequest,
 ) -> Result<GraphQLResponse> {
-    let context = AppContext::new(state.connection.clone(), user_id, organization_id, balance);
+    let context = AppContext::new(
+        state.connection.clone(),
+        user_id,
+        organization_id,
+        balance,
+        state.custody.clone(),
+        state.balance_cache.clone(),
+    );
 
     Ok(state
         .schema
         .execute(
             req.0
                 .data(context)
-                .data(state.fireblocks.clone())
+                .data(state.custody.clone())
                 .data(state.producer.clone())
                 .data(state.credits.clone()),
         )
@@ -53,3 +63,55 @@ pub fn metrics_handler(Data(metrics): Data<&Metrics>) -> Result<String> {
     encoder.encode(&metrics.registry.gather(), &mut buffer)?;
     Ok(String::from_utf8_lossy(&buffer).into_owned())
 }
+
+#[handler]
+pub async fn fireblocks_webhook(
+    Data(state): Data<&WebhookState>,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let Some(verifier) = &state.verifier else {
+        error!("Fireblocks webhook received but no webhook public key is configured");
+        return StatusCode::SERVICE_UNAVAILABLE;
+    };
+
+    let Some(signature) = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return StatusCode::UNAUTHORIZED;
+    };
+
+    if let Err(e) = verifier.verify(&body, signature) {
+        error!("Rejected Fireblocks webhook: {e:?}");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let notification = match serde_json::from_slice::<WebhookNotification>(&body) {
+        Ok(notification) => notification,
+        Err(e) => {
+            error!("Invalid Fireblocks webhook notification: {e:?}");
+            return StatusCode::BAD_REQUEST;
+        },
+    };
+
+    let processed: Result<()> = match notification {
+        WebhookNotification::TransactionStatusUpdated(transaction) => state
+            .processor
+            .transaction_status_updated(transaction)
+            .await
+            .map_err(Into::into),
+        WebhookNotification::VaultAccountAdded(vault) => {
+            state.reconciler.reconcile_vault(vault.id).await.map(|_| ())
+        },
+        WebhookNotification::Unsupported => Ok(()),
+    };
+
+    match processed {
+        Ok(()) => StatusCode::OK,
+        Err(e) => {
+            error!("Error processing Fireblocks webhook: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        },
+    }
+}
@@ -2,28 +2,38 @@
 #![warn(clippy::pedantic, clippy::cargo)]
 #![allow(clippy::module_name_repetitions)]
 
+pub mod admin;
+pub mod chains;
+pub mod custody;
 pub mod dataloaders;
 pub mod db;
+pub mod dlq;
 #[allow(clippy::pedantic)]
 pub mod entities;
 pub mod events;
 pub mod handlers;
 pub mod metrics;
+pub mod monitor;
 pub mod mutations;
 pub mod objects;
+pub mod provision;
 pub mod queries;
+pub mod reconciliation;
 
 use async_graphql::{
     dataloader::DataLoader,
     extensions::{ApolloTracing, Logger},
     EmptySubscription, Schema,
 };
+use chains::Chain;
+use custody::CustodyClient;
 use dataloaders::{
     CustomerTreasuryLoader, CustomerTreasuryWalletLoader, CustomerWalletAddressesLoader,
-    ProjectTreasuryLoader, TreasuryLoader, TreasuryWalletsLoader, WalletLoader,
+    ProjectTreasuryLoader, TransactionHistoryLoader, TreasuryLoader, TreasuryWalletsLoader,
+    VaultBalanceCache, VaultBalancesLoader, WalletLoader,
 };
 use db::Connection;
-use fireblocks::Fireblocks;
+use fireblocks::webhook::WebhookVerifier;
 use hub_core::{
     anyhow::{Error, Result},
     clap,
@@ -40,6 +50,9 @@ use poem::{async_trait, FromRequest, Request, RequestBody};
 use proto::{TreasuryEventKey, TreasuryEvents};
 use queries::Query;
 
+/// The name of the service. Treasury events are produced to the topic of the same name.
+pub const SERVICE_NAME: &str = "hub-treasuries";
+
 pub type AppSchema = Schema<Query, Mutation, EmptySubscription>;
 
 #[allow(clippy::pedantic)]
@@ -49,6 +62,8 @@ pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/nfts.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/solana_nfts.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/polygon_nfts.proto.rs"));
+    include!(concat!(env!("OUT_DIR"), "/ethereum_nfts.proto.rs"));
+    include!(concat!(env!("OUT_DIR"), "/evm_nfts.proto.rs"));
     include!(concat!(env!("OUT_DIR"), "/treasury.proto.rs"));
 }
 
@@ -58,15 +73,12 @@ pub enum Services {
     Customers(proto::CustomerEventKey, proto::CustomerEvents),
     Solana(proto::SolanaNftEventKey, proto::SolanaNftEvents),
     Polygon(proto::PolygonNftEventKey, proto::PolygonNftEvents),
+    Ethereum(proto::EthereumNftEventKey, proto::EthereumNftEvents),
+    Evm(&'static Chain, proto::EvmNftEventKey, proto::EvmNftEvents),
 }
 
 impl hub_core::consumer::MessageGroup for Services {
-    const REQUESTED_TOPICS: &'static [&'static str] = &[
-        "hub-orgs",
-        "hub-customers",
-        "hub-nfts-solana",
-        "hub-nfts-polygon",
-    ];
+    const REQUESTED_TOPICS: &'static [&'static str] = &chains::TOPICS;
 
     fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Result<Self, RecvError> {
         let topic = msg.topic();
@@ -74,6 +86,42 @@ impl hub_core::consumer::MessageGroup for Services {
         let val = msg.payload().ok_or(RecvError::MissingPayload)?;
         info!(topic, ?key, ?val);
 
+        Self::decode(topic, key, val)
+    }
+}
+
+impl Services {
+    /// The topic the message was consumed from.
+    #[must_use]
+    pub fn topic(&self) -> &'static str {
+        match self {
+            Services::Organizations(..) => "hub-orgs",
+            Services::Customers(..) => "hub-customers",
+            Services::Solana(..) => "hub-nfts-solana",
+            Services::Polygon(..) => "hub-nfts-polygon",
+            Services::Ethereum(..) => "hub-nfts-ethereum",
+            Services::Evm(chain, ..) => chain.topic,
+        }
+    }
+
+    /// Encodes the key and payload of the message as they were consumed.
+    #[must_use]
+    pub fn encode(&self) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            Services::Organizations(key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+            Services::Customers(key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+            Services::Solana(key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+            Services::Polygon(key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+            Services::Ethereum(key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+            Services::Evm(_, key, val) => (key.encode_to_vec(), val.encode_to_vec()),
+        }
+    }
+
+    /// Decodes the key and payload of a message consumed from `topic`.
+    ///
+    /// # Errors
+    /// Returns an error if the topic is not consumed by the service or the message cannot be decoded.
+    pub fn decode(topic: &str, key: &[u8], val: &[u8]) -> Result<Self, RecvError> {
         match topic {
             "hub-orgs" => {
                 let key = proto::OrganizationEventKey::decode(key)?;
@@ -99,7 +147,19 @@ impl hub_core::consumer::MessageGroup for Services {
 
                 Ok(Services::Polygon(key, val))
             },
-            t => Err(RecvError::BadTopic(t.into())),
+            "hub-nfts-ethereum" => {
+                let key = proto::EthereumNftEventKey::decode(key)?;
+                let val = proto::EthereumNftEvents::decode(val)?;
+
+                Ok(Services::Ethereum(key, val))
+            },
+            t => {
+                let chain = chains::by_evm_topic(t).ok_or_else(|| RecvError::BadTopic(t.into()))?;
+                let key = proto::EvmNftEventKey::decode(key)?;
+                let val = proto::EvmNftEvents::decode(val)?;
+
+                Ok(Services::Evm(chain, key, val))
+            },
         }
     }
 }
@@ -205,20 +265,45 @@ pub struct Args {
     #[arg(short, long, env, default_value_t = 3007)]
     pub port: u16,
 
+    /// Port of the operator-only admin GraphQL API, served on localhost only
+    #[arg(long, env, default_value_t = 3008)]
+    pub admin_port: u16,
+
+    /// Seconds vault balances fetched from the custodian are reused for
+    #[arg(long, env, default_value_t = 15)]
+    pub balance_cache_ttl_secs: u64,
+
+    /// Seconds after which a customer wallet creation that stopped making progress is completed on startup
+    #[arg(long, env, default_value_t = 300)]
+    pub wallet_creation_stale_secs: u64,
+
     #[command(flatten)]
     pub db: db::DbArgs,
 
     #[command(flatten)]
     pub fireblocks: fireblocks::FbArgs,
+
+    #[command(flatten)]
+    pub tracker: events::TrackerArgs,
+
+    #[command(flatten)]
+    pub balance_monitor: monitor::BalanceMonitorArgs,
+
+    #[command(flatten)]
+    pub gas_station: events::GasStationArgs,
+
+    #[command(flatten)]
+    pub reconciler: reconciliation::ReconcilerArgs,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub schema: AppSchema,
     pub connection: Connection,
-    pub fireblocks: Fireblocks,
+    pub custody: CustodyClient,
     pub producer: Producer<TreasuryEvents>,
     pub credits: CreditsClient<Actions>,
+    pub balance_cache: VaultBalanceCache,
 }
 
 impl AppState {
@@ -226,16 +311,41 @@ impl AppState {
     pub fn new(
         schema: AppSchema,
         connection: Connection,
-        fireblocks: Fireblocks,
+        custody: CustodyClient,
         producer: Producer<TreasuryEvents>,
         credits: CreditsClient<Actions>,
+        balance_cache: VaultBalanceCache,
     ) -> Self {
         Self {
             schema,
             connection,
-            fireblocks,
+            custody,
             producer,
             credits,
+            balance_cache,
+        }
+    }
+}
+
+/// State of the Fireblocks webhook endpoint.
+#[derive(Clone)]
+pub struct WebhookState {
+    pub processor: events::Processor,
+    pub reconciler: reconciliation::Reconciler,
+    pub verifier: Option<WebhookVerifier>,
+}
+
+impl WebhookState {
+    #[must_use]
+    pub fn new(
+        processor: events::Processor,
+        reconciler: reconciliation::Reconciler,
+        verifier: Option<WebhookVerifier>,
+    ) -> Self {
+        Self {
+            processor,
+            reconciler,
+            verifier,
         }
     }
 }
@@ -252,6 +362,8 @@ pub struct AppContext {
     pub customer_treasury_wallet_loader: DataLoader<CustomerTreasuryWalletLoader>,
     pub treasury_loader: DataLoader<TreasuryLoader>,
     pub customer_wallet_addresses_loader: DataLoader<CustomerWalletAddressesLoader>,
+    pub vault_balances_loader: DataLoader<VaultBalancesLoader>,
+    pub transaction_history_loader: DataLoader<TransactionHistoryLoader>,
 }
 
 impl AppContext {
@@ -261,6 +373,8 @@ impl AppContext {
         user_id: UserID,
         organization_id: OrganizationId,
         balance: Balance,
+        custody: CustodyClient,
+        balance_cache: VaultBalanceCache,
     ) -> Self {
         let customer_treasury_loader =
             DataLoader::new(CustomerTreasuryLoader::new(db.clone()), tokio::spawn);
@@ -274,6 +388,12 @@ impl AppContext {
         let treasury_loader = DataLoader::new(TreasuryLoader::new(db.clone()), tokio::spawn);
         let customer_wallet_addresses_loader =
             DataLoader::new(CustomerWalletAddressesLoader::new(db.clone()), tokio::spawn);
+        let transaction_history_loader =
+            DataLoader::new(TransactionHistoryLoader::new(db.clone()), tokio::spawn);
+        let vault_balances_loader = DataLoader::new(
+            VaultBalancesLoader::new(custody, balance_cache),
+            tokio::spawn,
+        );
 
         Self {
             db,
@@ -287,6 +407,8 @@ impl AppContext {
             customer_treasury_wallet_loader,
             treasury_loader,
             customer_wallet_addresses_loader,
+            vault_balances_loader,
+            transaction_history_loader,
         }
     }
 }
@@ -1,26 +1,42 @@
 //!
 
+use std::sync::Arc;
+
 use holaplex_hub_treasuries::{
+    admin::{self, AdminState},
     build_schema,
+    custody::CustodyClient,
+    dataloaders::VaultBalanceCache,
     db::Connection,
+    dlq::{self, DeadLetter, DeadLetterQueue},
     events,
-    handlers::{graphql_handler, health, metrics_handler, playground},
+    handlers::{fireblocks_webhook, graphql_handler, health, metrics_handler, playground},
     metrics::Metrics,
-    proto, Actions, AppState, Args, Services,
+    monitor::BalanceMonitor,
+    proto,
+    reconciliation::Reconciler,
+    Actions, AppState, Args, Services, WebhookState, SERVICE_NAME,
 };
 use hub_core::{anyhow::Context as AnyhowContext, prelude::*, tokio};
 use poem::{get, listener::TcpListener, middleware::AddData, post, EndpointExt, Route, Server};
 
 pub fn main() {
     let opts = hub_core::StartConfig {
-        service_name: "hub-treasuries",
+        service_name: SERVICE_NAME,
     };
 
     hub_core::run(opts, |common, args| {
         let Args {
             port,
+            admin_port,
+            balance_cache_ttl_secs,
+            wallet_creation_stale_secs,
             db,
             fireblocks,
+            tracker,
+            balance_monitor,
+            gas_station,
+            reconciler,
         } = args;
 
         common.rt.block_on(async move {
@@ -30,26 +46,103 @@ pub fn main() {
 
             let schema = build_schema();
             let fireblocks = fireblocks::Fireblocks::new(fireblocks)?;
-            let producer = common.producer_cfg.build::<proto::TreasuryEvents>().await?;
+            let webhook_verifier = fireblocks.webhook_verifier();
+            let custody: CustodyClient = Arc::new(fireblocks);
+            let producer = common
+                .producer_cfg
+                .clone()
+                .build::<proto::TreasuryEvents>()
+                .await?;
+
+            let dlq = DeadLetterQueue::new(
+                connection.clone(),
+                dlq::producer_config(&common.producer_cfg)
+                    .build::<DeadLetter>()
+                    .await?,
+            );
 
             let metrics = Metrics::new()?;
 
+            let credits = common.credits_cfg.build::<Actions>().await?;
+
             let event_processor = events::Processor::new(
                 connection.clone(),
                 producer.clone(),
-                fireblocks.clone(),
+                custody.clone(),
+                credits.clone(),
                 metrics.clone(),
+                dlq,
+                events::GasStation::new(gas_station),
             );
 
-            let credits = common.credits_cfg.build::<Actions>().await?;
+            let wallet_saga = event_processor.wallet_saga();
+
+            tokio::spawn(async move {
+                if let Err(e) = wallet_saga
+                    .reconcile(Duration::from_secs(wallet_creation_stale_secs))
+                    .await
+                {
+                    error!("failed to reconcile interrupted wallet creations: {e:?}");
+                }
+            });
+
+            let reconciler = Reconciler::new(
+                connection.clone(),
+                custody.clone(),
+                metrics.clone(),
+                reconciler,
+            );
+
+            tokio::spawn(reconciler.clone().run());
+
             let state = AppState::new(
                 schema,
                 connection.clone(),
-                fireblocks.clone(),
+                custody.clone(),
                 producer.clone(),
                 credits,
+                VaultBalanceCache::new(Duration::from_secs(balance_cache_ttl_secs)),
             );
 
+            let webhooks = WebhookState::new(
+                event_processor.clone(),
+                reconciler.clone(),
+                webhook_verifier,
+            );
+
+            let tracker = events::Tracker::new(event_processor.clone(), tracker);
+
+            tokio::spawn(tracker.run());
+
+            let balance_monitor = BalanceMonitor::new(
+                connection.clone(),
+                custody.clone(),
+                producer.clone(),
+                metrics.clone(),
+                balance_monitor,
+            );
+
+            tokio::spawn(balance_monitor.run());
+
+            let admin_state = AdminState::new(
+                admin::build_schema(),
+                connection.clone(),
+                event_processor.clone(),
+                reconciler,
+            );
+
+            tokio::spawn(async move {
+                if let Err(e) = Server::new(TcpListener::bind(format!("127.0.0.1:{admin_port}")))
+                    .run(Route::new().at(
+                        "/graphql",
+                        post(admin::graphql_handler).with(AddData::new(admin_state)),
+                    ))
+                    .await
+                {
+                    error!("admin server stopped: {e:?}");
+                }
+            });
+
             let cons = common.consumer_cfg.build::<Services>().await?;
 
             tokio::spawn(async move {
@@ -70,6 +163,10 @@ pub fn main() {
                         .at("/graphql", post(graphql_handler).with(AddData::new(state)))
                         .at("/playground", get(playground))
                         .at("/health", get(health))
+                        .at(
+                            "/webhooks/fireblocks",
+                            post(fireblocks_webhook).with(AddData::new(webhooks)),
+                        )
                         .at("/metrics", get(metrics_handler).with(AddData::new(metrics))),
                 )
                 .await
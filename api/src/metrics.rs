@@ -1,14 +1,27 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
 #[allow(clippy::wildcard_imports)]
 use hub_core::{
     anyhow::{anyhow, Result},
     metrics::*,
 };
 
+/// Latest balance and low balance threshold of each project treasury asset, keyed by project and asset ID.
+type TreasuryBalances = Arc<Mutex<HashMap<(String, String), (f64, f64)>>>;
+
+/// Issues found by the latest treasury reconciliation, keyed by kind.
+type ReconciliationIssues = Arc<Mutex<HashMap<&'static str, u64>>>;
+
 #[derive(Clone)]
 pub struct Metrics {
     pub registry: Registry,
     pub provider: MeterProvider,
     pub sign_duration_ms_bucket: Histogram<i64>,
+    treasury_balances: TreasuryBalances,
+    reconciliation_issues: ReconciliationIssues,
 }
 
 impl Metrics {
@@ -38,10 +51,89 @@ impl Metrics {
             .with_description("Signing duration time in milliseconds.")
             .init();
 
+        let treasury_balance = meter
+            .f64_observable_gauge("project_treasury.balance")
+            .with_description("Available balance of project treasury assets.")
+            .init();
+
+        let treasury_low_balance_threshold = meter
+            .f64_observable_gauge("project_treasury.low_balance_threshold")
+            .with_description("Balance below which a project treasury asset is running low.")
+            .init();
+
+        let treasury_balances = TreasuryBalances::default();
+        let balances = treasury_balances.clone();
+        let instruments = [
+            treasury_balance.as_any(),
+            treasury_low_balance_threshold.as_any(),
+        ];
+
+        meter
+            .register_callback(&instruments, move |observer| {
+                let balances = balances.lock().unwrap_or_else(PoisonError::into_inner);
+
+                for ((project_id, asset_id), (balance, threshold)) in balances.iter() {
+                    let attributes = [
+                        KeyValue::new("project_id", project_id.clone()),
+                        KeyValue::new("asset_id", asset_id.clone()),
+                    ];
+
+                    observer.observe_f64(&treasury_balance, *balance, &attributes);
+                    observer.observe_f64(&treasury_low_balance_threshold, *threshold, &attributes);
+                }
+            })
+            .map_err(|e| anyhow!("Failed to register balance gauges: {}", e))?;
+
+        let reconciliation_issue_count = meter
+            .u64_observable_gauge("treasury_reconciliation.issues")
+            .with_description(
+                "Issues found by the latest reconciliation of treasuries against custodian vaults.",
+            )
+            .init();
+
+        let reconciliation_issues = ReconciliationIssues::default();
+        let issues = reconciliation_issues.clone();
+
+        meter
+            .register_callback(&[reconciliation_issue_count.as_any()], move |observer| {
+                let issues = issues.lock().unwrap_or_else(PoisonError::into_inner);
+
+                for (kind, count) in issues.iter() {
+                    observer.observe_u64(&reconciliation_issue_count, *count, &[KeyValue::new(
+                        "kind", *kind,
+                    )]);
+                }
+            })
+            .map_err(|e| anyhow!("Failed to register reconciliation gauges: {}", e))?;
+
         Ok(Self {
             registry,
             provider,
             sign_duration_ms_bucket,
+            treasury_balances,
+            reconciliation_issues,
         })
     }
+
+    /// Records the latest balance of a project treasury asset and its low balance threshold.
+    pub fn record_treasury_balance(
+        &self,
+        project_id: String,
+        asset_id: String,
+        balance: f64,
+        threshold: f64,
+    ) {
+        self.treasury_balances
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((project_id, asset_id), (balance, threshold));
+    }
+
+    /// Replaces the issue counts reported by the previous treasury reconciliation.
+    pub fn record_reconciliation_issues(&self, counts: HashMap<&'static str, u64>) {
+        *self
+            .reconciliation_issues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = counts;
+    }
 }
@@ -0,0 +1,242 @@
+//! Periodic checks on the balances of project treasuries.
+
+use std::collections::{HashMap, HashSet};
+
+use hub_core::{anyhow::Result, clap, prelude::*, producer::Producer, tokio::time, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{
+    custody::CustodyClient,
+    db::Connection,
+    entities::{
+        project_balance_alerts, project_balance_thresholds, project_treasuries, treasuries,
+        wallets::{AssetAmount, AssetType},
+    },
+    metrics::Metrics,
+    proto::{
+        treasury_events::{self, ProjectTreasuryLowBalance},
+        TreasuryEventKey, TreasuryEvents,
+    },
+};
+
+#[derive(Debug, clap::Args)]
+pub struct BalanceMonitorArgs {
+    /// Seconds between checks on the balances of project treasuries
+    #[arg(long, env, default_value_t = 300)]
+    pub balance_monitor_interval_secs: u64,
+
+    /// Default low balance thresholds per asset, e.g. `SOL=0.5,MATIC=10`. Projects may override them.
+    #[arg(long, env, value_delimiter = ',')]
+    pub low_balance_thresholds: Vec<AssetAmount>,
+}
+
+/// Reports project treasuries whose balance of an asset falls below its threshold.
+/// A treasury is reported once when it runs low and again only after it has been topped up. Reports
+/// are recorded as alerts in the database, so restarts and replicas do not report a treasury twice.
+pub struct BalanceMonitor {
+    db: Connection,
+    custody: CustodyClient,
+    producer: Producer<TreasuryEvents>,
+    metrics: Metrics,
+    interval: Duration,
+    defaults: HashMap<AssetType, f64>,
+}
+
+impl BalanceMonitor {
+    #[must_use]
+    pub fn new(
+        db: Connection,
+        custody: CustodyClient,
+        producer: Producer<TreasuryEvents>,
+        metrics: Metrics,
+        args: BalanceMonitorArgs,
+    ) -> Self {
+        let BalanceMonitorArgs {
+            balance_monitor_interval_secs,
+            low_balance_thresholds,
+        } = args;
+
+        Self {
+            db,
+            custody,
+            producer,
+            metrics,
+            interval: Duration::from_secs(balance_monitor_interval_secs),
+            defaults: low_balance_thresholds
+                .into_iter()
+                .map(|t| (t.asset_type, t.amount))
+                .collect(),
+        }
+    }
+
+    /// Checks the balances of project treasuries every interval until the process exits.
+    pub async fn run(self) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.tick().await {
+                error!("Error checking project treasury balances: {e:?}");
+            }
+        }
+    }
+
+    async fn tick(&self) -> Result<()> {
+        let conn = self.db.get();
+
+        let project_treasuries = project_treasuries::Entity::find()
+            .find_also_related(treasuries::Entity)
+            .all(conn)
+            .await?;
+
+        let mut overrides: HashMap<Uuid, HashMap<AssetType, f64>> = HashMap::new();
+
+        for threshold in project_balance_thresholds::Entity::find().all(conn).await? {
+            overrides
+                .entry(threshold.project_id)
+                .or_default()
+                .insert(threshold.asset_id, threshold.threshold);
+        }
+
+        let alerts = project_balance_alerts::Entity::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|alert| (alert.project_id, alert.asset_id))
+            .collect::<HashSet<_>>();
+
+        for (project_treasury, treasury) in project_treasuries {
+            let Some(treasury) = treasury else {
+                continue;
+            };
+
+            let mut thresholds = self.defaults.clone();
+            thresholds.extend(
+                overrides
+                    .remove(&project_treasury.project_id)
+                    .unwrap_or_default(),
+            );
+
+            if thresholds.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self
+                .check(project_treasury.project_id, &treasury, thresholds, &alerts)
+                .await
+            {
+                error!(
+                    "Error checking balances of project {} treasury: {e:?}",
+                    project_treasury.project_id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check(
+        &self,
+        project_id: Uuid,
+        treasury: &treasuries::Model,
+        thresholds: HashMap<AssetType, f64>,
+        alerts: &HashSet<(Uuid, AssetType)>,
+    ) -> Result<()> {
+        let conn = self.db.get();
+        let vault = self.custody.vault(treasury.vault_id.clone()).await?;
+
+        for (asset_type, threshold) in thresholds {
+            let asset_id = self.custody.asset_id(asset_type.as_str());
+
+            let Some(asset) = vault.assets.iter().find(|asset| asset.id == asset_id) else {
+                continue;
+            };
+
+            let balance: f64 = asset.available.parse()?;
+
+            self.metrics.record_treasury_balance(
+                project_id.to_string(),
+                asset_id.clone(),
+                balance,
+                threshold,
+            );
+
+            let alerted = alerts.contains(&(project_id, asset_type));
+
+            if balance >= threshold {
+                if alerted {
+                    self.clear_alert(project_id, asset_type).await?;
+                }
+
+                continue;
+            }
+
+            if alerted {
+                continue;
+            }
+
+            // the alert is recorded first so only one replica reports the treasury
+            let recorded =
+                project_balance_alerts::Entity::insert(project_balance_alerts::ActiveModel {
+                    project_id: Set(project_id),
+                    asset_id: Set(asset_type),
+                    ..Default::default()
+                })
+                .on_conflict(
+                    OnConflict::columns([
+                        project_balance_alerts::Column::ProjectId,
+                        project_balance_alerts::Column::AssetId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(conn)
+                .await?;
+
+            if recorded == 0 {
+                continue;
+            }
+
+            info!("project {project_id} treasury is running low on {asset_id}: {balance} < {threshold}");
+
+            let event = TreasuryEvents {
+                event: Some(treasury_events::Event::ProjectTreasuryLowBalance(
+                    ProjectTreasuryLowBalance {
+                        project_id: project_id.to_string(),
+                        treasury_id: treasury.id.to_string(),
+                        vault_id: treasury.vault_id.clone(),
+                        asset_id,
+                        balance: asset.available.clone(),
+                        threshold: threshold.to_string(),
+                    },
+                )),
+            };
+
+            let key = TreasuryEventKey {
+                id: treasury.id.to_string(),
+                user_id: String::new(),
+                project_id: project_id.to_string(),
+            };
+
+            if let Err(e) = self.producer.send(Some(&event), Some(&key)).await {
+                self.clear_alert(project_id, asset_type).await?;
+
+                return Err(e.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the alert of a treasury on an asset so it is reported the next time it runs low.
+    async fn clear_alert(&self, project_id: Uuid, asset_type: AssetType) -> Result<()> {
+        project_balance_alerts::Entity::delete_many()
+            .filter(project_balance_alerts::Column::ProjectId.eq(project_id))
+            .filter(project_balance_alerts::Column::AssetId.eq(asset_type))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
+use hub_core::{chrono::Utc, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set, TransactionTrait};
+
+use crate::{
+    entities::{auto_provisioned_assets, organization_credit_balances, wallets::AssetType},
+    AppContext,
+};
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "AutoProvisionMutation")]
+impl Mutation {
+    /// Set the asset types a wallet is created for when a customer of the project is created.
+    /// The wallets are paid for with the credits of the organization sending the request, like wallets created with `createCustomerWallet`.
+    /// They are charged against the credit balance the gateway last reported for the organization, and are skipped while no balance has been reported.
+    /// An empty list turns automatic wallet creation off. Tokens cannot be provisioned automatically.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the request headers are missing, a token is requested or it is unable to interact with the database.
+    pub async fn set_project_auto_provisioned_assets(
+        &self,
+        ctx: &Context<'_>,
+        input: SetProjectAutoProvisionedAssetsInput,
+    ) -> Result<SetProjectAutoProvisionedAssetsPayload> {
+        let AppContext {
+            db,
+            user_id,
+            organization_id,
+            balance,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let SetProjectAutoProvisionedAssetsInput {
+            project,
+            asset_types,
+        } = input;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+        let org_id = organization_id
+            .0
+            .ok_or(Error::new("X-ORGANIZATION-ID header not found"))?;
+
+        if let Some(token) = asset_types
+            .iter()
+            .find(|asset_type| asset_type.token().is_some())
+        {
+            return Err(Error::new(format!(
+                "{token} is a token and cannot be provisioned automatically"
+            )));
+        }
+
+        let mut asset_types = asset_types;
+        asset_types.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        asset_types.dedup();
+
+        let txn = db.get().begin().await?;
+
+        if let Some(balance) = balance.0 {
+            organization_credit_balances::Entity::record(&txn, org_id, balance).await?;
+        }
+
+        auto_provisioned_assets::Entity::delete_many()
+            .filter(
+                auto_provisioned_assets::Column::ProjectId
+                    .eq(project)
+                    .and(auto_provisioned_assets::Column::AssetId.is_not_in(asset_types.clone())),
+            )
+            .exec(&txn)
+            .await?;
+
+        for asset_type in &asset_types {
+            let active_model = auto_provisioned_assets::ActiveModel {
+                project_id: Set(project),
+                asset_id: Set(*asset_type),
+                organization_id: Set(org_id),
+                created_by: Set(user_id),
+                created_at: Set(Utc::now().into()),
+                ..Default::default()
+            };
+
+            auto_provisioned_assets::Entity::insert(active_model)
+                .on_conflict(
+                    OnConflict::columns([
+                        auto_provisioned_assets::Column::ProjectId,
+                        auto_provisioned_assets::Column::AssetId,
+                    ])
+                    .update_columns([
+                        auto_provisioned_assets::Column::OrganizationId,
+                        auto_provisioned_assets::Column::CreatedBy,
+                    ])
+                    .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(SetProjectAutoProvisionedAssetsPayload { asset_types })
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct SetProjectAutoProvisionedAssetsInput {
+    /// The ID of the project.
+    pub project: Uuid,
+    /// The asset types to create a wallet for in the treasury of every new customer.
+    pub asset_types: Vec<AssetType>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SetProjectAutoProvisionedAssetsPayload {
+    /// The asset types now created for new customers.
+    pub asset_types: Vec<AssetType>,
+}
@@ -0,0 +1,89 @@
+use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
+use hub_core::{chrono::Utc, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{
+    entities::{project_balance_thresholds, project_treasuries, wallets::AssetType},
+    AppContext,
+};
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "BalanceThresholdMutation")]
+impl Mutation {
+    /// Set the balance below which a project's treasury is reported as running low on an asset.
+    /// The threshold overrides the default threshold of the asset for the project.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the threshold is not positive, the project has no treasury owned by the caller's organization or it is unable to interact with the database.
+    pub async fn set_project_balance_threshold(
+        &self,
+        ctx: &Context<'_>,
+        input: SetProjectBalanceThresholdInput,
+    ) -> Result<SetProjectBalanceThresholdPayload> {
+        let AppContext {
+            db,
+            organization_id,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let SetProjectBalanceThresholdInput {
+            project,
+            asset_type,
+            threshold,
+        } = input;
+
+        let org_id = organization_id
+            .0
+            .ok_or(Error::new("X-ORGANIZATION-ID header not found"))?;
+
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(Error::new("threshold must be a positive amount"));
+        }
+
+        project_treasuries::Entity::find_by_project_and_organization(db.get(), project, org_id)
+            .await?
+            .ok_or(Error::new("project treasury not found"))?;
+
+        let active_model = project_balance_thresholds::ActiveModel {
+            project_id: Set(project),
+            asset_id: Set(asset_type),
+            threshold: Set(threshold),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        };
+
+        let threshold = project_balance_thresholds::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::columns([
+                    project_balance_thresholds::Column::ProjectId,
+                    project_balance_thresholds::Column::AssetId,
+                ])
+                .update_columns([
+                    project_balance_thresholds::Column::Threshold,
+                    project_balance_thresholds::Column::UpdatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec_with_returning(db.get())
+            .await?;
+
+        Ok(SetProjectBalanceThresholdPayload { threshold })
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct SetProjectBalanceThresholdInput {
+    /// The ID of the project.
+    pub project: Uuid,
+    /// The asset the threshold applies to.
+    pub asset_type: AssetType,
+    /// The available balance, in units of the asset, below which the treasury is running low.
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SetProjectBalanceThresholdPayload {
+    /// The threshold that was set.
+    pub threshold: project_balance_thresholds::Model,
+}
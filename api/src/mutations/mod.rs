@@ -1,6 +1,16 @@
+mod auto_provision;
+mod balance_threshold;
+mod project_settings;
+mod transfer;
 mod vault;
 
 // // Add your other ones here to create a unified Mutation object
 // // e.x. Mutation(OrganizationMutation, OtherMutation, OtherOtherMutation)
 #[derive(async_graphql::MergedObject, Default)]
-pub struct Mutation(vault::Mutation);
+pub struct Mutation(
+    auto_provision::Mutation,
+    balance_threshold::Mutation,
+    project_settings::Mutation,
+    transfer::Mutation,
+    vault::Mutation,
+);
@@ -0,0 +1,121 @@
+use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
+use hub_core::{chrono::Utc, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{
+    entities::{
+        project_settings, project_treasuries,
+        sea_orm_active_enums::{AutoFuelPolicy, FeeLevel},
+        wallets::AssetType,
+    },
+    AppContext,
+};
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "ProjectSettingsMutation")]
+impl Mutation {
+    /// Set the custody options of a project, replacing its current settings.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the project has no treasury owned by the caller's organization, the signing vault is not the project's treasury vault, the X-USER-ID or X-ORGANIZATION-ID header is missing or it is unable to interact with the database.
+    pub async fn set_project_settings(
+        &self,
+        ctx: &Context<'_>,
+        input: SetProjectSettingsInput,
+    ) -> Result<SetProjectSettingsPayload> {
+        let AppContext {
+            db,
+            user_id,
+            organization_id,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let SetProjectSettingsInput {
+            project,
+            enabled_chains,
+            fee_level,
+            auto_fuel,
+            signing_vault_id,
+            polygon_contract_wallet_id,
+        } = input;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+        let org_id = organization_id
+            .0
+            .ok_or(Error::new("X-ORGANIZATION-ID header not found"))?;
+
+        let (_, treasury) =
+            project_treasuries::Entity::find_by_project_and_organization(db.get(), project, org_id)
+                .await?
+                .ok_or(Error::new("project treasury not found"))?;
+        let treasury = treasury.ok_or(Error::new("treasury not found"))?;
+
+        if let Some(vault_id) = &signing_vault_id {
+            if *vault_id != treasury.vault_id {
+                return Err(Error::new(format!(
+                    "vault {vault_id} is not the treasury vault of project {project}"
+                )));
+            }
+        }
+
+        let active_model = project_settings::ActiveModel {
+            project_id: Set(project),
+            enabled_chains: Set(enabled_chains.map(|chains| {
+                chains
+                    .iter()
+                    .map(|chain| chain.as_str().to_string())
+                    .collect()
+            })),
+            fee_level: Set(fee_level),
+            auto_fuel: Set(auto_fuel),
+            signing_vault_id: Set(signing_vault_id),
+            polygon_contract_wallet_id: Set(polygon_contract_wallet_id),
+            updated_by: Set(user_id),
+            updated_at: Set(Utc::now().into()),
+        };
+
+        let settings = project_settings::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(project_settings::Column::ProjectId)
+                    .update_columns([
+                        project_settings::Column::EnabledChains,
+                        project_settings::Column::FeeLevel,
+                        project_settings::Column::AutoFuel,
+                        project_settings::Column::SigningVaultId,
+                        project_settings::Column::PolygonContractWalletId,
+                        project_settings::Column::UpdatedBy,
+                        project_settings::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(db.get())
+            .await?;
+
+        Ok(SetProjectSettingsPayload { settings })
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct SetProjectSettingsInput {
+    /// The ID of the project.
+    pub project: Uuid,
+    /// The chains the project may transact on. All chains are enabled when omitted.
+    pub enabled_chains: Option<Vec<AssetType>>,
+    /// The fee level of contract calls submitted for the project.
+    #[graphql(default)]
+    pub fee_level: FeeLevel,
+    /// How the vaults of the project are kept funded.
+    #[graphql(default)]
+    pub auto_fuel: AutoFuelPolicy,
+    /// The vault submitting the project's contract calls instead of the treasury vault of the service. Only the project's own treasury vault is accepted.
+    pub signing_vault_id: Option<String>,
+    /// The whitelisted Polygon contract wallet the project's contract calls are sent to instead of the one of the service.
+    pub polygon_contract_wallet_id: Option<String>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SetProjectSettingsPayload {
+    /// The settings that were set.
+    pub settings: project_settings::Model,
+}
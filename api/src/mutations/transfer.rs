@@ -0,0 +1,247 @@
+use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
+use fireblocks::objects::transaction::TransactionStatus;
+use hub_core::{util::ValidateAddress, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{
+    custody::{CustodyClient, Destination},
+    entities::{
+        project_treasuries,
+        sea_orm_active_enums::{TxStatus, TxType},
+        transaction_status_changes, transactions,
+        wallets::AssetType,
+    },
+    AppContext,
+};
+
+#[derive(Default)]
+pub struct Mutation;
+
+#[Object(name = "TransferMutation")]
+impl Mutation {
+    /// Transfer the native asset of a blockchain, such as SOL, MATIC or ETH, out of a project's treasury, either to an external address or to the treasury of another project.
+    /// The transfer is recorded with the project's transactions.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the asset is a token, the amount is not a positive decimal within the precision of the asset, the destination is invalid, the project or the destination project has no treasury owned by the caller's organization, it is unable to interact with the database or communicate with Fireblocks.
+    pub async fn withdraw_from_project_treasury(
+        &self,
+        ctx: &Context<'_>,
+        input: WithdrawFromProjectTreasuryInput,
+    ) -> Result<WithdrawFromProjectTreasuryPayload> {
+        let AppContext {
+            db,
+            user_id,
+            organization_id,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let custody = ctx.data::<CustodyClient>()?;
+        let conn = db.get();
+        let WithdrawFromProjectTreasuryInput {
+            project,
+            asset_type,
+            amount,
+            address,
+            destination_project,
+            idempotency_key,
+        } = input;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+        let org_id = organization_id
+            .0
+            .ok_or(Error::new("X-ORGANIZATION-ID header not found"))?;
+
+        if asset_type.token().is_some() {
+            return Err(Error::new(format!(
+                "{} is not a native asset",
+                asset_type.as_str()
+            )));
+        }
+
+        let decimals = asset_type.chain().decimals;
+
+        if !is_positive_decimal(&amount, decimals) {
+            return Err(Error::new(format!(
+                "amount must be a positive decimal with at most {decimals} decimal places"
+            )));
+        }
+
+        let (_, treasury) =
+            project_treasuries::Entity::find_by_project_and_organization(conn, project, org_id)
+                .await?
+                .ok_or(Error::new("project treasury not found"))?;
+        let treasury = treasury.ok_or(Error::new("treasury not found"))?;
+
+        let destination = match (address, destination_project) {
+            (Some(address), None) => {
+                let valid = if asset_type.chain().evm {
+                    ValidateAddress::is_evm_address(&address)
+                } else {
+                    ValidateAddress::is_solana_address(&address)
+                };
+
+                if !valid {
+                    return Err(Error::new(format!(
+                        "invalid {} address {address:?}",
+                        asset_type.as_str()
+                    )));
+                }
+
+                Destination::Address(address)
+            },
+            (None, Some(destination_project)) => {
+                let (_, destination) =
+                    project_treasuries::Entity::find_by_project_and_organization(
+                        conn,
+                        destination_project,
+                        org_id,
+                    )
+                    .await?
+                    .ok_or(Error::new("destination project treasury not found"))?;
+                let destination =
+                    destination.ok_or(Error::new("destination treasury not found"))?;
+
+                if destination.id == treasury.id {
+                    return Err(Error::new("cannot transfer to the same treasury"));
+                }
+
+                Destination::Vault(destination.vault_id)
+            },
+            _ => {
+                return Err(Error::new(
+                    "exactly one of address or destinationProject must be provided",
+                ))
+            },
+        };
+
+        // Fireblocks requires external transaction ids to be unique across the workspace, so keys
+        // are scoped to the project to keep projects sending the same key apart.
+        let external_tx_id = format!(
+            "transfer:{project}:{}",
+            idempotency_key.unwrap_or_else(|| Uuid::new_v4().to_string())
+        );
+        let asset_id = custody.asset_id(asset_type.as_str());
+
+        let transaction = custody
+            .transfer(
+                asset_id.clone(),
+                treasury.vault_id.clone(),
+                destination,
+                amount,
+                format!(
+                    "Transfer from project {project} by user {user_id} of organization {org_id}"
+                ),
+                external_tx_id.clone(),
+            )
+            .await?;
+
+        let fireblocks_id = Uuid::parse_str(&transaction.id)?;
+        let status = TxStatus::from(transaction.status);
+
+        let active_model = transactions::ActiveModel {
+            fireblocks_id: Set(fireblocks_id),
+            tx_type: Set(TxType::TransferNative),
+            signatures: Set(Vec::new()),
+            event_id: Set(external_tx_id.clone()),
+            project_id: Set(project),
+            vault_id: Set(treasury.vault_id),
+            asset_id: Set(asset_id),
+            status: Set(status),
+            external_tx_id: Set(Some(external_tx_id)),
+            ..Default::default()
+        };
+
+        transactions::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(transactions::Column::FireblocksId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
+
+        transaction_status_changes::record(conn, fireblocks_id, status, None).await?;
+
+        Ok(WithdrawFromProjectTreasuryPayload {
+            fireblocks_id,
+            status: status.into(),
+        })
+    }
+}
+
+/// Checks that an amount is a plain decimal string, e.g. `1.5`, greater than zero and with no more
+/// decimal places than the asset has. Signs, exponents and surrounding whitespace are rejected.
+fn is_positive_decimal(amount: &str, decimals: u32) -> bool {
+    let (whole, fraction) = match amount.split_once('.') {
+        Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+        Some(_) => return false,
+        None => (amount, ""),
+    };
+
+    !whole.is_empty()
+        && whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        && fraction.len() <= decimals as usize
+        && whole.bytes().chain(fraction.bytes()).any(|b| b != b'0')
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct WithdrawFromProjectTreasuryInput {
+    /// The ID of the project whose treasury funds the transfer.
+    pub project: Uuid,
+    /// The native asset to transfer.
+    pub asset_type: AssetType,
+    /// The amount to transfer as a decimal string in units of the asset, e.g. `1.5`, with at most as many decimal places as the asset has.
+    pub amount: String,
+    /// The external address receiving the transfer.
+    pub address: Option<String>,
+    /// The ID of the project whose treasury receives the transfer.
+    pub destination_project: Option<Uuid>,
+    /// A key identifying the transfer within the project. Retrying with the same key does not transfer twice.
+    /// Retries without a key are not deduplicated.
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WithdrawFromProjectTreasuryPayload {
+    /// The ID of the transfer transaction in Fireblocks.
+    pub fireblocks_id: Uuid,
+    /// The status of the transfer transaction.
+    pub status: TransactionStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_positive_decimal;
+
+    #[test]
+    fn amounts_are_positive_decimals_within_precision() {
+        for amount in ["1", "1.5", "0.000000001", "0010.100"] {
+            assert!(is_positive_decimal(amount, 9), "{amount}");
+        }
+
+        for amount in [
+            "",
+            ".",
+            ".5",
+            "5.",
+            "0",
+            "0.0",
+            "-1",
+            "+1",
+            "1e3",
+            "1.0000000001",
+            "1,5",
+            " 1",
+            "NaN",
+            "inf",
+            "1.2.3",
+        ] {
+            assert!(!is_positive_decimal(amount, 9), "{amount}");
+        }
+
+        assert!(is_positive_decimal("0.000000000000000001", 18));
+    }
+}
@@ -1,15 +1,21 @@
+use std::collections::HashSet;
+
 use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject};
-use fireblocks::{objects::vault::CreateVaultWallet, Fireblocks};
 use hub_core::{
     chrono::Utc,
     credits::{CreditsClient, TransactionId},
+    futures_util::{stream, StreamExt},
     producer::Producer,
 };
-use sea_orm::{prelude::*, JoinType, QuerySelect, Set};
+use sea_orm::{prelude::*, Set};
 
 use crate::{
-    entities::{customer_treasuries, prelude::Wallets, treasuries, wallets},
+    custody::CustodyClient,
+    entities::{
+        customer_treasuries, organization_credit_balances, prelude::Wallets, treasuries, wallets,
+    },
     proto::{treasury_events, TreasuryEventKey, TreasuryEvents},
+    provision::{WalletRequest, WalletSaga},
     Actions, AppContext,
 };
 
@@ -19,9 +25,11 @@ pub struct Mutation;
 #[Object(name = "VaultMutation")]
 impl Mutation {
     /// Create a wallet for a customer and assign it to the customer's treasury account.
+    /// A wallet may be created again for an archived asset type, in which case the treasury's address for the asset is restored.
+    /// Tokens are not created as wallets, they are activated on a treasury with `activateTreasuryToken`.
     ///
     /// # Errors
-    /// The mutation will result in an error if it is unable to interact with the database or communicate with Fireblocks.
+    /// The mutation will result in an error if the asset type is a token or it is unable to interact with the database or communicate with Fireblocks.
     pub async fn create_customer_wallet(
         &self,
         ctx: &Context<'_>,
@@ -34,7 +42,7 @@ impl Mutation {
             balance,
             ..
         } = ctx.data::<AppContext>()?;
-        let fireblocks = ctx.data::<Fireblocks>()?;
+        let custody = ctx.data::<CustodyClient>()?;
         let credits = ctx.data::<CreditsClient<Actions>>()?;
         let conn = db.get();
         let producer = ctx.data::<Producer<TreasuryEvents>>()?;
@@ -51,35 +59,15 @@ impl Mutation {
             .0
             .ok_or(Error::new("X-CREDIT-BALANCE header not found"))?;
 
-        let (customer_treasury, treasury) = customer_treasuries::Entity::find()
-            .join(
-                JoinType::InnerJoin,
-                customer_treasuries::Relation::Treasuries.def(),
-            )
-            .filter(customer_treasuries::Column::CustomerId.eq(customer))
-            .select_also(treasuries::Entity)
-            .one(conn)
-            .await?
-            .ok_or(Error::new("customer treasury not found"))?;
-
-        let treasury = treasury.ok_or(Error::new("treasury not found"))?;
-
-        let wallet = Wallets::find()
-            .filter(
-                wallets::Column::TreasuryId
-                    .eq(treasury.id)
-                    .and(wallets::Column::AssetId.eq(asset_type)),
-            )
-            .one(conn)
-            .await?;
+        organization_credit_balances::Entity::record(conn, org_id, balance).await?;
 
-        if let Some(wallet) = wallet {
-            if wallet.address.is_some() {
-                return Err(Error::new(format!(
-                    "wallet already exists for customer {customer} and asset type {asset_type} "
-                )));
-            }
-        }
+        let request = WalletRequest::find(conn, customer, asset_type).await?;
+        let saga = WalletSaga::new(
+            db.clone(),
+            custody.clone(),
+            credits.clone(),
+            producer.clone(),
+        );
 
         let TransactionId(credits_deduction_id) = credits
             .submit_pending_deduction(
@@ -91,60 +79,335 @@ impl Mutation {
             )
             .await?;
 
-        let vault_asset = fireblocks
-            .client()
-            .create()
-            .wallet(
-                treasury.vault_id.clone(),
-                fireblocks.assets().id(asset_type.as_str()),
-                CreateVaultWallet {
-                    eos_account_name: None,
-                },
-            )
+        let wallet = saga
+            .create(request, org_id, user_id, credits_deduction_id)
             .await?;
 
-        credits
-            .confirm_deduction(TransactionId(credits_deduction_id))
-            .await?;
+        Ok(CreateCustomerWalletPayload { wallet })
+    }
 
-        let wallet = wallets::ActiveModel {
-            treasury_id: Set(treasury.id),
-            address: Set(Some(vault_asset.address.clone())),
-            created_at: Set(Utc::now().into()),
-            removed_at: Set(None),
-            created_by: Set(user_id),
-            asset_id: Set(asset_type),
-            deduction_id: Set(Some(credits_deduction_id)),
-            ..Default::default()
+    /// Create wallets for many customers at once. Up to 1000 wallets are created per call, 8 at a time.
+    /// Every wallet is reported on individually, so a failing wallet does not fail the others.
+    /// With `batchDeduction` set the credits for every wallet are reserved before any wallet is created, and no wallet is created unless all of them could be reserved.
+    /// The credits service has no API to release a reservation, so the reservations made before a batch fails are left pending. They are never confirmed and are recorded as failed wallet creations.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the request headers are missing or too many wallets are requested.
+    pub async fn create_customer_wallets(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateCustomerWalletsInput,
+    ) -> Result<CreateCustomerWalletsPayload> {
+        let AppContext {
+            db,
+            user_id,
+            organization_id,
+            balance,
+            ..
+        } = ctx.data::<AppContext>()?;
+        let custody = ctx.data::<CustodyClient>()?;
+        let credits = ctx.data::<CreditsClient<Actions>>()?;
+        let conn = db.get();
+        let producer = ctx.data::<Producer<TreasuryEvents>>()?;
+        let CreateCustomerWalletsInput {
+            wallets: inputs,
+            batch_deduction,
+        } = input;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+        let org_id = organization_id
+            .0
+            .ok_or(Error::new("X-ORGANIZATION-ID header not found"))?;
+        let balance = balance
+            .0
+            .ok_or(Error::new("X-CREDIT-BALANCE header not found"))?;
+
+        organization_credit_balances::Entity::record(conn, org_id, balance).await?;
+
+        if inputs.len() > MAX_BULK_WALLETS {
+            return Err(Error::new(format!(
+                "at most {MAX_BULK_WALLETS} wallets may be created at once"
+            )));
+        }
+
+        let saga = WalletSaga::new(
+            db.clone(),
+            custody.clone(),
+            credits.clone(),
+            producer.clone(),
+        );
+        let saga = &saga;
+        let mut seen = HashSet::new();
+
+        let requests: Vec<Result<WalletRequest>> = stream::iter(inputs.iter().copied())
+            .map(|CreateCustomerWalletInput { customer, asset_type }| {
+                let duplicate = !seen.insert((customer, asset_type));
+
+                async move {
+                    if duplicate {
+                        return Err(Error::new(format!(
+                            "wallet for customer {customer} and asset type {asset_type} is requested more than once"
+                        )));
+                    }
+
+                    Ok(WalletRequest::find(conn, customer, asset_type).await?)
+                }
+            })
+            .buffered(BULK_WALLETS_CONCURRENCY)
+            .collect()
+            .await;
+
+        let deduct = |request: &WalletRequest| {
+            credits.submit_pending_deduction(
+                org_id,
+                user_id,
+                Actions::CreateWallet,
+                request.asset_type().into(),
+                balance,
+            )
+        };
+
+        let results: Vec<Result<wallets::Model>> = if batch_deduction {
+            // Credits are reserved one wallet at a time and reserving stops at the first failure,
+            // so that as few deductions as possible are left pending when the batch fails.
+            let mut deductions: Vec<Option<Uuid>> = Vec::with_capacity(requests.len());
+            let mut failure = None;
+
+            for request in &requests {
+                let Ok(request) = request else {
+                    deductions.push(None);
+                    continue;
+                };
+
+                match deduct(request).await {
+                    Ok(TransactionId(id)) => deductions.push(Some(id)),
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    },
+                }
+            }
+
+            if let Some(e) = failure {
+                let message = format!("credits could not be reserved for the batch: {e}");
+                let mut results = Vec::with_capacity(requests.len());
+
+                // The credits service has no API to cancel a pending deduction. The deductions
+                // already submitted are never confirmed, and are recorded as failed wallet
+                // creations so they can be found and settled with the credits service.
+                for (request, deduction) in requests
+                    .into_iter()
+                    .zip(deductions.into_iter().chain(std::iter::repeat(None)))
+                {
+                    let result = match (request, deduction) {
+                        (Ok(request), Some(credits_deduction_id)) => saga
+                            .skip(
+                                request,
+                                org_id,
+                                user_id,
+                                Some(credits_deduction_id),
+                                message.clone(),
+                            )
+                            .await
+                            .map_err(Into::into)
+                            .and(Err(Error::new(message.clone()))),
+                        (request, _) => request.and(Err(Error::new(message.clone()))),
+                    };
+
+                    results.push(result);
+                }
+
+                results
+            } else {
+                stream::iter(requests.into_iter().zip(deductions))
+                    .map(|(request, deduction)| async move {
+                        let request = request?;
+                        let credits_deduction_id =
+                            deduction.ok_or(Error::new("credits were not reserved"))?;
+
+                        Ok(saga
+                            .create(request, org_id, user_id, credits_deduction_id)
+                            .await?)
+                    })
+                    .buffered(BULK_WALLETS_CONCURRENCY)
+                    .collect()
+                    .await
+            }
+        } else {
+            stream::iter(requests)
+                .map(|request| async move {
+                    let request = request?;
+                    let TransactionId(credits_deduction_id) = deduct(&request).await?;
+
+                    Ok(saga
+                        .create(request, org_id, user_id, credits_deduction_id)
+                        .await?)
+                })
+                .buffered(BULK_WALLETS_CONCURRENCY)
+                .collect()
+                .await
         };
-        let wallet = wallet.insert(conn).await?;
+
+        let results = inputs
+            .into_iter()
+            .zip(results)
+            .map(|(input, result)| {
+                let (wallet, error) = match result {
+                    Ok(wallet) => (Some(wallet), None),
+                    Err(e) => (None, Some(e.message)),
+                };
+
+                CreateCustomerWalletResult {
+                    customer: input.customer,
+                    asset_type: input.asset_type,
+                    wallet,
+                    error,
+                }
+            })
+            .collect();
+
+        Ok(CreateCustomerWalletsPayload { results })
+    }
+
+    /// Archive a customer's wallet. Archived wallets are hidden unless `includeArchived` is requested and a wallet of the same asset type may be created again.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the wallet is not a customer wallet, it is already archived or it is unable to interact with the database.
+    pub async fn archive_customer_wallet(
+        &self,
+        ctx: &Context<'_>,
+        input: ArchiveCustomerWalletInput,
+    ) -> Result<ArchiveCustomerWalletPayload> {
+        let AppContext { db, user_id, .. } = ctx.data::<AppContext>()?;
+        let conn = db.get();
+        let producer = ctx.data::<Producer<TreasuryEvents>>()?;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+
+        let wallet = Wallets::find_by_id(input.id)
+            .one(conn)
+            .await?
+            .ok_or(Error::new("wallet not found"))?;
+
+        if wallet.removed_at.is_some() {
+            return Err(Error::new("wallet is already archived"));
+        }
+
+        let customer_treasury = customer_treasuries::Entity::find()
+            .filter(customer_treasuries::Column::TreasuryId.eq(wallet.treasury_id))
+            .one(conn)
+            .await?
+            .ok_or(Error::new("wallet does not belong to a customer"))?;
+
+        let mut active_model: wallets::ActiveModel = wallet.into();
+        active_model.removed_at = Set(Some(Utc::now().into()));
+        let wallet = active_model.update(conn).await?;
 
         let project_id = customer_treasury.project_id.to_string();
 
         let event = TreasuryEvents {
-            event: Some(treasury_events::Event::CustomerWalletCreated(
+            event: Some(treasury_events::Event::CustomerWalletArchived(
                 treasury_events::CustomerWallet {
                     project_id: project_id.clone(),
                     customer_id: customer_treasury.customer_id.to_string(),
-                    blockchain: asset_type.into(),
-                    wallet_address: vault_asset.address,
+                    blockchain: wallet.asset_id.into(),
+                    wallet_address: wallet.address.clone().unwrap_or_default(),
                 },
             )),
         };
         let key = TreasuryEventKey {
-            id: treasury.id.to_string(),
+            id: wallet.treasury_id.to_string(),
             user_id: user_id.to_string(),
             project_id,
         };
 
         producer.send(Some(&event), Some(&key)).await?;
 
-        Ok(CreateCustomerWalletPayload { wallet })
+        Ok(ArchiveCustomerWalletPayload { wallet })
+    }
+
+    /// Activate a fungible token, such as `USDC_SOL`, on an existing project or customer treasury. The token wallet shares the address of the treasury's wallet on the token's chain and shows up in the treasury's wallets.
+    /// Activating a token that is already active returns its wallet.
+    ///
+    /// # Errors
+    /// The mutation will result in an error if the asset type is not a token, the treasury does not exist or has no wallet on the token's chain, the X-USER-ID header is missing or it is unable to interact with the database or communicate with Fireblocks.
+    pub async fn activate_treasury_token(
+        &self,
+        ctx: &Context<'_>,
+        input: ActivateTreasuryTokenInput,
+    ) -> Result<ActivateTreasuryTokenPayload> {
+        let AppContext { db, user_id, .. } = ctx.data::<AppContext>()?;
+        let custody = ctx.data::<CustodyClient>()?;
+        let conn = db.get();
+        let ActivateTreasuryTokenInput {
+            treasury,
+            asset_type,
+        } = input;
+
+        let user_id = user_id.0.ok_or(Error::new("X-USER-ID header not found"))?;
+
+        let token = asset_type
+            .token()
+            .ok_or_else(|| Error::new(format!("{asset_type} is not a token")))?;
+
+        let treasury = treasuries::Entity::find_by_id(treasury)
+            .one(conn)
+            .await?
+            .ok_or(Error::new("treasury not found"))?;
+
+        let chain_wallet = Wallets::find()
+            .filter(wallets::Column::TreasuryId.eq(treasury.id))
+            .filter(wallets::Column::AssetId.eq(token.chain))
+            .filter(wallets::Column::RemovedAt.is_null())
+            .filter(wallets::Column::Address.is_not_null())
+            .one(conn)
+            .await?;
+
+        if chain_wallet.is_none() {
+            return Err(Error::new(format!(
+                "treasury has no {} wallet to activate {asset_type} on",
+                token.chain
+            )));
+        }
+
+        let wallet = Wallets::find()
+            .filter(wallets::Column::TreasuryId.eq(treasury.id))
+            .filter(wallets::Column::AssetId.eq(asset_type))
+            .filter(wallets::Column::RemovedAt.is_null())
+            .one(conn)
+            .await?;
+
+        if let Some(wallet) = wallet {
+            return Ok(ActivateTreasuryTokenPayload { wallet });
+        }
+
+        let vault = custody.vault(treasury.vault_id).await?;
+        let address = custody
+            .find_or_create_wallet(&vault, custody.asset_id(asset_type.as_str()))
+            .await?;
+
+        let wallet = wallets::ActiveModel {
+            treasury_id: Set(treasury.id),
+            asset_id: Set(asset_type),
+            address: Set(Some(address)),
+            created_by: Set(user_id),
+            deduction_id: Set(None),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await?;
+
+        Ok(ActivateTreasuryTokenPayload { wallet })
     }
 }
 
+/// The most wallets created by a single `createCustomerWallets` call.
+const MAX_BULK_WALLETS: usize = 1000;
+/// How many wallets of a `createCustomerWallets` call are created at the same time.
+const BULK_WALLETS_CONCURRENCY: usize = 8;
+
 /// Input for creating a customer wallet.
-#[derive(InputObject, Clone, Debug)]
+#[derive(InputObject, Clone, Copy, Debug)]
 pub struct CreateCustomerWalletInput {
     /// The customer ID.
     pub customer: Uuid,
@@ -159,12 +422,68 @@ pub struct CreateCustomerWalletPayload {
     pub wallet: wallets::Model,
 }
 
+/// Input for creating wallets for many customers.
+#[derive(InputObject, Clone, Debug)]
+pub struct CreateCustomerWalletsInput {
+    /// The customers and asset types to create wallets for.
+    pub wallets: Vec<CreateCustomerWalletInput>,
+    /// Reserve the credits for every wallet before creating any of them.
+    #[graphql(default = false)]
+    pub batch_deduction: bool,
+}
+
+/// The outcome of creating one of the wallets of a `createCustomerWallets` call.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct CreateCustomerWalletResult {
+    /// The customer ID.
+    pub customer: Uuid,
+    /// The requested asset type.
+    pub asset_type: wallets::AssetType,
+    /// The created wallet, unless creating it failed.
+    pub wallet: Option<wallets::Model>,
+    /// Why the wallet could not be created.
+    pub error: Option<String>,
+}
+
+/// Response after creating wallets for many customers.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct CreateCustomerWalletsPayload {
+    /// The outcome for each requested wallet, in the order they were requested.
+    pub results: Vec<CreateCustomerWalletResult>,
+}
+
+/// Input for archiving a customer wallet.
+#[derive(InputObject, Clone, Debug)]
+pub struct ArchiveCustomerWalletInput {
+    /// The ID of the wallet to archive.
+    pub id: Uuid,
+}
+
+/// Response after archiving a customer wallet.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ArchiveCustomerWalletPayload {
+    /// The archived wallet.
+    pub wallet: wallets::Model,
+}
+
+/// Input for activating a token on a treasury.
+#[derive(InputObject, Clone, Copy, Debug)]
+pub struct ActivateTreasuryTokenInput {
+    /// The ID of the treasury.
+    pub treasury: Uuid,
+    /// The token to activate, e.g. `USDC_SOL`.
+    pub asset_type: wallets::AssetType,
+}
+
+/// Response after activating a token on a treasury.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ActivateTreasuryTokenPayload {
+    /// The token wallet of the treasury.
+    pub wallet: wallets::Model,
+}
+
 impl From<wallets::AssetType> for treasury_events::Blockchain {
     fn from(value: wallets::AssetType) -> Self {
-        match value {
-            wallets::AssetType::Solana => treasury_events::Blockchain::Solana,
-            wallets::AssetType::Matic => treasury_events::Blockchain::Polygon,
-            wallets::AssetType::Eth => treasury_events::Blockchain::Ethereum,
-        }
+        value.chain().event_blockchain
     }
 }
@@ -0,0 +1,39 @@
+use std::str::FromStr;
+
+use async_graphql::SimpleObject;
+
+use crate::{custody::AssetBalance, entities::wallets::AssetType};
+
+/// The balance of an asset held in a wallet, as reported by the custodian.
+/// Amounts are decimal strings denominated in the asset, e.g. `1.5` SOL.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WalletBalance {
+    /// The custodian asset ID of the balance.
+    pub asset_id: String,
+    /// The asset type of the balance, unless the asset is not supported by the service.
+    pub asset_type: Option<AssetType>,
+    /// The total amount held.
+    pub total: String,
+    /// The amount that can be spent.
+    pub available: String,
+    /// The amount of incoming transactions that are not confirmed yet.
+    pub pending: String,
+    /// The amount frozen by AML policies.
+    pub frozen: String,
+    /// The amount locked by pending outgoing transactions.
+    pub locked: String,
+}
+
+impl From<AssetBalance> for WalletBalance {
+    fn from(asset: AssetBalance) -> Self {
+        Self {
+            asset_type: AssetType::from_str(&asset.id).ok(),
+            asset_id: asset.id,
+            total: asset.total,
+            available: asset.available,
+            pending: asset.pending,
+            frozen: asset.frozen,
+            locked: asset.locked,
+        }
+    }
+}
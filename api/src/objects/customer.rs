@@ -42,23 +42,21 @@ impl Customer {
         &self,
         ctx: &Context<'_>,
         asset_id: Option<AssetType>,
+        #[graphql(default = false)] include_archived: bool,
     ) -> Result<Option<Vec<wallets::Model>>> {
         let AppContext {
             customer_treasury_wallet_loader,
             ..
         } = ctx.data::<AppContext>()?;
 
-        let mut wallets = customer_treasury_wallet_loader.load_one(self.id).await?;
+        let wallets = customer_treasury_wallet_loader.load_one(self.id).await?;
 
-        if let Some(asset_id) = asset_id {
-            wallets = wallets.clone().map(|w| {
-                w.iter()
-                    .filter(|wallet| wallet.asset_id == asset_id)
-                    .cloned()
-                    .collect::<Vec<_>>()
-            });
-        };
-
-        Ok(wallets)
+        Ok(wallets.map(|wallets| {
+            wallets
+                .into_iter()
+                .filter(|wallet| asset_id.map_or(true, |asset_id| wallet.asset_id == asset_id))
+                .filter(|wallet| include_archived || !wallet.is_archived())
+                .collect()
+        }))
     }
 }
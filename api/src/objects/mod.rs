@@ -1,7 +1,11 @@
 #![allow(clippy::missing_errors_doc)]
 
+mod balance;
 mod customer;
 mod project;
+mod transaction;
 
+pub use balance::WalletBalance;
 pub use customer::Customer;
 pub use project::Project;
+pub use transaction::{Transaction, TransactionStatusChange};
@@ -1,7 +1,19 @@
 use async_graphql::{ComplexObject, Context, Result, SimpleObject};
-use hub_core::uuid::Uuid;
+use fireblocks::objects::transaction::TransactionStatus;
+use hub_core::{
+    chrono::{DateTime, Utc},
+    uuid::Uuid,
+};
+use sea_orm::prelude::*;
 
-use crate::{entities::treasuries, AppContext};
+use crate::{
+    entities::{
+        auto_provisioned_assets, project_balance_thresholds, project_settings, treasuries,
+        wallets::AssetType,
+    },
+    objects::Transaction,
+    AppContext,
+};
 
 #[derive(Debug, Clone, SimpleObject)]
 #[graphql(complex)]
@@ -20,4 +32,59 @@ impl Project {
 
         project_treasury_loader.load_one(self.id).await
     }
+
+    /// The low balance thresholds set for the project's treasury. Assets without a threshold use the default of the service.
+    pub async fn balance_thresholds(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<project_balance_thresholds::Model>> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        let thresholds = project_balance_thresholds::Entity::find()
+            .filter(project_balance_thresholds::Column::ProjectId.eq(self.id))
+            .all(db.get())
+            .await?;
+
+        Ok(thresholds)
+    }
+
+    /// The asset types a wallet is created for when a customer of the project is created.
+    pub async fn auto_provisioned_assets(&self, ctx: &Context<'_>) -> Result<Vec<AssetType>> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        let assets = auto_provisioned_assets::Entity::find()
+            .filter(auto_provisioned_assets::Column::ProjectId.eq(self.id))
+            .all(db.get())
+            .await?;
+
+        Ok(assets.into_iter().map(|asset| asset.asset_id).collect())
+    }
+
+    /// The custody options of the project. Projects without settings use the defaults of the service.
+    pub async fn settings(&self, ctx: &Context<'_>) -> Result<project_settings::Model> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        let settings = project_settings::Entity::find_by_id(self.id)
+            .one(db.get())
+            .await?;
+
+        Ok(settings.unwrap_or_else(|| project_settings::Model::defaults(self.id)))
+    }
+
+    /// The transactions submitted for the project, oldest first.
+    pub async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return transactions currently in this status.")] status: Option<
+            TransactionStatus,
+        >,
+        #[graphql(desc = "Only return transactions submitted after this datetime.")] after: Option<
+            DateTime<Utc>,
+        >,
+        #[graphql(default = 50, desc = "The most transactions to return, at most 100.")] limit: u64,
+    ) -> Result<Vec<Transaction>> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        Transaction::list(db, self.id, status, after, limit).await
+    }
 }
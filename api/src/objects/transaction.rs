@@ -0,0 +1,168 @@
+use async_graphql::{ComplexObject, Context, Result, SimpleObject};
+use fireblocks::objects::transaction::TransactionStatus;
+use hub_core::{
+    chrono::{DateTime, Utc},
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+
+use crate::{
+    db::Connection,
+    entities::{sea_orm_active_enums::TxStatus, transaction_status_changes, transactions},
+    AppContext,
+};
+
+/// The most transactions returned by a single query.
+pub const MAX_LIMIT: u64 = 100;
+
+/// A transaction submitted to the custodian to sign or send on behalf of a project.
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Transaction {
+    /// The ID of the transaction in Fireblocks.
+    pub fireblocks_id: Uuid,
+    /// The kind of transaction.
+    pub tx_type: String,
+    /// The current status of the transaction.
+    pub status: TransactionStatus,
+    /// The Fireblocks sub status explaining the current status, if any.
+    pub sub_status: Option<String>,
+    /// The hash of the transaction once it was broadcast.
+    pub tx_hash: Option<String>,
+    /// The signatures of the messages signed by the transaction.
+    pub signed_messages: Vec<String>,
+    /// The project the transaction was submitted for.
+    pub project_id: Uuid,
+    /// The vault that signed the transaction.
+    pub vault_id: String,
+    /// The Fireblocks asset of the transaction.
+    pub asset_id: String,
+    /// The ID in the key of the event the transaction was submitted for, e.g. the ID of a mint.
+    pub event_id: String,
+    /// The kind of event the transaction was submitted for.
+    pub event_kind: Option<String>,
+    /// The ID sent to Fireblocks to reject duplicate submissions.
+    pub external_tx_id: Option<String>,
+    /// The datetime the transaction was submitted.
+    pub created_at: DateTimeWithTimeZone,
+    /// The datetime the status of the transaction was last updated.
+    pub updated_at: Option<DateTimeWithTimeZone>,
+}
+
+#[ComplexObject]
+impl Transaction {
+    /// The statuses the transaction went through, oldest first.
+    async fn history(&self, ctx: &Context<'_>) -> Result<Vec<TransactionStatusChange>> {
+        let AppContext {
+            transaction_history_loader,
+            ..
+        } = ctx.data::<AppContext>()?;
+
+        let changes = transaction_history_loader
+            .load_one(self.fireblocks_id)
+            .await?
+            .unwrap_or_default();
+
+        Ok(changes.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Transaction {
+    /// Loads the transactions of a project, oldest first. At most [`MAX_LIMIT`] transactions are
+    /// loaded whatever the `limit`.
+    ///
+    /// # Errors
+    /// Returns an error if the transactions cannot be loaded.
+    pub async fn list(
+        db: &Connection,
+        project_id: Uuid,
+        status: Option<TransactionStatus>,
+        after: Option<DateTime<Utc>>,
+        limit: u64,
+    ) -> Result<Vec<Self>> {
+        let mut query =
+            transactions::Entity::find().filter(transactions::Column::ProjectId.eq(project_id));
+
+        if let Some(status) = status {
+            query = query.filter(transactions::Column::Status.eq(TxStatus::from(status)));
+        }
+
+        if let Some(after) = after {
+            query = query.filter(transactions::Column::CreatedAt.gt(after));
+        }
+
+        let transactions = query
+            .order_by_asc(transactions::Column::CreatedAt)
+            .limit(limit.min(MAX_LIMIT))
+            .all(db.get())
+            .await?;
+
+        Ok(transactions.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<transactions::Model> for Transaction {
+    fn from(
+        transactions::Model {
+            fireblocks_id,
+            tx_type,
+            created_at,
+            signatures,
+            event_id,
+            project_id,
+            vault_id,
+            asset_id,
+            status,
+            tx_hash,
+            updated_at,
+            external_tx_id,
+            sub_status,
+            event_kind,
+        }: transactions::Model,
+    ) -> Self {
+        Self {
+            fireblocks_id,
+            tx_type: tx_type.into(),
+            status: status.into(),
+            sub_status,
+            tx_hash,
+            signed_messages: signatures,
+            project_id,
+            vault_id,
+            asset_id,
+            event_id,
+            event_kind,
+            external_tx_id,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+/// A status a transaction was observed in.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TransactionStatusChange {
+    /// The status of the transaction.
+    pub status: TransactionStatus,
+    /// The Fireblocks sub status explaining the status, if any.
+    pub sub_status: Option<String>,
+    /// The datetime the status was first observed.
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl From<transaction_status_changes::Model> for TransactionStatusChange {
+    fn from(
+        transaction_status_changes::Model {
+            status,
+            sub_status,
+            created_at,
+            ..
+        }: transaction_status_changes::Model,
+    ) -> Self {
+        Self {
+            status: status.into(),
+            sub_status: Some(sub_status).filter(|sub_status| !sub_status.is_empty()),
+            created_at,
+        }
+    }
+}
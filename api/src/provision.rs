@@ -0,0 +1,428 @@
+//! Customer wallet creation, run as a saga across the credits service, the custodian, the
+//! database and the event stream.
+//!
+//! A wallet's credits are reserved by a pending deduction before anything else happens, and the
+//! deduction is only confirmed once the wallet is recorded. A wallet the custodian cannot create
+//! is marked as failed and its deduction is never confirmed. The credits client of hub-core has no
+//! API to cancel or refund a pending deduction, so the deduction is left pending and the failed
+//! creation, which shares its ID, is the record of it. Every completed step is recorded in `wallet_creations`, so a creation interrupted after
+//! the custodian created the wallet is carried to completion by [`WalletSaga::reconcile`] instead
+//! of charging for a wallet that is never recorded.
+
+use hub_core::{
+    anyhow::{anyhow, bail, Result},
+    chrono::Utc,
+    credits::{CreditsClient, TransactionId},
+    prelude::*,
+    producer::Producer,
+    uuid::Uuid,
+};
+use sea_orm::{
+    prelude::*, sea_query::OnConflict, DatabaseConnection, JoinType, QueryOrder, QuerySelect, Set,
+};
+
+use crate::{
+    custody::CustodyClient,
+    db::Connection,
+    entities::{
+        customer_treasuries, sea_orm_active_enums::WalletCreationStatus, treasuries,
+        wallet_creations, wallets,
+    },
+    proto::{treasury_events, TreasuryEventKey, TreasuryEvents},
+    Actions,
+};
+
+/// Statuses of wallet creations that have not completed or failed yet. A unique index on
+/// `wallet_creations` allows one creation in these statuses per treasury and asset type.
+pub(crate) const IN_PROGRESS: [WalletCreationStatus; 4] = [
+    WalletCreationStatus::Reserved,
+    WalletCreationStatus::Provisioned,
+    WalletCreationStatus::Recorded,
+    WalletCreationStatus::Charged,
+];
+
+/// A wallet to create in a customer's treasury.
+#[derive(Debug, Clone)]
+pub struct WalletRequest {
+    customer_treasury: customer_treasuries::Model,
+    treasury: treasuries::Model,
+    asset_type: wallets::AssetType,
+    archived_address: Option<String>,
+}
+
+impl WalletRequest {
+    /// Looks up the treasury of the customer and checks it has no wallet for the asset type yet.
+    ///
+    /// # Errors
+    /// Returns an error if the asset type is a token, the customer has no treasury, already has a
+    /// wallet for the asset type or one is being created.
+    pub async fn find(
+        conn: &DatabaseConnection,
+        customer: Uuid,
+        asset_type: wallets::AssetType,
+    ) -> Result<Self> {
+        if asset_type.token().is_some() {
+            bail!("{asset_type} is a token and is activated on a treasury instead of created");
+        }
+
+        let (customer_treasury, treasury) = customer_treasuries::Entity::find()
+            .join(
+                JoinType::InnerJoin,
+                customer_treasuries::Relation::Treasuries.def(),
+            )
+            .filter(customer_treasuries::Column::CustomerId.eq(customer))
+            .select_also(treasuries::Entity)
+            .one(conn)
+            .await?
+            .ok_or_else(|| anyhow!("customer treasury not found"))?;
+
+        let treasury = treasury.ok_or_else(|| anyhow!("treasury not found"))?;
+
+        let wallet = wallets::Entity::find()
+            .filter(
+                wallets::Column::TreasuryId
+                    .eq(treasury.id)
+                    .and(wallets::Column::AssetId.eq(asset_type))
+                    .and(wallets::Column::RemovedAt.is_null()),
+            )
+            .one(conn)
+            .await?;
+
+        if let Some(wallet) = wallet {
+            if wallet.address.is_some() {
+                bail!("wallet already exists for customer {customer} and asset type {asset_type} ");
+            }
+        }
+
+        let in_progress = wallet_creations::Entity::find()
+            .filter(
+                wallet_creations::Column::TreasuryId
+                    .eq(treasury.id)
+                    .and(wallet_creations::Column::AssetId.eq(asset_type))
+                    .and(wallet_creations::Column::Status.is_in(IN_PROGRESS)),
+            )
+            .one(conn)
+            .await?;
+
+        if in_progress.is_some() {
+            bail!("wallet for customer {customer} and asset type {asset_type} is being created");
+        }
+
+        let archived_address = wallets::Entity::find()
+            .filter(
+                wallets::Column::TreasuryId
+                    .eq(treasury.id)
+                    .and(wallets::Column::AssetId.eq(asset_type))
+                    .and(wallets::Column::RemovedAt.is_not_null())
+                    .and(wallets::Column::Address.is_not_null()),
+            )
+            .order_by_desc(wallets::Column::RemovedAt)
+            .one(conn)
+            .await?
+            .and_then(|wallet| wallet.address);
+
+        Ok(Self {
+            customer_treasury,
+            treasury,
+            asset_type,
+            archived_address,
+        })
+    }
+
+    #[must_use]
+    pub fn asset_type(&self) -> wallets::AssetType {
+        self.asset_type
+    }
+}
+
+/// Carries customer wallet creations through each of their steps.
+#[derive(Clone)]
+pub struct WalletSaga {
+    db: Connection,
+    custody: CustodyClient,
+    credits: CreditsClient<Actions>,
+    producer: Producer<TreasuryEvents>,
+}
+
+impl WalletSaga {
+    #[must_use]
+    pub fn new(
+        db: Connection,
+        custody: CustodyClient,
+        credits: CreditsClient<Actions>,
+        producer: Producer<TreasuryEvents>,
+    ) -> Self {
+        Self {
+            db,
+            custody,
+            credits,
+            producer,
+        }
+    }
+
+    /// Creates the wallet of a request whose credits are reserved by the pending deduction
+    /// `credits_deduction_id`. The deduction is only confirmed once the wallet is recorded.
+    ///
+    /// # Errors
+    /// Returns an error if a step of the creation fails or another creation of the wallet is in
+    /// progress. A creation failing after the custodian created the wallet is completed by
+    /// [`Self::reconcile`].
+    pub async fn create(
+        &self,
+        request: WalletRequest,
+        organization_id: Uuid,
+        user_id: Uuid,
+        credits_deduction_id: Uuid,
+    ) -> Result<wallets::Model> {
+        let WalletRequest {
+            customer_treasury,
+            treasury,
+            asset_type,
+            archived_address,
+        } = request;
+
+        let creation = wallet_creations::ActiveModel {
+            id: Set(credits_deduction_id),
+            organization_id: Set(organization_id),
+            user_id: Set(user_id),
+            customer_id: Set(customer_treasury.customer_id),
+            project_id: Set(customer_treasury.project_id),
+            treasury_id: Set(treasury.id),
+            asset_id: Set(asset_type),
+            address: Set(archived_address),
+            status: Set(WalletCreationStatus::Reserved),
+            error: Set(None),
+            ..Default::default()
+        }
+        .insert(self.db.get())
+        .await?;
+
+        self.resume(creation).await
+    }
+
+    /// Records a wallet of a request that is not created, e.g. because its credits could not be
+    /// reserved. The creation is marked as failed with `reason`.
+    ///
+    /// A pending deduction already submitted for the wallet is recorded as the ID of the creation
+    /// and never confirmed. The credits service has no API to cancel it.
+    ///
+    /// # Errors
+    /// Returns an error if the creation cannot be recorded.
+    pub async fn skip(
+        &self,
+        request: WalletRequest,
+        organization_id: Uuid,
+        user_id: Uuid,
+        credits_deduction_id: Option<Uuid>,
+        reason: String,
+    ) -> Result<()> {
+        let WalletRequest {
+            customer_treasury,
+            treasury,
+            asset_type,
+            ..
+        } = request;
+
+        wallet_creations::ActiveModel {
+            id: Set(credits_deduction_id.unwrap_or_else(Uuid::new_v4)),
+            organization_id: Set(organization_id),
+            user_id: Set(user_id),
+            customer_id: Set(customer_treasury.customer_id),
+            project_id: Set(customer_treasury.project_id),
+            treasury_id: Set(treasury.id),
+            asset_id: Set(asset_type),
+            address: Set(None),
+            status: Set(WalletCreationStatus::Failed),
+            error: Set(Some(reason)),
+            ..Default::default()
+        }
+        .insert(self.db.get())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Completes the remaining steps of a wallet creation.
+    ///
+    /// # Errors
+    /// Returns an error if a step fails or the creation has failed before.
+    pub async fn resume(&self, mut creation: wallet_creations::Model) -> Result<wallets::Model> {
+        loop {
+            creation = match creation.status {
+                WalletCreationStatus::Reserved => self.provision(creation).await?,
+                WalletCreationStatus::Provisioned => self.record(creation).await?,
+                WalletCreationStatus::Recorded => self.charge(creation).await?,
+                WalletCreationStatus::Charged => self.announce(creation).await?,
+                WalletCreationStatus::Completed => {
+                    return wallets::Entity::find_by_id(creation.id)
+                        .one(self.db.get())
+                        .await?
+                        .ok_or_else(|| anyhow!("wallet {} not found", creation.id));
+                },
+                WalletCreationStatus::Failed => bail!(
+                    "wallet creation {} failed: {}",
+                    creation.id,
+                    creation.error.unwrap_or_default()
+                ),
+            }
+        }
+    }
+
+    /// Completes wallet creations interrupted at least `stale_after` ago, e.g. by a restart.
+    ///
+    /// # Errors
+    /// Returns an error if the interrupted creations cannot be loaded.
+    pub async fn reconcile(&self, stale_after: Duration) -> Result<()> {
+        let cutoff = Utc::now() - hub_core::chrono::Duration::from_std(stale_after)?;
+
+        let creations = wallet_creations::Entity::find()
+            .filter(
+                wallet_creations::Column::Status
+                    .is_in(IN_PROGRESS)
+                    .and(wallet_creations::Column::UpdatedAt.lt(cutoff)),
+            )
+            .order_by_asc(wallet_creations::Column::CreatedAt)
+            .all(self.db.get())
+            .await?;
+
+        for creation in creations {
+            let id = creation.id;
+            let status = creation.status;
+
+            match self.resume(creation).await {
+                Ok(_) => info!("completed wallet creation {id} interrupted while {status:?}"),
+                Err(e) => error!("failed to complete wallet creation {id}: {e:?}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Obtains the address of the wallet from the custodian unless an archived wallet's address
+    /// is restored. A wallet the vault already holds, e.g. because an interrupted creation created
+    /// it, is reused. A wallet the custodian cannot create fails and its deduction is left pending.
+    async fn provision(
+        &self,
+        creation: wallet_creations::Model,
+    ) -> Result<wallet_creations::Model> {
+        // Fireblocks derives a single address per asset and vault, so a wallet created again after
+        // being archived restores the address of the archived wallet.
+        if let Some(address) = creation.address.clone() {
+            return self
+                .advance(creation, WalletCreationStatus::Provisioned, Some(address))
+                .await;
+        }
+
+        let created = async {
+            let treasury = treasuries::Entity::find_by_id(creation.treasury_id)
+                .one(self.db.get())
+                .await?
+                .ok_or_else(|| anyhow!("treasury not found"))?;
+
+            let vault = self.custody.vault(treasury.vault_id).await?;
+
+            self.custody
+                .find_or_create_wallet(&vault, self.custody.asset_id(creation.asset_id.as_str()))
+                .await
+        }
+        .await;
+
+        match created {
+            Ok(address) => {
+                self.advance(creation, WalletCreationStatus::Provisioned, Some(address))
+                    .await
+            },
+            Err(e) => {
+                let id = creation.id;
+
+                let mut active_model: wallet_creations::ActiveModel = creation.into();
+                active_model.status = Set(WalletCreationStatus::Failed);
+                active_model.error = Set(Some(format!("{e:?}")));
+                active_model.updated_at = Set(Utc::now().into());
+                active_model.update(self.db.get()).await?;
+
+                Err(e.context(format!("failed to create wallet {id}")))
+            },
+        }
+    }
+
+    /// Records the wallet in the customer's treasury. The wallet shares the ID of the creation so
+    /// that recording it again is a no-op.
+    async fn record(&self, creation: wallet_creations::Model) -> Result<wallet_creations::Model> {
+        let wallet = wallets::ActiveModel {
+            id: Set(creation.id),
+            treasury_id: Set(creation.treasury_id),
+            address: Set(creation.address.clone()),
+            created_at: Set(Utc::now().into()),
+            removed_at: Set(None),
+            created_by: Set(creation.user_id),
+            asset_id: Set(creation.asset_id),
+            deduction_id: Set(Some(creation.id)),
+        };
+
+        wallets::Entity::insert(wallet)
+            .on_conflict(
+                OnConflict::column(wallets::Column::Id)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        self.advance(creation, WalletCreationStatus::Recorded, None)
+            .await
+    }
+
+    /// Confirms the deduction of the credits reserved for the wallet.
+    async fn charge(&self, creation: wallet_creations::Model) -> Result<wallet_creations::Model> {
+        self.credits
+            .confirm_deduction(TransactionId(creation.id))
+            .await?;
+
+        self.advance(creation, WalletCreationStatus::Charged, None)
+            .await
+    }
+
+    /// Publishes the `CustomerWalletCreated` event for the wallet.
+    async fn announce(&self, creation: wallet_creations::Model) -> Result<wallet_creations::Model> {
+        let project_id = creation.project_id.to_string();
+
+        let event = TreasuryEvents {
+            event: Some(treasury_events::Event::CustomerWalletCreated(
+                treasury_events::CustomerWallet {
+                    project_id: project_id.clone(),
+                    customer_id: creation.customer_id.to_string(),
+                    blockchain: creation.asset_id.into(),
+                    wallet_address: creation.address.clone().unwrap_or_default(),
+                },
+            )),
+        };
+        let key = TreasuryEventKey {
+            id: creation.treasury_id.to_string(),
+            user_id: creation.user_id.to_string(),
+            project_id,
+        };
+
+        self.producer.send(Some(&event), Some(&key)).await?;
+
+        self.advance(creation, WalletCreationStatus::Completed, None)
+            .await
+    }
+
+    async fn advance(
+        &self,
+        creation: wallet_creations::Model,
+        status: WalletCreationStatus,
+        address: Option<String>,
+    ) -> Result<wallet_creations::Model> {
+        let mut active_model: wallet_creations::ActiveModel = creation.into();
+        active_model.status = Set(status);
+        active_model.updated_at = Set(Utc::now().into());
+
+        if let Some(address) = address {
+            active_model.address = Set(Some(address));
+        }
+
+        Ok(active_model.update(self.db.get()).await?)
+    }
+}
@@ -2,6 +2,7 @@
 
 mod customer;
 mod project;
+mod transaction;
 mod treasury;
 mod wallet;
 
@@ -10,6 +11,7 @@ mod wallet;
 pub struct Query(
     customer::Query,
     project::Query,
+    transaction::Query,
     treasury::Query,
     wallet::Query,
 );
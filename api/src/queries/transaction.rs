@@ -0,0 +1,48 @@
+use async_graphql::{Context, Object, Result};
+use fireblocks::objects::transaction::TransactionStatus;
+use hub_core::{
+    chrono::{DateTime, Utc},
+    uuid::Uuid,
+};
+use sea_orm::prelude::*;
+
+use crate::{entities::transactions, objects::Transaction, AppContext};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query;
+
+#[Object(name = "TransactionQuery")]
+impl Query {
+    /// Query the transactions submitted for a project, oldest first.
+    ///
+    /// # Errors
+    /// This function fails if the `AppContext` cannot be accessed or fails to load from the database.
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        project_id: Uuid,
+        #[graphql(desc = "Only return transactions currently in this status.")] status: Option<
+            TransactionStatus,
+        >,
+        #[graphql(desc = "Only return transactions submitted after this datetime.")] after: Option<
+            DateTime<Utc>,
+        >,
+        #[graphql(default = 50, desc = "The most transactions to return, at most 100.")] limit: u64,
+    ) -> Result<Vec<Transaction>> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        Transaction::list(db, project_id, status, after, limit).await
+    }
+
+    /// Query a transaction by its Fireblocks ID.
+    ///
+    /// # Errors
+    /// This function fails if the `AppContext` cannot be accessed or fails to load from the database.
+    async fn transaction(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Transaction>> {
+        let AppContext { db, .. } = ctx.data::<AppContext>()?;
+
+        let transaction = transactions::Entity::find_by_id(id).one(db.get()).await?;
+
+        Ok(transaction.map(Into::into))
+    }
+}
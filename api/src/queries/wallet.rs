@@ -23,22 +23,29 @@ impl Query {
         ctx: &Context<'_>,
         #[graphql(key)] address: String,
     ) -> Result<Option<Model>> {
-        self.wallet(ctx, address).await
+        self.wallet(ctx, address, true).await
     }
 
-    /// Query to find a `Wallet` by its blockchain address.
+    /// Query to find a `Wallet` by its blockchain address. Archived wallets are only returned when `includeArchived` is set.
     ///
     /// # Errors
     /// This function fails if the `AppContext` cannot be accessed,
     /// the address provided is not a valid blockchain address
     /// or fails to load from the database.
-    async fn wallet(&self, ctx: &Context<'_>, address: String) -> Result<Option<Model>> {
+    async fn wallet(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        #[graphql(default = false)] include_archived: bool,
+    ) -> Result<Option<Model>> {
         if !ValidateAddress::is_blockchain_address(&address) {
             return Err(Error::new("Invalid address"));
         }
 
         let AppContext { wallet_loader, .. } = ctx.data::<AppContext>()?;
 
-        wallet_loader.load_one(address).await
+        let wallet = wallet_loader.load_one(address).await?;
+
+        Ok(wallet.filter(|wallet| include_archived || !wallet.is_archived()))
     }
 }
@@ -0,0 +1,689 @@
+//! Reconciliation of treasuries and wallets against the vaults of the custodian.
+//!
+//! Every treasury is compared with the vault it points to, every wallet with the deposit addresses
+//! of its vault, and every vault named after a project or customer with the treasuries recorded
+//! for it. The issues found are counted in metrics and kept as the latest report for the admin
+//! API. A reconciliation may also repair the database by recording the treasuries and wallets it
+//! finds in the custodian but not in the database.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use async_graphql::{Enum, SimpleObject};
+use hub_core::{
+    anyhow::Result,
+    chrono::{DateTime, Utc},
+    clap,
+    futures_util::{stream, StreamExt, TryStreamExt},
+    prelude::*,
+    tokio::{self, sync::Mutex as AsyncMutex, time},
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, QueryOrder, Set, TransactionTrait};
+
+use crate::{
+    custody::{CustodyClient, Vault, VaultQuery},
+    db::Connection,
+    entities::{
+        auto_provisioned_assets, project_treasuries, treasuries, wallet_creations,
+        wallets::{self, AssetType},
+    },
+    metrics::Metrics,
+    provision::IN_PROGRESS,
+};
+
+/// Vaults requested from the custodian per page.
+const PAGE_SIZE: u64 = 200;
+
+/// Treasuries loaded from the database per page.
+const TREASURY_PAGE_SIZE: u64 = 100;
+
+/// Treasuries checked against the custodian at once.
+const RECONCILE_CONCURRENCY: usize = 8;
+
+#[derive(Debug, clap::Args)]
+pub struct ReconcilerArgs {
+    /// Seconds between reconciliations of treasuries against the vaults of the custodian
+    #[arg(long, env, default_value_t = 3600)]
+    pub reconcile_interval_secs: u64,
+
+    /// Record the treasuries and wallets found in the custodian but missing from the database when reconciling periodically
+    #[arg(long, env, default_value_t = false)]
+    pub reconcile_repair: bool,
+}
+
+/// A difference between the database and the vaults of the custodian.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReconciliationIssueKind {
+    /// The vault of a treasury does not exist in the custodian.
+    MissingVault,
+    /// A vault named after a project or customer is not the vault of any treasury.
+    OrphanedVault,
+    /// The address of a wallet is not a deposit address of its vault.
+    AddressMismatch,
+    /// The vault of a treasury holds an asset the treasury has no wallet for.
+    UntrackedWallet,
+}
+
+impl ReconciliationIssueKind {
+    const ALL: [Self; 4] = [
+        Self::MissingVault,
+        Self::OrphanedVault,
+        Self::AddressMismatch,
+        Self::UntrackedWallet,
+    ];
+
+    /// The label of the kind in metrics.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingVault => "missing_vault",
+            Self::OrphanedVault => "orphaned_vault",
+            Self::AddressMismatch => "address_mismatch",
+            Self::UntrackedWallet => "untracked_wallet",
+        }
+    }
+}
+
+/// A difference found by a reconciliation.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ReconciliationIssue {
+    /// The kind of difference.
+    pub kind: ReconciliationIssueKind,
+    /// The ID of the vault in the custodian.
+    pub vault_id: String,
+    /// The name of the vault, unless it is missing from the custodian.
+    pub vault_name: Option<String>,
+    /// The treasury of the vault, if one is recorded.
+    pub treasury_id: Option<Uuid>,
+    /// The wallet whose address does not match.
+    pub wallet_id: Option<Uuid>,
+    /// The asset of the wallet.
+    pub asset_type: Option<AssetType>,
+    /// The address recorded for the wallet.
+    pub recorded_address: Option<String>,
+    /// The deposit addresses of the wallet in the custodian.
+    pub custodian_addresses: Vec<String>,
+    /// Whether the reconciliation recorded the missing treasury or wallet.
+    pub repaired: bool,
+}
+
+impl ReconciliationIssue {
+    fn new(kind: ReconciliationIssueKind, vault_id: String, vault_name: Option<String>) -> Self {
+        Self {
+            kind,
+            vault_id,
+            vault_name,
+            treasury_id: None,
+            wallet_id: None,
+            asset_type: None,
+            recorded_address: None,
+            custodian_addresses: Vec::new(),
+            repaired: false,
+        }
+    }
+
+    fn of_vault(kind: ReconciliationIssueKind, vault: &Vault) -> Self {
+        Self::new(kind, vault.id.clone(), Some(vault.name.clone()))
+    }
+}
+
+/// The outcome of a reconciliation.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ReconciliationReport {
+    /// The datetime the reconciliation started.
+    pub started_at: DateTime<Utc>,
+    /// The datetime the reconciliation finished.
+    pub finished_at: DateTime<Utc>,
+    /// Whether missing treasuries and wallets were recorded.
+    pub repair: bool,
+    /// The number of vaults listed from the custodian.
+    pub vaults: usize,
+    /// The number of treasuries checked.
+    pub treasuries: usize,
+    /// The number of wallets checked.
+    pub wallets: usize,
+    /// The differences found.
+    pub issues: Vec<ReconciliationIssue>,
+}
+
+/// The owner a vault is named after when its treasury is created.
+enum VaultOwner {
+    Project(Uuid),
+    Customer(Uuid),
+}
+
+impl VaultOwner {
+    fn parse(name: &str) -> Option<Self> {
+        let (kind, id) = name.split_once(':')?;
+        let id = Uuid::parse_str(id).ok()?;
+
+        match kind {
+            "project" => Some(Self::Project(id)),
+            "customer" => Some(Self::Customer(id)),
+            _ => None,
+        }
+    }
+}
+
+/// Reconciles treasuries and wallets against the vaults of the custodian.
+#[derive(Clone)]
+pub struct Reconciler {
+    db: Connection,
+    custody: CustodyClient,
+    metrics: Metrics,
+    interval: Duration,
+    repair: bool,
+    running: Arc<AsyncMutex<()>>,
+    latest: Arc<Mutex<Option<ReconciliationReport>>>,
+}
+
+impl Reconciler {
+    #[must_use]
+    pub fn new(
+        db: Connection,
+        custody: CustodyClient,
+        metrics: Metrics,
+        args: ReconcilerArgs,
+    ) -> Self {
+        let ReconcilerArgs {
+            reconcile_interval_secs,
+            reconcile_repair,
+        } = args;
+
+        Self {
+            db,
+            custody,
+            metrics,
+            interval: Duration::from_secs(reconcile_interval_secs),
+            repair: reconcile_repair,
+            running: Arc::default(),
+            latest: Arc::default(),
+        }
+    }
+
+    /// Reconciles every interval until the process exits.
+    pub async fn run(self) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.reconcile(self.repair).await {
+                error!("Error reconciling treasuries: {e:?}");
+            }
+        }
+    }
+
+    /// The report of the latest reconciliation run by this instance, if any.
+    #[must_use]
+    pub fn latest(&self) -> Option<ReconciliationReport> {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Starts a reconciliation in the background unless one is already running.
+    /// Returns whether a reconciliation was started.
+    #[must_use]
+    pub fn trigger(&self, repair: bool) -> bool {
+        let Ok(running) = self.running.clone().try_lock_owned() else {
+            return false;
+        };
+
+        let reconciler = self.clone();
+
+        tokio::spawn(async move {
+            let _running = running;
+
+            if let Err(e) = reconciler.reconcile_exclusive(repair).await {
+                error!("Error reconciling treasuries: {e:?}");
+            }
+        });
+
+        true
+    }
+
+    /// Compares every treasury and wallet with the vaults of the custodian.
+    /// With `repair` set, the project treasuries and wallets found only in the custodian are recorded.
+    /// Customer treasuries are never recorded since their vaults do not name their project.
+    /// A reconciliation started while another one is running waits for it to finish.
+    ///
+    /// # Errors
+    /// Returns an error if the vaults or the treasuries cannot be loaded or a repair fails.
+    pub async fn reconcile(&self, repair: bool) -> Result<ReconciliationReport> {
+        let _running = self.running.lock().await;
+
+        self.reconcile_exclusive(repair).await
+    }
+
+    /// Reconciles while holding the `running` lock. Treasuries are loaded a page at a time along
+    /// with their wallets, and the treasuries of a page are checked concurrently.
+    async fn reconcile_exclusive(&self, repair: bool) -> Result<ReconciliationReport> {
+        let started_at = Utc::now();
+        let conn = self.db.get();
+
+        let vaults = self.vaults().await?;
+
+        let vaults_by_id = vaults
+            .iter()
+            .map(|vault| (vault.id.as_str(), vault))
+            .collect::<HashMap<_, _>>();
+
+        let mut recorded = HashSet::new();
+        let mut treasury_count = 0;
+        let mut wallet_count = 0;
+        let mut issues = Vec::new();
+
+        let mut pages = treasuries::Entity::find()
+            .order_by_asc(treasuries::Column::Id)
+            .paginate(conn, TREASURY_PAGE_SIZE);
+
+        while let Some(treasuries) = pages.fetch_and_next().await? {
+            let treasury_ids = treasuries
+                .iter()
+                .map(|treasury| treasury.id)
+                .collect::<Vec<_>>();
+
+            let wallets = wallets::Entity::find()
+                .filter(wallets::Column::TreasuryId.is_in(treasury_ids.clone()))
+                .all(conn)
+                .await?;
+
+            // Wallets being created are recorded once the saga creating them moves on.
+            let creating = wallet_creations::Entity::find()
+                .filter(wallet_creations::Column::Status.is_in(IN_PROGRESS))
+                .filter(wallet_creations::Column::TreasuryId.is_in(treasury_ids))
+                .all(conn)
+                .await?
+                .into_iter()
+                .map(|creation| (creation.treasury_id, creation.asset_id))
+                .collect::<HashSet<_>>();
+
+            treasury_count += treasuries.len();
+            wallet_count += wallets.len();
+
+            let mut treasury_wallets: HashMap<Uuid, Vec<wallets::Model>> = HashMap::new();
+
+            for wallet in wallets {
+                treasury_wallets
+                    .entry(wallet.treasury_id)
+                    .or_default()
+                    .push(wallet);
+            }
+
+            recorded.extend(treasuries.iter().map(|treasury| treasury.vault_id.clone()));
+
+            let checked = stream::iter(&treasuries)
+                .map(|treasury| {
+                    let wallets = treasury_wallets.remove(&treasury.id).unwrap_or_default();
+                    let vault = vaults_by_id.get(treasury.vault_id.as_str()).copied();
+
+                    self.check_treasury(treasury, vault, wallets, &creating, repair)
+                })
+                .buffered(RECONCILE_CONCURRENCY)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            issues.extend(checked.into_iter().flatten());
+        }
+
+        for vault in &vaults {
+            if recorded.contains(&vault.id) {
+                continue;
+            }
+
+            issues.extend(self.check_orphaned_vault(vault, repair).await?);
+        }
+
+        let mut counts = ReconciliationIssueKind::ALL
+            .iter()
+            .map(|kind| (kind.as_str(), 0))
+            .collect::<HashMap<_, _>>();
+
+        for issue in &issues {
+            *counts.entry(issue.kind.as_str()).or_default() += 1;
+        }
+
+        self.metrics.record_reconciliation_issues(counts);
+
+        info!(
+            "reconciled {treasury_count} treasuries against {} vaults: {} issues found",
+            vaults.len(),
+            issues.len()
+        );
+
+        let report = ReconciliationReport {
+            started_at,
+            finished_at: Utc::now(),
+            repair,
+            vaults: vaults.len(),
+            treasuries: treasury_count,
+            wallets: wallet_count,
+            issues,
+        };
+
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner) = Some(report.clone());
+
+        Ok(report)
+    }
+
+    /// Reconciles a single vault, such as one reported as added by the custodian. The wallets of a
+    /// vault recorded as a treasury are checked and, when repairing is configured, the missing ones
+    /// are recorded. A vault named after a project or customer without a treasury is reported as
+    /// orphaned but never recorded, since its treasury may still be in the middle of being created.
+    /// The issues found are logged and left out of the latest report and the metrics.
+    ///
+    /// # Errors
+    /// Returns an error if the vault, its treasury or its wallets cannot be loaded or a repair fails.
+    pub async fn reconcile_vault(&self, vault_id: String) -> Result<Vec<ReconciliationIssue>> {
+        let conn = self.db.get();
+        let vault = self.custody.vault(vault_id).await?;
+
+        let treasury = treasuries::Entity::find()
+            .filter(treasuries::Column::VaultId.eq(vault.id.clone()))
+            .one(conn)
+            .await?;
+
+        let issues = if let Some(treasury) = treasury {
+            let wallets = wallets::Entity::find()
+                .filter(wallets::Column::TreasuryId.eq(treasury.id))
+                .all(conn)
+                .await?;
+
+            let creating = wallet_creations::Entity::find()
+                .filter(wallet_creations::Column::Status.is_in(IN_PROGRESS))
+                .filter(wallet_creations::Column::TreasuryId.eq(treasury.id))
+                .all(conn)
+                .await?
+                .into_iter()
+                .map(|creation| (creation.treasury_id, creation.asset_id))
+                .collect::<HashSet<_>>();
+
+            self.check_treasury(&treasury, Some(&vault), wallets, &creating, self.repair)
+                .await?
+        } else {
+            self.check_orphaned_vault(&vault, false)
+                .await?
+                .into_iter()
+                .collect()
+        };
+
+        for issue in &issues {
+            info!(
+                "vault {} {:?}: {:?}{}",
+                vault.id,
+                vault.name,
+                issue.kind,
+                if issue.repaired { " (repaired)" } else { "" }
+            );
+        }
+
+        Ok(issues)
+    }
+
+    /// Lists every vault of the custodian, page by page.
+    async fn vaults(&self) -> Result<Vec<Vault>> {
+        let mut vaults = Vec::new();
+        let mut after = None;
+
+        loop {
+            let page = self
+                .custody
+                .vaults(VaultQuery {
+                    limit: Some(PAGE_SIZE),
+                    after,
+                    ..Default::default()
+                })
+                .await?;
+
+            vaults.extend(page.vaults);
+            after = page.after;
+
+            if after.is_none() {
+                return Ok(vaults);
+            }
+        }
+    }
+
+    /// Fetches the deposit addresses of an asset of the vault. A vault without the asset has none.
+    async fn addresses(&self, vault: &Vault, asset_id: &str) -> Result<Vec<String>> {
+        if !vault.assets.iter().any(|asset| asset.id == asset_id) {
+            return Ok(Vec::new());
+        }
+
+        self.custody
+            .deposit_addresses(vault.id.clone(), asset_id.to_string())
+            .await
+    }
+
+    /// Checks a treasury against its vault, returning the issues found.
+    async fn check_treasury(
+        &self,
+        treasury: &treasuries::Model,
+        vault: Option<&Vault>,
+        wallets: Vec<wallets::Model>,
+        creating: &HashSet<(Uuid, AssetType)>,
+        repair: bool,
+    ) -> Result<Vec<ReconciliationIssue>> {
+        let treasury_id = treasury.id;
+
+        let Some(vault) = vault else {
+            return Ok(vec![ReconciliationIssue {
+                treasury_id: Some(treasury_id),
+                ..ReconciliationIssue::new(
+                    ReconciliationIssueKind::MissingVault,
+                    treasury.vault_id.clone(),
+                    None,
+                )
+            }]);
+        };
+
+        let mut issues = Vec::new();
+
+        for wallet in &wallets {
+            let asset_id = self.custody.asset_id(wallet.asset_id.as_str());
+            let addresses = self.addresses(vault, &asset_id).await?;
+
+            if wallet
+                .address
+                .as_ref()
+                .map_or(false, |address| addresses.contains(address))
+            {
+                continue;
+            }
+
+            issues.push(ReconciliationIssue {
+                treasury_id: Some(treasury_id),
+                wallet_id: Some(wallet.id),
+                asset_type: Some(wallet.asset_id),
+                recorded_address: wallet.address.clone(),
+                custodian_addresses: addresses,
+                ..ReconciliationIssue::of_vault(ReconciliationIssueKind::AddressMismatch, vault)
+            });
+        }
+
+        for asset in &vault.assets {
+            let Ok(asset_type) = AssetType::from_str(&asset.id) else {
+                continue;
+            };
+
+            if creating.contains(&(treasury_id, asset_type))
+                || wallets.iter().any(|wallet| wallet.asset_id == asset_type)
+            {
+                continue;
+            }
+
+            let addresses = self.addresses(vault, &asset.id).await?;
+            let repaired = match addresses.first() {
+                Some(address) if repair => {
+                    wallet_model(treasury_id, asset_type, address.clone(), vault)
+                        .insert(self.db.get())
+                        .await?;
+
+                    info!(
+                        "recorded {asset_type} wallet {address} of treasury {treasury_id} from vault {}",
+                        vault.id
+                    );
+
+                    true
+                },
+                Some(_) | None => false,
+            };
+
+            issues.push(ReconciliationIssue {
+                treasury_id: Some(treasury_id),
+                asset_type: Some(asset_type),
+                custodian_addresses: addresses,
+                repaired,
+                ..ReconciliationIssue::of_vault(ReconciliationIssueKind::UntrackedWallet, vault)
+            });
+        }
+
+        Ok(issues)
+    }
+
+    /// Reports a vault without a treasury if it is named after a project or customer. With `repair`
+    /// set, the vault of a project is recorded as its treasury.
+    async fn check_orphaned_vault(
+        &self,
+        vault: &Vault,
+        repair: bool,
+    ) -> Result<Option<ReconciliationIssue>> {
+        let Some(owner) = VaultOwner::parse(&vault.name) else {
+            return Ok(None);
+        };
+
+        let repaired = match owner {
+            VaultOwner::Project(project_id) if repair => {
+                self.record_project_treasury(project_id, vault).await?
+            },
+            VaultOwner::Project(_) | VaultOwner::Customer(_) => false,
+        };
+
+        Ok(Some(ReconciliationIssue {
+            repaired,
+            ..ReconciliationIssue::of_vault(ReconciliationIssueKind::OrphanedVault, vault)
+        }))
+    }
+
+    /// Records the treasury of a project along with the wallets of its vault, unless the project
+    /// already has a treasury or its organization is unknown. Only the first vault of a project is
+    /// recorded when several were created for it. Returns whether the treasury was recorded.
+    async fn record_project_treasury(&self, project_id: Uuid, vault: &Vault) -> Result<bool> {
+        let project_treasury = project_treasuries::Entity::find()
+            .filter(project_treasuries::Column::ProjectId.eq(project_id))
+            .one(self.db.get())
+            .await?;
+
+        if project_treasury.is_some() {
+            return Ok(false);
+        }
+
+        let Some(organization_id) = self.project_organization(project_id).await? else {
+            info!(
+                "not recording vault {} of project {project_id}: the organization of the project is unknown",
+                vault.id
+            );
+
+            return Ok(false);
+        };
+
+        let mut wallets = Vec::new();
+
+        for asset in &vault.assets {
+            let Ok(asset_type) = AssetType::from_str(&asset.id) else {
+                continue;
+            };
+
+            if let Some(address) = self.addresses(vault, &asset.id).await?.into_iter().next() {
+                wallets.push((asset_type, address));
+            }
+        }
+
+        let txn = self.db.get().begin().await?;
+
+        let treasury = treasuries::ActiveModel {
+            vault_id: Set(vault.id.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        project_treasuries::ActiveModel {
+            project_id: Set(project_id),
+            treasury_id: Set(treasury.id),
+            organization_id: Set(Some(organization_id)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        for (asset_type, address) in wallets {
+            wallet_model(treasury.id, asset_type, address, vault)
+                .insert(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        info!(
+            "recorded treasury {} of project {project_id} from vault {}",
+            treasury.id, vault.id
+        );
+
+        Ok(true)
+    }
+
+    /// Looks up the organization of a project from the wallets created or auto provisioned for it,
+    /// the only records naming a project's organization apart from its treasury.
+    async fn project_organization(&self, project_id: Uuid) -> Result<Option<Uuid>> {
+        let conn = self.db.get();
+
+        let wallet_creation = wallet_creations::Entity::find()
+            .filter(wallet_creations::Column::ProjectId.eq(project_id))
+            .order_by_desc(wallet_creations::Column::CreatedAt)
+            .one(conn)
+            .await?;
+
+        if let Some(wallet_creation) = wallet_creation {
+            return Ok(Some(wallet_creation.organization_id));
+        }
+
+        let auto_provisioned_asset = auto_provisioned_assets::Entity::find()
+            .filter(auto_provisioned_assets::Column::ProjectId.eq(project_id))
+            .order_by_desc(auto_provisioned_assets::Column::CreatedAt)
+            .one(conn)
+            .await?;
+
+        Ok(auto_provisioned_asset.map(|a| a.organization_id))
+    }
+}
+
+/// A wallet recorded from a vault. Project vaults carry the ID of the user who created them as
+/// their customer reference, which is recorded as the creator. Other wallets are recorded as
+/// created by the nil UUID.
+fn wallet_model(
+    treasury_id: Uuid,
+    asset_type: AssetType,
+    address: String,
+    vault: &Vault,
+) -> wallets::ActiveModel {
+    let created_by = vault
+        .customer_ref_id
+        .as_deref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .unwrap_or_default();
+
+    wallets::ActiveModel {
+        treasury_id: Set(treasury_id),
+        asset_id: Set(asset_type),
+        address: Set(Some(address)),
+        created_by: Set(created_by),
+        deduction_id: Set(None),
+        ..Default::default()
+    }
+}
@@ -7,6 +7,20 @@ pub const SOL_TEST: &str = "SOL_TEST";
 pub const MATIC_TEST: &str = "MATIC_POLYGON_MUMBAI";
 pub const MATIC_POLYGON: &str = "MATIC_POLYGON";
 pub const ETH_TEST: &str = "ETH_TEST";
+pub const BASE: &str = "BASE";
+pub const BASE_ETH: &str = "BASECHAIN_ETH";
+pub const BASE_TEST: &str = "BASECHAIN_ETH_TEST5";
+pub const ARBITRUM: &str = "ARBITRUM";
+pub const ARBITRUM_ETH: &str = "ETH-AETH";
+pub const ARBITRUM_TEST: &str = "ETH-AETH_SEPOLIA";
+pub const OPTIMISM: &str = "OPTIMISM";
+pub const OPTIMISM_ETH: &str = "ETH-OPT";
+pub const OPTIMISM_TEST: &str = "ETH-OPT_SEPOLIA";
+pub const USDC_SOL: &str = "USDC_SOL";
+pub const SOL_USDC: &str = "SOL_USDC_PTHX";
+pub const SOL_USDC_TEST: &str = "SOL_USDC_TEST";
+pub const USDC_POLYGON: &str = "USDC_POLYGON";
+pub const USDC_POLYGON_TEST: &str = "USDC_POLYGON_TEST";
 
 #[derive(Clone, Debug)]
 pub struct Assets {
@@ -23,6 +37,12 @@ impl Assets {
         Self { ids, test_mode }
     }
 
+    /// The network agnostic symbols of the supported assets, as configured.
+    #[must_use]
+    pub fn symbols(&self) -> &[String] {
+        &self.ids
+    }
+
     #[must_use]
     pub fn ids(&self) -> Vec<String> {
         // TODO: adjust in the future to compute the ids based on the test_mode once during initialization of the struct
@@ -0,0 +1,43 @@
+//! Serves the Fireblocks emulator for running the treasury service without a Fireblocks workspace.
+
+use fireblocks::mock::Emulator;
+use hub_core::{
+    anyhow::{Context, Result},
+    clap::{self, Parser},
+    tokio,
+};
+use poem::{listener::TcpListener, Server};
+
+#[derive(Debug, Parser)]
+#[command(version, author, about)]
+struct Args {
+    #[arg(short, long, env = "FIREBLOCKS_EMULATOR_PORT", default_value_t = 3010)]
+    port: u16,
+    #[arg(long, env)]
+    fireblocks_api_key: String,
+    /// Path to the RSA public key of the API user secret configured in `FIREBLOCKS_SECRET_PATH`.
+    #[arg(long, env)]
+    fireblocks_public_key_path: String,
+}
+
+fn main() -> Result<()> {
+    let Args {
+        port,
+        fireblocks_api_key,
+        fireblocks_public_key_path,
+    } = Args::parse();
+
+    let public_key =
+        std::fs::read(fireblocks_public_key_path).context("failed to read public key")?;
+    let emulator = Emulator::new(fireblocks_api_key, &public_key)?;
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async move {
+            Server::new(TcpListener::bind(format!("0.0.0.0:{port}")))
+                .run(emulator.routes())
+                .await
+                .context("failed to run fireblocks emulator")
+        })
+}
@@ -3,32 +3,37 @@
 use std::time::Duration;
 
 use hub_core::{
-    anyhow::{Context as _, Result},
-    backon::{ExponentialBuilder, Retryable},
-    reqwest::{Client as HttpClient, RequestBuilder, Url},
-    serde_json, thiserror,
+    anyhow::{self, Context as _, Result},
+    reqwest::{self, Client as HttpClient, Method, RequestBuilder, Url},
+    serde_json,
     tokio::time,
     tracing::info,
 };
 use jsonwebtoken::EncodingKey;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     objects::{
         transaction::{
-            CreateTransaction, CreateTransactionResponse, DestinationTransferPeerPath,
-            ExtraParameters, RawMessageData, TransactionDetails, TransactionOperation,
-            TransactionStatus, TransferPeerPath, UnsignedMessage,
+            CancelTransactionResponse, CreateTransaction, CreateTransactionResponse,
+            DestinationTransferPeerPath, ExtraParameters, FeeLevel, RawMessageData, StatusClass,
+            TransactionDetails, TransactionOperation, TransferPeerPath, UnsignedMessage,
         },
         vault::{
             CreateVault, CreateVaultAssetResponse, CreateVaultWallet, QueryVaultAccounts,
-            VaultAccount, VaultAccountsPagedResponse, VaultAsset,
+            VaultAccount, VaultAccountsPagedResponse, VaultAsset, VaultAssetAddress,
         },
     },
+    rate_limit::RateLimiter,
     signer::RequestSigner,
-    FbArgs,
+    Error, FbArgs, TransactionOutcome, WaitPolicy,
 };
 
+/// Delay before the first retry of a failed request. It doubles with every attempt unless Fireblocks
+/// asks for a specific delay with a `Retry-After` header.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
 /// Represents a Fireblocks API client.
 /// It contains an HTTP client, a request signer, the base URL for the Fireblocks endpoint, and an API key.
 #[derive(Clone)]
@@ -38,12 +43,9 @@ pub struct Client {
     base_url: Url,
     api_key: String,
     contract_wallet_id: String,
-}
-
-#[derive(Debug, Clone, Copy, thiserror::Error)]
-pub enum Error {
-    #[error("failed to sign transaction")]
-    Transaction(TransactionStatus),
+    wait_policy: WaitPolicy,
+    rate_limiter: RateLimiter,
+    max_retries: u32,
 }
 
 impl Client {
@@ -61,11 +63,15 @@ impl Client {
     /// * Failed to read the secret key file.
     /// * Failed to create an encoding key from the secret key.
     pub fn new(args: FbArgs) -> Result<Self> {
+        let wait_policy = WaitPolicy::new(&args);
         let FbArgs {
             fireblocks_endpoint,
             fireblocks_api_key,
             fireblocks_secret_path,
             fireblocks_whitelisted_contract_wallet_id,
+            fireblocks_rate_limit_per_sec,
+            fireblocks_rate_limit_burst,
+            fireblocks_max_retries,
             ..
         } = args;
 
@@ -82,6 +88,12 @@ impl Client {
             base_url,
             api_key: fireblocks_api_key,
             contract_wallet_id: fireblocks_whitelisted_contract_wallet_id,
+            wait_policy,
+            rate_limiter: RateLimiter::new(
+                fireblocks_rate_limit_per_sec,
+                fireblocks_rate_limit_burst,
+            ),
+            max_retries: fireblocks_max_retries,
         })
     }
 
@@ -132,6 +144,83 @@ impl Client {
         Ok(req.header("X-API-KEY", &self.api_key).bearer_auth(token))
     }
 
+    /// Sends a request to the Fireblocks API and deserializes the response body.
+    /// Throttled requests are always sent again. Requests failing with a connection or server error
+    /// are only sent again when `idempotent` is set, since Fireblocks may have processed them.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The URL parsing fails.
+    /// * Failed to serialize the request body.
+    /// * The HTTP request fails and may not or can no longer be retried.
+    /// * Fireblocks responds with an unsuccessful status, returned as an [`Error`].
+    /// * Failed to deserialize the response body.
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: impl Serialize,
+        idempotent: bool,
+    ) -> Result<T> {
+        let url = self.base_url.join(path)?;
+        let body = serde_json::to_value(body)?;
+        let mut attempt = 0;
+
+        loop {
+            let err = match self.attempt(method.clone(), url.clone(), path, &body).await {
+                Ok(res) => break Ok(res),
+                Err(e) => e,
+            };
+
+            let delay = retry_delay(&err, idempotent, attempt);
+
+            match delay {
+                Some(delay) if attempt < self.max_retries => {
+                    attempt += 1;
+                    info!("Retrying {method} {path} in {delay:?} (attempt {attempt}): {err}");
+
+                    time::sleep(delay).await;
+                },
+                _ => break Err(err),
+            }
+        }
+    }
+
+    async fn attempt<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<T> {
+        self.rate_limiter.acquire().await;
+
+        let mut req = self.http.request(method.clone(), url);
+
+        if method != Method::GET {
+            req = req.json(body);
+        }
+
+        req = self.authenticate(req, path.to_owned(), body)?;
+
+        let response = req.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await?;
+
+        if !status.is_success() {
+            return Err(Error::from_response(status, &headers, text).into());
+        }
+
+        if method != Method::GET {
+            info!("Response: {}", text);
+        }
+
+        Ok(serde_json::from_str(&text)?)
+    }
+
     #[must_use]
     pub fn read(&self) -> ReadRequestBuilder {
         ReadRequestBuilder(self.clone())
@@ -142,7 +231,13 @@ impl Client {
         CreateRequestBuilder(self.clone())
     }
 
-    /// Waits for a transaction to reach the "COMPLETED" status by periodically checking the transaction details.
+    #[must_use]
+    pub fn wait_policy(&self) -> &WaitPolicy {
+        &self.wait_policy
+    }
+
+    /// Waits for a transaction to leave the pending state by periodically checking the transaction details.
+    /// The [`WaitPolicy`] of the client decides which statuses are pending and how long to wait.
     ///
     /// # Arguments
     ///
@@ -154,40 +249,33 @@ impl Client {
     ///
     /// * The GET request fails.
     /// * Failed to deserialize the transaction details.
-    /// * The transaction status is not one of the expected states.
     ///
     /// # Returns
     ///
-    /// Transaction details when the transaction is completed.
-    pub async fn wait_on_transaction_completion(&self, id: String) -> Result<TransactionDetails> {
-        let mut interval = time::interval(time::Duration::from_millis(2500));
+    /// The outcome of the transaction once it completes, fails or the deadline passes.
+    pub async fn wait_on_transaction_completion(&self, id: String) -> Result<TransactionOutcome> {
+        let policy = &self.wait_policy;
+        let deadline = time::Instant::now() + policy.deadline;
+        let mut interval = time::interval(policy.interval);
 
         loop {
-            let tx_details = (|| async { self.read().transaction(id.clone()).await })
-                .retry(
-                    &ExponentialBuilder::default()
-                        .with_jitter()
-                        .with_min_delay(Duration::from_millis(30))
-                        .with_max_times(10),
-                )
-                .await?;
-
-            let status = tx_details.clone().status;
-
-            match status {
-                TransactionStatus::SUBMITTED
-                | TransactionStatus::QUEUED
-                | TransactionStatus::BROADCASTING
-                | TransactionStatus::CONFIRMING
-                | TransactionStatus::PENDING_SIGNATURE => {
-                    interval.tick().await;
-
-                    continue;
+            let tx_details = self.read().transaction(id.clone()).await?;
+
+            match policy.classify(tx_details.status) {
+                StatusClass::Success => break Ok(TransactionOutcome::Completed(tx_details)),
+                StatusClass::Failure => break Ok(TransactionOutcome::Failed(tx_details)),
+                StatusClass::Pending if time::Instant::now() >= deadline => {
+                    let cancelled =
+                        policy.cancel_on_timeout && self.create().cancel(id.clone()).await?.success;
+
+                    break Ok(TransactionOutcome::TimedOut {
+                        details: tx_details,
+                        cancelled,
+                    });
                 },
-                TransactionStatus::COMPLETED => {
-                    break Ok(tx_details);
+                StatusClass::Pending => {
+                    interval.tick().await;
                 },
-                _ => return Err(Error::Transaction(status).into()),
             }
         }
     }
@@ -198,6 +286,7 @@ pub struct ReadRequestBuilder(Client);
 
 impl ReadRequestBuilder {
     /// Sends a GET request to the specified path and deserializes the response body.
+    /// Reads are idempotent, so they are retried on throttling, connection and server errors.
     ///
     /// # Arguments
     ///
@@ -209,27 +298,14 @@ impl ReadRequestBuilder {
     ///
     /// * The URL parsing fails.
     /// * The HTTP request fails.
+    /// * Fireblocks responds with an unsuccessful status, returned as an [`Error`].
     /// * Failed to deserialize the response body.
     ///
     /// # Returns
     ///
     /// Deserialized response body of type `T`.
-    pub async fn send<T: serde::de::DeserializeOwned>(
-        &self,
-        path: &str,
-        body: impl Serialize,
-    ) -> Result<T> {
-        let client = &self.0;
-
-        let url = client.base_url.join(path)?;
-
-        let mut req = client.http.get(url);
-
-        req = client.authenticate(req, path.to_owned(), body)?;
-
-        let response = req.send().await?.text().await?;
-
-        Ok(serde_json::from_str(&response)?)
+    pub async fn send<T: DeserializeOwned>(&self, path: &str, body: impl Serialize) -> Result<T> {
+        self.0.execute(Method::GET, path, body, true).await
     }
 
     /// Retrieves a paged response of vault accounts based on the provided filters.
@@ -242,7 +318,7 @@ impl ReadRequestBuilder {
     ///
     /// This function can fail if:
     ///
-    /// * The POST request fails.
+    /// * The GET request fails.
     /// * Failed to serialize the query filters.
     /// * Failed to deserialize the paged response.
     ///
@@ -251,7 +327,50 @@ impl ReadRequestBuilder {
     /// Paged response of vault accounts.
     pub async fn vaults(&self, filters: QueryVaultAccounts) -> Result<VaultAccountsPagedResponse> {
         let endpoint = "/v1/vault/accounts_paged";
-        self.send(endpoint, filters).await
+        let serde_json::Value::Object(filters) = serde_json::to_value(filters)? else {
+            return self.send(endpoint, ()).await;
+        };
+
+        // GET requests carry no body, so the filters are sent as query parameters, which are
+        // part of the URI the request is signed for.
+        let mut url = self.0.base_url.join(endpoint)?;
+        url.query_pairs_mut()
+            .extend_pairs(filters.into_iter().map(|(key, value)| match value {
+                serde_json::Value::String(value) => (key, value),
+                value => (key, value.to_string()),
+            }));
+
+        match url.query().filter(|query| !query.is_empty()) {
+            Some(query) => self.send(&format!("{endpoint}?{query}"), ()).await,
+            None => self.send(endpoint, ()).await,
+        }
+    }
+
+    /// Retrieves the deposit addresses of an asset wallet within a vault account.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - Vault account ID.
+    /// * `asset_id` - ID of the asset.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The GET request fails.
+    /// * Failed to deserialize the addresses.
+    ///
+    /// # Returns
+    ///
+    /// Deposit addresses of the asset wallet.
+    pub async fn addresses(
+        &self,
+        vault_id: String,
+        asset_id: String,
+    ) -> Result<Vec<VaultAssetAddress>> {
+        let endpoint = format!("/v1/vault/accounts/{vault_id}/{asset_id}/addresses");
+
+        self.send(&endpoint, ()).await
     }
 
     /// Retrieves the details of a specific transaction based on the transaction ID.
@@ -277,6 +396,31 @@ impl ReadRequestBuilder {
         self.send(&endpoint, ()).await
     }
 
+    /// Retrieves the details of a transaction based on the external transaction ID it was submitted with.
+    ///
+    /// # Arguments
+    ///
+    /// * `external_tx_id` - External transaction ID.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The GET request fails.
+    /// * Failed to deserialize the transaction details.
+    ///
+    /// # Returns
+    ///
+    /// Transaction details.
+    pub async fn transaction_by_external_id(
+        &self,
+        external_tx_id: String,
+    ) -> Result<TransactionDetails> {
+        let endpoint = format!("/v1/transactions/external_tx_id/{external_tx_id}");
+
+        self.send(&endpoint, ()).await
+    }
+
     /// Retrieves the details of a specific vault account based on the vault ID.
     ///
     /// # Arguments
@@ -338,6 +482,7 @@ pub struct CreateRequestBuilder(Client);
 
 impl CreateRequestBuilder {
     /// Sends a POST request to the specified path with the provided body and deserializes the response body.
+    /// Only throttled requests are retried, since any other failure may leave the request processed.
     ///
     /// # Arguments
     ///
@@ -351,27 +496,28 @@ impl CreateRequestBuilder {
     /// * The URL parsing fails.
     /// * The HTTP request fails.
     /// * Failed to serialize the request body.
+    /// * Fireblocks responds with an unsuccessful status, returned as an [`Error`].
     /// * Failed to deserialize the response body.
     ///
     /// # Returns
     ///
     /// Deserialized response body of type `T`.
-    pub async fn send<T: serde::de::DeserializeOwned>(
+    pub async fn send<T: DeserializeOwned>(&self, path: &str, body: impl Serialize) -> Result<T> {
+        self.0.execute(Method::POST, path, body, false).await
+    }
+
+    /// Sends a POST request that Fireblocks processes at most once, such as a transaction carrying an
+    /// external transaction ID. The request is also retried on connection and server errors.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail for the same reasons as [`send`](Self::send).
+    pub async fn send_idempotent<T: DeserializeOwned>(
         &self,
         path: &str,
         body: impl Serialize,
     ) -> Result<T> {
-        let client = &self.0;
-        let url = client.base_url.join(path)?;
-        let mut req = client.http.post(url).json(&body);
-
-        req = client.authenticate(req, path.to_owned(), body)?;
-
-        let response = req.send().await?.text().await?;
-
-        info!("Response: {}", response);
-
-        Ok(serde_json::from_str(&response)?)
+        self.0.execute(Method::POST, path, body, true).await
     }
 
     /// Creates a new vault account with the provided details.
@@ -444,7 +590,12 @@ impl CreateRequestBuilder {
     /// Created transaction details.
     pub async fn transaction(&self, tx: CreateTransaction) -> Result<CreateTransactionResponse> {
         let endpoint = "/v1/transactions".to_string();
-        self.send(&endpoint, tx).await
+
+        if tx.external_tx_id.is_some() {
+            self.send_idempotent(&endpoint, tx).await
+        } else {
+            self.send(&endpoint, tx).await
+        }
     }
 
     /// Creates and signs a raw message transaction with the provided asset ID, vault ID, message content, and note.
@@ -455,6 +606,7 @@ impl CreateRequestBuilder {
     /// * `vault_id` - ID of the vault.
     /// * `message` - Message content as a byte array.
     /// * `note` - Note for the transaction.
+    /// * `external_tx_id` - Unique ID used by Fireblocks to reject duplicate submissions.
     ///
     /// # Errors
     ///
@@ -473,6 +625,7 @@ impl CreateRequestBuilder {
         vault_id: String,
         messages: Vec<Vec<u8>>,
         note: String,
+        external_tx_id: Option<String>,
     ) -> Result<CreateTransactionResponse> {
         let tx = CreateTransaction {
             asset_id,
@@ -485,6 +638,7 @@ impl CreateRequestBuilder {
             destinations: None,
             treat_as_gross_amount: None,
             customer_ref_id: None,
+            external_tx_id,
             amount: "0".to_string(),
             feelevel: None,
             extra_parameters: Some(ExtraParameters::RawMessageData(RawMessageData {
@@ -498,18 +652,117 @@ impl CreateRequestBuilder {
             note: Some(note),
         };
 
-        let endpoint = "/v1/transactions".to_string();
-        self.send(&endpoint, tx).await
+        self.transaction(tx).await
+    }
+
+    /// Creates a transfer of `amount` of the asset from a vault to a destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset_id` - ID of the asset.
+    /// * `vault_id` - ID of the source vault.
+    /// * `destination` - Vault account or one time address receiving the asset.
+    /// * `amount` - Amount of the asset as a decimal string.
+    /// * `note` - Note for the transaction.
+    /// * `external_tx_id` - Unique ID used by Fireblocks to reject duplicate submissions.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The POST request fails.
+    /// * Failed to serialize the request body.
+    /// * Failed to deserialize the created transaction details.
+    ///
+    /// # Returns
+    ///
+    /// Created transaction details.
+    pub async fn transfer(
+        &self,
+        asset_id: String,
+        vault_id: String,
+        destination: DestinationTransferPeerPath,
+        amount: String,
+        note: String,
+        external_tx_id: Option<String>,
+    ) -> Result<CreateTransactionResponse> {
+        let tx = CreateTransaction {
+            asset_id,
+            operation: TransactionOperation::TRANSFER,
+            source: TransferPeerPath {
+                peer_type: "VAULT_ACCOUNT".to_string(),
+                id: vault_id,
+            },
+            destination: Some(destination),
+            destinations: None,
+            treat_as_gross_amount: None,
+            customer_ref_id: None,
+            external_tx_id,
+            amount,
+            feelevel: None,
+            extra_parameters: None,
+            note: Some(note),
+        };
+
+        self.transaction(tx).await
+    }
+
+    /// Cancels a transaction that has not been signed yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `txid` - Transaction ID.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The POST request fails.
+    /// * Failed to deserialize the response.
+    ///
+    /// # Returns
+    ///
+    /// Whether the transaction was cancelled.
+    pub async fn cancel(&self, txid: String) -> Result<CancelTransactionResponse> {
+        let endpoint = format!("/v1/transactions/{txid}/cancel");
+        self.send_idempotent(&endpoint, ()).await
     }
 
+    /// Creates a contract call paid for by a vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Encoded contract call data.
+    /// * `asset_id` - ID of the asset paying for the call.
+    /// * `vault_id` - ID of the vault sending the call.
+    /// * `note` - Note for the transaction.
+    /// * `external_tx_id` - Unique ID used by Fireblocks to reject duplicate submissions.
+    /// * `contract_wallet_id` - Whitelisted contract wallet to call instead of the configured one.
+    /// * `fee_level` - Fee level of the transaction.
+    ///
+    /// # Errors
+    ///
+    /// This function can fail if:
+    ///
+    /// * The POST request fails.
+    /// * Failed to serialize the request body.
+    /// * Failed to deserialize the created transaction details.
+    ///
+    /// # Returns
+    ///
+    /// Created transaction details.
+    #[allow(clippy::too_many_arguments)]
     pub async fn contract_call(
         &self,
         data: Vec<u8>,
         asset_id: String,
         vault_id: String,
         note: String,
+        external_tx_id: Option<String>,
+        contract_wallet_id: Option<String>,
+        fee_level: FeeLevel,
     ) -> Result<CreateTransactionResponse> {
-        let contract = &self.0.contract_wallet_id;
+        let contract = contract_wallet_id.unwrap_or_else(|| self.0.contract_wallet_id.clone());
 
         let tx = CreateTransaction {
             asset_id,
@@ -520,20 +773,20 @@ impl CreateRequestBuilder {
             },
             destination: Some(DestinationTransferPeerPath {
                 peer_type: "EXTERNAL_WALLET".into(),
-                id: Some(contract.into()),
+                id: Some(contract),
                 one_time_address: None,
             }),
             destinations: None,
             treat_as_gross_amount: None,
             customer_ref_id: None,
+            external_tx_id,
             amount: "0".to_string(),
-            feelevel: Some("HIGH".to_string()),
+            feelevel: Some(fee_level),
             extra_parameters: Some(ExtraParameters::ContractCallData(hex::encode(data))),
             note: Some(note),
         };
 
-        let endpoint = "/v1/transactions".to_string();
-        self.send(&endpoint, tx).await
+        self.transaction(tx).await
     }
 
     /// Creates a new wallet within a vault account for the specified asset.
@@ -565,3 +818,71 @@ impl CreateRequestBuilder {
         self.send(&endpoint, body).await
     }
 }
+
+/// How long to wait before sending a failed request again, or `None` if it must not be retried.
+fn retry_delay(err: &anyhow::Error, idempotent: bool, attempt: u32) -> Option<Duration> {
+    let backoff = RETRY_BASE_DELAY
+        .saturating_mul(2_u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY);
+
+    if let Some(e) = err.downcast_ref::<Error>() {
+        return (e.is_rate_limited() || (idempotent && e.is_server_error()))
+            .then(|| e.retry_after().unwrap_or(backoff));
+    }
+
+    err.downcast_ref::<reqwest::Error>()
+        .filter(|e| idempotent && (e.is_connect() || e.is_timeout()))
+        .map(|_| backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::reqwest::StatusCode;
+
+    use super::*;
+    use crate::objects::transaction::TransactionStatus;
+
+    fn status_error(status: StatusCode, retry_after: Option<Duration>) -> anyhow::Error {
+        Error::Status {
+            status,
+            body: String::new(),
+            retry_after,
+        }
+        .into()
+    }
+
+    #[test]
+    fn retry_delay_backs_off_throttled_requests() {
+        let err = status_error(StatusCode::TOO_MANY_REQUESTS, None);
+
+        assert_eq!(retry_delay(&err, false, 0), Some(RETRY_BASE_DELAY));
+        assert_eq!(retry_delay(&err, false, 2), Some(RETRY_BASE_DELAY * 4));
+        assert_eq!(retry_delay(&err, false, 20), Some(RETRY_MAX_DELAY));
+    }
+
+    #[test]
+    fn retry_delay_prefers_retry_after() {
+        let err = status_error(StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(3)));
+
+        assert_eq!(retry_delay(&err, false, 0), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn retry_delay_retries_server_errors_of_idempotent_requests_only() {
+        let err = status_error(StatusCode::SERVICE_UNAVAILABLE, None);
+
+        assert_eq!(retry_delay(&err, true, 1), Some(RETRY_BASE_DELAY * 2));
+        assert_eq!(retry_delay(&err, false, 1), None);
+    }
+
+    #[test]
+    fn retry_delay_gives_up_on_other_errors() {
+        let rejected = status_error(StatusCode::BAD_REQUEST, None);
+        let failed = Error::Transaction(TransactionStatus::FAILED).into();
+        let other = anyhow::anyhow!("failed to deserialize the response");
+
+        for err in [rejected, failed, other] {
+            assert_eq!(retry_delay(&err, true, 0), None);
+        }
+    }
+}
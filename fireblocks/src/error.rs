@@ -0,0 +1,195 @@
+//! Errors returned by the Fireblocks API.
+
+use std::time::Duration;
+
+use hub_core::{
+    reqwest::{
+        header::{HeaderMap, RETRY_AFTER},
+        StatusCode,
+    },
+    serde_json, thiserror,
+};
+use serde::Deserialize;
+
+use crate::objects::transaction::TransactionStatus;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("failed to sign transaction")]
+    Transaction(TransactionStatus),
+    /// Fireblocks rejected the request and described why.
+    #[error("Fireblocks responded with {status}: {message} (code {code:?})")]
+    Api {
+        status: StatusCode,
+        message: String,
+        code: Option<i64>,
+        retry_after: Option<Duration>,
+    },
+    /// Fireblocks rejected the request with a body that is not a Fireblocks error.
+    #[error("Fireblocks responded with {status}")]
+    Status {
+        status: StatusCode,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+}
+
+/// The body of an error response from the Fireblocks API.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: String,
+    code: Option<i64>,
+}
+
+impl Error {
+    /// Builds an error from the status, headers and body of an unsuccessful response.
+    /// Only the delay-seconds form of `Retry-After` is understood.
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: String) -> Self {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+
+        match serde_json::from_str::<ErrorBody>(&body) {
+            Ok(ErrorBody { message, code }) => Self::Api {
+                status,
+                message,
+                code,
+                retry_after,
+            },
+            Err(_) => Self::Status {
+                status,
+                body,
+                retry_after,
+            },
+        }
+    }
+
+    /// The HTTP status of the response, if the error came from one.
+    #[must_use]
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Transaction(_) => None,
+            Self::Api { status, .. } | Self::Status { status, .. } => Some(*status),
+        }
+    }
+
+    /// How long Fireblocks asked to wait before sending the request again.
+    #[must_use]
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Transaction(_) => None,
+            Self::Api { retry_after, .. } | Self::Status { retry_after, .. } => *retry_after,
+        }
+    }
+
+    /// Whether Fireblocks throttled the request. Throttled requests were not processed and are always
+    /// safe to send again.
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Whether Fireblocks has no resource at the requested path.
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Whether Fireblocks rejected a transaction because an earlier transaction was created with
+    /// the same `externalTxId`.
+    #[must_use]
+    pub fn is_external_tx_id_taken(&self) -> bool {
+        match self {
+            Self::Api {
+                status, message, ..
+            } if *status == StatusCode::BAD_REQUEST => {
+                let message = message.to_lowercase().replace(' ', "");
+
+                message.contains("externaltxid") && message.contains("alreadyexist")
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether Fireblocks failed to handle the request. The request may have been processed, so it
+    /// is only safe to send again when it is idempotent.
+    #[must_use]
+    pub fn is_server_error(&self) -> bool {
+        self.status()
+            .map_or(false, |status| status.is_server_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hub_core::reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(retry_after: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(retry_after));
+
+        headers
+    }
+
+    #[test]
+    fn from_response_parses_fireblocks_errors() {
+        let err = Error::from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(" 7 "),
+            r#"{"message":"Too many requests","code":1429}"#.to_string(),
+        );
+
+        assert!(matches!(
+            &err,
+            Error::Api { message, code: Some(1429), .. } if message == "Too many requests"
+        ));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+        assert!(err.is_rate_limited());
+        assert!(!err.is_server_error());
+    }
+
+    #[test]
+    fn external_tx_id_reuse_is_recognized() {
+        let taken = |message: &str| {
+            Error::from_response(
+                StatusCode::BAD_REQUEST,
+                &HeaderMap::new(),
+                format!(r#"{{"message":"{message}","code":1438}}"#),
+            )
+            .is_external_tx_id_taken()
+        };
+
+        assert!(taken(
+            "The external tx id that was provided in the request, already exists"
+        ));
+        assert!(taken("transaction with externalTxId sign:1 already exists"));
+        assert!(!taken("Insufficient funds"));
+
+        let not_found = Error::from_response(
+            StatusCode::NOT_FOUND,
+            &HeaderMap::new(),
+            r#"{"message":"Transaction not found","code":1404}"#.to_string(),
+        );
+
+        assert!(not_found.is_not_found());
+        assert!(!not_found.is_external_tx_id_taken());
+    }
+
+    #[test]
+    fn from_response_keeps_other_bodies() {
+        let err = Error::from_response(
+            StatusCode::BAD_GATEWAY,
+            &headers("Wed, 21 Oct 2015 07:28:00 GMT"),
+            "<html>Bad Gateway</html>".to_string(),
+        );
+
+        assert!(matches!(&err, Error::Status { body, .. } if body == "<html>Bad Gateway</html>"));
+        assert_eq!(err.retry_after(), None);
+        assert!(err.is_server_error());
+        assert!(!err.is_rate_limited());
+    }
+}
@@ -12,11 +12,22 @@
 use hub_core::{anyhow::Result, clap};
 pub mod assets;
 mod client;
+mod error;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod objects;
+mod rate_limit;
 mod signer;
+mod wait;
+pub mod webhook;
 
 use assets::Assets;
-pub use client::{Client, Error};
+pub use client::Client;
+pub use error::Error;
+use objects::transaction::TransactionStatus;
+pub use rate_limit::RateLimiter;
+pub use wait::{TransactionOutcome, WaitPolicy};
+use webhook::WebhookVerifier;
 
 #[derive(clap::Args, Clone, Debug)]
 pub struct FbArgs {
@@ -34,6 +45,33 @@ pub struct FbArgs {
     pub fireblocks_treasury_vault_id: String,
     #[arg(long, env)]
     pub fireblocks_whitelisted_contract_wallet_id: String,
+    /// Seconds to wait on a pending transaction before giving up on it
+    #[arg(long, env, default_value_t = 600)]
+    pub fireblocks_wait_deadline_secs: u64,
+    /// Milliseconds between checks on a pending transaction
+    #[arg(long, env, default_value_t = 2500)]
+    pub fireblocks_wait_interval_ms: u64,
+    /// Cancel transactions still pending once the wait deadline passes
+    #[arg(long, env, default_value = "false")]
+    pub fireblocks_cancel_on_timeout: bool,
+    /// Transaction statuses to keep waiting on, overriding their default class
+    #[arg(long, env, value_delimiter = ',')]
+    pub fireblocks_pending_statuses: Vec<TransactionStatus>,
+    /// Transaction statuses to treat as failed, overriding their default class
+    #[arg(long, env, value_delimiter = ',')]
+    pub fireblocks_failure_statuses: Vec<TransactionStatus>,
+    /// PEM encoded public key verifying webhook notifications. Notifications are rejected when unset.
+    #[arg(long, env)]
+    pub fireblocks_webhook_public_key: Option<String>,
+    /// Requests per second sent to the Fireblocks API on average
+    #[arg(long, env, default_value_t = 10)]
+    pub fireblocks_rate_limit_per_sec: u32,
+    /// Requests that may be sent to the Fireblocks API at once before rate limiting kicks in
+    #[arg(long, env, default_value_t = 20)]
+    pub fireblocks_rate_limit_burst: u32,
+    /// Times a throttled or failed request to the Fireblocks API is sent again
+    #[arg(long, env, default_value_t = 5)]
+    pub fireblocks_max_retries: u32,
 }
 
 #[allow(missing_debug_implementations)]
@@ -42,6 +80,7 @@ pub struct Fireblocks {
     client: Client,
     assets: Assets,
     treasury_vault: String,
+    webhook_verifier: Option<WebhookVerifier>,
 }
 
 impl Fireblocks {
@@ -51,12 +90,18 @@ impl Fireblocks {
     pub fn new(args: FbArgs) -> Result<Self> {
         let client = Client::new(args.clone())?;
         let assets = Assets::new(args.clone());
+        let webhook_verifier = args
+            .fireblocks_webhook_public_key
+            .as_deref()
+            .map(WebhookVerifier::from_pem)
+            .transpose()?;
         let treasury_vault = args.fireblocks_treasury_vault_id;
 
         Ok(Self {
             client,
             assets,
             treasury_vault,
+            webhook_verifier,
         })
     }
 
@@ -74,4 +119,9 @@ impl Fireblocks {
     pub fn treasury_vault(&self) -> String {
         self.treasury_vault.clone()
     }
+
+    #[must_use]
+    pub fn webhook_verifier(&self) -> Option<WebhookVerifier> {
+        self.webhook_verifier.clone()
+    }
 }
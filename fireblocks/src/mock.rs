@@ -0,0 +1,613 @@
+//! An in-process emulator of the Fireblocks API.
+//!
+//! The emulator serves the subset of endpoints used by [`Client`](crate::Client), authenticates
+//! requests the same way Fireblocks does, and signs RAW transactions with real ed25519 and
+//! secp256k1 keys held in memory. It lets the treasury service run without a Fireblocks workspace.
+#![allow(missing_debug_implementations)]
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ed25519_dalek::Signer;
+use hub_core::{
+    anyhow::{Context as _, Result},
+    serde_json,
+    uuid::Uuid,
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use poem::{
+    get, handler,
+    http::StatusCode,
+    post,
+    web::{Data, Json, Path, Query},
+    EndpointExt, Request, Response, Route,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use crate::{
+    objects::{
+        transaction::{
+            CancelTransactionResponse, CreateTransaction, CreateTransactionResponse,
+            ExtraParameters, SignatureResponse, SignedMessageResponse, StatusClass,
+            TransactionDetails, TransactionOperation, TransactionStatus,
+        },
+        vault::{
+            CreateVault, CreateVaultAssetResponse, CreateVaultWallet, Paging, QueryVaultAccounts,
+            VaultAccount, VaultAccountsPagedResponse, VaultAsset, VaultAssetAddress,
+        },
+    },
+    signer::Payload,
+};
+
+/// A key held by the emulator for a single vault asset.
+enum Key {
+    Ed25519(ed25519_dalek::SigningKey),
+    Secp256k1(k256::ecdsa::SigningKey),
+}
+
+impl Key {
+    fn generate(asset_id: &str) -> Self {
+        if asset_id.starts_with("SOL") {
+            Self::Ed25519(ed25519_dalek::SigningKey::generate(&mut OsRng))
+        } else {
+            Self::Secp256k1(k256::ecdsa::SigningKey::random(&mut OsRng))
+        }
+    }
+
+    fn address(&self) -> String {
+        match self {
+            Self::Ed25519(key) => {
+                hub_core::bs58::encode(key.verifying_key().as_bytes()).into_string()
+            },
+            Self::Secp256k1(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                let hash = Keccak256::digest(&point.as_bytes()[1..]);
+
+                format!("0x{}", hex::encode(&hash[12..]))
+            },
+        }
+    }
+
+    fn public_key(&self) -> String {
+        match self {
+            Self::Ed25519(key) => hex::encode(key.verifying_key().as_bytes()),
+            Self::Secp256k1(key) => {
+                hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+            },
+        }
+    }
+
+    /// Signs a hex encoded message the way Fireblocks signs RAW messages.
+    /// ECDSA keys expect the message to already be a 32 byte hash.
+    fn sign(&self, content: String) -> poem::Result<SignedMessageResponse> {
+        let message = hex::decode(&content).map_err(|_| {
+            error(
+                StatusCode::BAD_REQUEST,
+                "message content is not hex encoded",
+            )
+        })?;
+
+        let (algorithm, signature) = match self {
+            Self::Ed25519(key) => {
+                let full_sig = hex::encode(key.sign(&message).to_bytes());
+
+                ("MPC_EDDSA_ED25519", SignatureResponse {
+                    full_sig,
+                    r: None,
+                    s: None,
+                    v: None,
+                })
+            },
+            Self::Secp256k1(key) => {
+                let (sig, recovery_id) = key
+                    .sign_prehash_recoverable(&message)
+                    .map_err(|_| error(StatusCode::BAD_REQUEST, "message is not a 32 byte hash"))?;
+                let bytes = sig.to_bytes();
+
+                ("MPC_ECDSA_SECP256K1", SignatureResponse {
+                    full_sig: hex::encode(bytes),
+                    r: Some(hex::encode(&bytes[..32])),
+                    s: Some(hex::encode(&bytes[32..])),
+                    v: Some(recovery_id.to_byte().into()),
+                })
+            },
+        };
+
+        Ok(SignedMessageResponse {
+            content,
+            algorithm: algorithm.to_string(),
+            derivation_path: vec![44, 0, 0, 0, 0],
+            signature,
+            public_key: self.public_key(),
+        })
+    }
+}
+
+struct Vault {
+    account: VaultAccount,
+    keys: HashMap<String, Key>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    vaults: Vec<Vault>,
+    transactions: HashMap<String, TransactionDetails>,
+    external_tx_ids: HashMap<String, String>,
+}
+
+struct State {
+    api_key: String,
+    decoding_key: DecodingKey,
+    ledger: Mutex<Ledger>,
+}
+
+impl State {
+    /// Verifies the JWT produced by the client's request signer and deserializes the body.
+    fn authenticate<T: DeserializeOwned>(&self, req: &Request, body: &[u8]) -> poem::Result<T> {
+        let api_key = req
+            .headers()
+            .get("X-API-KEY")
+            .and_then(|value| value.to_str().ok());
+
+        if api_key != Some(self.api_key.as_str()) {
+            return Err(error(StatusCode::UNAUTHORIZED, "unknown API key"));
+        }
+
+        let token = req
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| error(StatusCode::UNAUTHORIZED, "missing bearer token"))?;
+
+        let payload = decode::<Payload>(
+            token,
+            &self.decoding_key,
+            &Validation::new(Algorithm::RS256),
+        )
+        .map_err(|_| error(StatusCode::UNAUTHORIZED, "invalid token signature"))?
+        .claims;
+
+        let uri = req
+            .uri()
+            .path_and_query()
+            .map_or_else(|| req.uri().path(), |p| p.as_str());
+
+        if payload.sub != self.api_key || payload.uri != uri {
+            return Err(error(
+                StatusCode::UNAUTHORIZED,
+                "token does not match request",
+            ));
+        }
+
+        // The request signer hashes the serialized body even for requests sent without one.
+        let body = if body.is_empty() {
+            b"null".as_slice()
+        } else {
+            body
+        };
+
+        if payload.body_hash != hex::encode(Sha256::digest(body)) {
+            return Err(error(StatusCode::UNAUTHORIZED, "body hash mismatch"));
+        }
+
+        serde_json::from_slice(body).map_err(|e| error(StatusCode::BAD_REQUEST, e.to_string()))
+    }
+}
+
+/// An in-memory Fireblocks API.
+#[derive(Clone)]
+pub struct Emulator(Arc<State>);
+
+impl Emulator {
+    /// Creates an emulator accepting requests signed for `api_key` by the private half of `public_key_pem`.
+    ///
+    /// # Errors
+    /// Returns an error if the public key is not a valid RSA PEM.
+    pub fn new(api_key: String, public_key_pem: &[u8]) -> Result<Self> {
+        let decoding_key =
+            DecodingKey::from_rsa_pem(public_key_pem).context("failed to parse RSA public key")?;
+
+        Ok(Self(Arc::new(State {
+            api_key,
+            decoding_key,
+            ledger: Mutex::new(Ledger::default()),
+        })))
+    }
+
+    /// Builds the routes served by the emulator.
+    #[must_use]
+    pub fn routes(&self) -> impl poem::Endpoint {
+        Route::new()
+            .at("/v1/vault/accounts", post(create_vault))
+            .at("/v1/vault/accounts_paged", get(vaults))
+            .at("/v1/vault/accounts/:id", get(vault))
+            .at("/v1/vault/accounts/:id/:asset", post(create_wallet))
+            .at("/v1/vault/accounts/:id/:asset/addresses", get(addresses))
+            .at("/v1/transactions", post(create_transaction))
+            .at("/v1/transactions/:id", get(transaction))
+            .at("/v1/transactions/:id/cancel", post(cancel_transaction))
+            .at(
+                "/v1/transactions/external_tx_id/:id",
+                get(transaction_by_external_id),
+            )
+            .data(self.0.clone())
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> poem::Error {
+    let body = serde_json::json!({ "message": message.into(), "code": status.as_u16() });
+
+    poem::Error::from_response(
+        Response::builder()
+            .status(status)
+            .content_type("application/json")
+            .body(body.to_string()),
+    )
+}
+
+fn lock(state: &State) -> std::sync::MutexGuard<'_, Ledger> {
+    state
+        .ledger
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn vault_not_found(id: &str) -> poem::Error {
+    error(
+        StatusCode::NOT_FOUND,
+        format!("vault account {id} not found"),
+    )
+}
+
+#[handler]
+fn create_vault(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    body: Vec<u8>,
+) -> poem::Result<Json<VaultAccount>> {
+    let CreateVault {
+        name,
+        customer_ref_id,
+        auto_fuel,
+        ..
+    } = state.authenticate(req, &body)?;
+
+    let mut ledger = lock(state);
+
+    let account = VaultAccount {
+        id: ledger.vaults.len().to_string(),
+        name,
+        hidden_on_ui: false,
+        auto_fuel,
+        assets: Vec::new(),
+        customer_ref_id,
+    };
+
+    ledger.vaults.push(Vault {
+        account: account.clone(),
+        keys: HashMap::new(),
+    });
+
+    Ok(Json(account))
+}
+
+#[handler]
+fn vault(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path(id): Path<String>,
+    body: Vec<u8>,
+) -> poem::Result<Json<VaultAccount>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let ledger = lock(state);
+
+    ledger
+        .vaults
+        .iter()
+        .find(|v| v.account.id == id)
+        .map(|v| Json(v.account.clone()))
+        .ok_or_else(|| vault_not_found(&id))
+}
+
+/// Lists vaults in creation order. The `after` cursor is the index of the next vault.
+#[handler]
+fn vaults(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Query(filters): Query<QueryVaultAccounts>,
+    body: Vec<u8>,
+) -> poem::Result<Json<VaultAccountsPagedResponse>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let ledger = lock(state);
+    let start = filters
+        .after
+        .map(|after| after.parse::<usize>())
+        .transpose()
+        .map_err(|_| error(StatusCode::BAD_REQUEST, "invalid paging cursor"))?
+        .unwrap_or_default();
+    let limit = filters
+        .limit
+        .and_then(|limit| usize::try_from(limit).ok())
+        .unwrap_or(200);
+
+    let matching = ledger
+        .vaults
+        .iter()
+        .map(|v| &v.account)
+        .filter(|account| {
+            filters
+                .name_prefix
+                .as_ref()
+                .map_or(true, |prefix| account.name.starts_with(prefix))
+        })
+        .collect::<Vec<_>>();
+
+    let accounts = matching
+        .iter()
+        .skip(start)
+        .take(limit)
+        .map(|account| (*account).clone())
+        .collect::<Vec<_>>();
+
+    let after = (start + limit < matching.len()).then(|| (start + limit).to_string());
+
+    Ok(Json(VaultAccountsPagedResponse {
+        accounts,
+        paging: Some(Paging {
+            before: None,
+            after,
+        }),
+        previous_url: None,
+        next_url: None,
+    }))
+}
+
+#[handler]
+fn addresses(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path((id, asset_id)): Path<(String, String)>,
+    body: Vec<u8>,
+) -> poem::Result<Json<Vec<VaultAssetAddress>>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let ledger = lock(state);
+    let vault = ledger
+        .vaults
+        .iter()
+        .find(|v| v.account.id == id)
+        .ok_or_else(|| vault_not_found(&id))?;
+
+    let addresses = vault
+        .keys
+        .get(&asset_id)
+        .map(|key| VaultAssetAddress {
+            asset_id: asset_id.clone(),
+            address: key.address(),
+            description: None,
+            tag: None,
+            address_type: Some("Permanent".to_string()),
+            customer_ref_id: vault.account.customer_ref_id.clone(),
+            address_format: None,
+            legacy_address: None,
+        })
+        .into_iter()
+        .collect();
+
+    Ok(Json(addresses))
+}
+
+#[handler]
+fn create_wallet(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path((id, asset_id)): Path<(String, String)>,
+    body: Vec<u8>,
+) -> poem::Result<Json<CreateVaultAssetResponse>> {
+    state.authenticate::<CreateVaultWallet>(req, &body)?;
+
+    let mut ledger = lock(state);
+    let vault = ledger
+        .vaults
+        .iter_mut()
+        .find(|v| v.account.id == id)
+        .ok_or_else(|| vault_not_found(&id))?;
+
+    if vault.keys.contains_key(&asset_id) {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("asset {asset_id} already exists in vault account {id}"),
+        ));
+    }
+
+    let key = Key::generate(&asset_id);
+    let address = key.address();
+
+    vault.keys.insert(asset_id.clone(), key);
+    vault.account.assets.push(VaultAsset {
+        id: asset_id.clone(),
+        total: "0".to_string(),
+        pending: "0".to_string(),
+        locked_amount: "0".to_string(),
+        staked: None,
+        available: "0".to_string(),
+        frozen: "0".to_string(),
+        max_bip44_address_index_used: None,
+        max_bip44_change_address_index_used: None,
+        total_staked_cpu: None,
+        total_staked_network: None,
+        self_staked_cpu: None,
+        self_staked_network: None,
+        pending_refund_cpu: None,
+        pending_refund_network: None,
+        block_height: None,
+        block_hash: None,
+    });
+
+    Ok(Json(CreateVaultAssetResponse {
+        id: asset_id,
+        address: address.clone(),
+        legacy_address: address,
+        tag: String::new(),
+        eos_account_name: None,
+    }))
+}
+
+#[handler]
+fn create_transaction(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    body: Vec<u8>,
+) -> poem::Result<Json<CreateTransactionResponse>> {
+    let CreateTransaction {
+        asset_id,
+        source,
+        operation,
+        external_tx_id,
+        extra_parameters,
+        ..
+    } = state.authenticate(req, &body)?;
+
+    let mut ledger = lock(state);
+
+    if let Some(external_tx_id) = &external_tx_id {
+        if ledger.external_tx_ids.contains_key(external_tx_id) {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                format!("transaction with externalTxId {external_tx_id} already exists"),
+            ));
+        }
+    }
+    let vault = ledger
+        .vaults
+        .iter()
+        .find(|v| v.account.id == source.id)
+        .ok_or_else(|| vault_not_found(&source.id))?;
+
+    let (signed_messages, tx_hash) = match (operation, extra_parameters) {
+        (TransactionOperation::RAW, Some(ExtraParameters::RawMessageData(data))) => {
+            let key = vault.keys.get(&asset_id).ok_or_else(|| {
+                error(
+                    StatusCode::BAD_REQUEST,
+                    format!("asset {asset_id} not found in vault account {}", source.id),
+                )
+            })?;
+
+            let signed_messages = data
+                .messages
+                .into_iter()
+                .map(|m| key.sign(m.content))
+                .collect::<poem::Result<Vec<_>>>()?;
+
+            (signed_messages, String::new())
+        },
+        (TransactionOperation::RAW, _) => {
+            return Err(error(StatusCode::BAD_REQUEST, "missing raw message data"));
+        },
+        _ => {
+            let mut hash = [0u8; 32];
+            OsRng.fill_bytes(&mut hash);
+
+            (Vec::new(), format!("0x{}", hex::encode(hash)))
+        },
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let status = TransactionStatus::COMPLETED;
+
+    if let Some(external_tx_id) = external_tx_id {
+        ledger.external_tx_ids.insert(external_tx_id, id.clone());
+    }
+
+    ledger.transactions.insert(id.clone(), TransactionDetails {
+        id: id.clone(),
+        asset_id,
+        tx_hash,
+        status,
+        sub_status: String::new(),
+        signed_messages,
+    });
+
+    Ok(Json(CreateTransactionResponse { id, status }))
+}
+
+#[handler]
+fn transaction(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path(id): Path<String>,
+    body: Vec<u8>,
+) -> poem::Result<Json<TransactionDetails>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let ledger = lock(state);
+
+    ledger
+        .transactions
+        .get(&id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("transaction {id} not found")))
+}
+
+#[handler]
+fn transaction_by_external_id(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path(external_tx_id): Path<String>,
+    body: Vec<u8>,
+) -> poem::Result<Json<TransactionDetails>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let ledger = lock(state);
+
+    ledger
+        .external_tx_ids
+        .get(&external_tx_id)
+        .and_then(|id| ledger.transactions.get(id))
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            error(
+                StatusCode::NOT_FOUND,
+                format!("transaction with externalTxId {external_tx_id} not found"),
+            )
+        })
+}
+
+#[handler]
+fn cancel_transaction(
+    req: &Request,
+    Data(state): Data<&Arc<State>>,
+    Path(id): Path<String>,
+    body: Vec<u8>,
+) -> poem::Result<Json<CancelTransactionResponse>> {
+    state.authenticate::<()>(req, &body)?;
+
+    let mut ledger = lock(state);
+
+    let transaction = ledger
+        .transactions
+        .get_mut(&id)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("transaction {id} not found")))?;
+
+    if transaction.status.class() != StatusClass::Pending {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("transaction {id} can no longer be cancelled"),
+        ));
+    }
+
+    transaction.status = TransactionStatus::CANCELLED;
+
+    Ok(Json(CancelTransactionResponse { success: true }))
+}
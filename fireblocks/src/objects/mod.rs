@@ -1,3 +1,4 @@
 #![allow(clippy::module_name_repetitions)]
 pub mod transaction;
 pub mod vault;
+pub mod webhook;
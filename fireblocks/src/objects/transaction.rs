@@ -1,5 +1,7 @@
+use std::str::FromStr;
+
 use async_graphql::{Enum, SimpleObject};
-use hub_core::serde_with::skip_serializing_none;
+use hub_core::{serde_json, serde_with::skip_serializing_none};
 use serde::{Deserialize, Serialize};
 
 /// <https://docs.fireblocks.com/api/?javascript#create-a-new-transaction>
@@ -14,10 +16,11 @@ pub struct CreateTransaction {
     pub amount: String,
     pub treat_as_gross_amount: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub feelevel: Option<String>,
+    pub feelevel: Option<FeeLevel>,
     pub note: Option<String>,
     pub operation: TransactionOperation,
     pub customer_ref_id: Option<String>,
+    pub external_tx_id: Option<String>,
     pub extra_parameters: Option<ExtraParameters>,
 }
 
@@ -28,6 +31,15 @@ pub enum ExtraParameters {
     RawMessageData(RawMessageData),
 }
 
+/// <https://docs.fireblocks.com/api/?javascript#create-a-new-transaction>
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum FeeLevel {
+    LOW,
+    MEDIUM,
+    HIGH,
+}
+
 /// <https://docs.fireblocks.com/api/?javascript#transactionoperation>
 #[allow(non_camel_case_types)]
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
@@ -86,7 +98,7 @@ pub struct OneTimeAddress {
 /// <https://docs.fireblocks.com/api/?javascript#transactionstatus>
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Enum, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 pub enum TransactionStatus {
     UNSPECIFIED = 0,
     SUBMITTED = 1,
@@ -105,6 +117,64 @@ pub enum TransactionStatus {
     BLOCKED = 14,
     FAILED = 15,
     PENDING = 16,
+    /// A status Fireblocks reported that this client does not know, e.g. one added to the API since.
+    #[serde(other)]
+    UNKNOWN = 17,
+}
+
+/// How a transaction status is treated while waiting on a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusClass {
+    /// The transaction may still change state.
+    Pending,
+    /// The transaction completed.
+    Success,
+    /// The transaction will never complete.
+    Failure,
+}
+
+impl TransactionStatus {
+    /// The class of the status unless overridden by a [`WaitPolicy`](crate::WaitPolicy).
+    #[must_use]
+    pub fn class(self) -> StatusClass {
+        match self {
+            Self::COMPLETED => StatusClass::Success,
+            Self::CANCELLED | Self::REJECTED | Self::BLOCKED | Self::FAILED => StatusClass::Failure,
+            Self::UNSPECIFIED
+            | Self::SUBMITTED
+            | Self::QUEUED
+            | Self::PENDING_AUTHORIZATION
+            | Self::PENDING_SIGNATURE
+            | Self::BROADCASTING
+            | Self::PENDING_3RD_PARTY_MANUAL_APPROVAL
+            | Self::PENDING_3RD_PARTY
+            | Self::CONFIRMING
+            | Self::PARTIALLY_COMPLETED
+            | Self::PENDING_AML_SCREENING
+            | Self::PENDING
+            | Self::UNKNOWN => StatusClass::Pending,
+        }
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match serde_json::from_value(serde_json::Value::String(s.to_uppercase()))? {
+            Self::UNKNOWN => Err(serde::de::Error::custom(format!(
+                "unknown transaction status {s:?}"
+            ))),
+            status => Ok(status),
+        }
+    }
+}
+
+/// <https://developers.fireblocks.com/reference/post_transactions-txid-cancel>
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelTransactionResponse {
+    pub success: bool,
 }
 
 /// <https://docs.fireblocks.com/api/?javascript#createtransactionresponse>
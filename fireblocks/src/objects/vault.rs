@@ -5,19 +5,20 @@ use serde::{Deserialize, Serialize};
 /// Paged
 /// Query parameters
 /// <https://docs.fireblocks.com/api/?javascript#list-vault-accounts-paged>
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, SimpleObject)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryVaultAccounts {
     pub name_prefix: Option<String>,
     pub name_suffix: Option<String>,
     pub min_amount_threshold: Option<u64>,
-    pub asset_id: Option<u64>,
-    pub order_by: String,
-    pub limit: u64,
+    pub asset_id: Option<String>,
+    pub order_by: Option<String>,
+    pub limit: Option<u64>,
     pub before: Option<String>,
     pub after: Option<String>,
-    pub max_bip44_address_index_used: u64,
-    pub max_bip44_change_address_index_used: u64,
+    pub max_bip44_address_index_used: Option<u64>,
+    pub max_bip44_change_address_index_used: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
@@ -78,6 +79,21 @@ pub struct VaultAsset {
     pub block_hash: Option<String>,
 }
 
+/// <https://docs.fireblocks.com/api/?javascript#vaultassetaddress>
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultAssetAddress {
+    pub asset_id: String,
+    pub address: String,
+    pub description: Option<String>,
+    pub tag: Option<String>,
+    #[serde(rename = "type")]
+    pub address_type: Option<String>,
+    pub customer_ref_id: Option<String>,
+    pub address_format: Option<String>,
+    pub legacy_address: Option<String>,
+}
+
 /// Query parameters
 /// <https://docs.fireblocks.com/api/?javascript#create-a-new-vault-account>
 #[skip_serializing_none]
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::transaction::TransactionStatus;
+
+/// A notification posted by Fireblocks to the configured webhook URL.
+/// <https://developers.fireblocks.com/reference/webhooks-structures>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookNotification {
+    TransactionStatusUpdated(TransactionNotification),
+    VaultAccountAdded(VaultAccountNotification),
+    #[serde(other)]
+    Unsupported,
+}
+
+/// The transaction carried by a `TRANSACTION_STATUS_UPDATED` notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionNotification {
+    pub id: String,
+    pub status: TransactionStatus,
+    pub sub_status: Option<String>,
+    pub tx_hash: Option<String>,
+    pub external_tx_id: Option<String>,
+}
+
+/// The vault account carried by a `VAULT_ACCOUNT_ADDED` notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultAccountNotification {
+    pub id: String,
+    pub name: String,
+    pub customer_ref_id: Option<String>,
+}
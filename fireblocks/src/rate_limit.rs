@@ -0,0 +1,100 @@
+//! Client-side rate limiting of requests to the Fireblocks API.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hub_core::tokio::time::{self, Instant};
+
+/// A token bucket shared by every clone of a [`Client`](crate::Client).
+/// Requests take a token each, and tokens are refilled at a steady rate up to the bucket capacity.
+#[derive(Debug, Clone)]
+pub struct RateLimiter(Arc<Mutex<Bucket>>);
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    per_second: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `per_second` requests on average, with bursts of up to `burst` requests.
+    #[must_use]
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+
+        Self(Arc::new(Mutex::new(Bucket {
+            capacity,
+            tokens: capacity,
+            per_second: f64::from(per_second.max(1)),
+            refilled_at: Instant::now(),
+        })))
+    }
+
+    /// Waits until a request may be sent.
+    /// Tokens are reserved in the order callers arrive, so waiting callers are served first come first serve.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self
+                .0
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+
+            bucket.tokens = (bucket.tokens + elapsed * bucket.per_second).min(bucket.capacity);
+            bucket.refilled_at = now;
+            bucket.tokens -= 1.0;
+
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+
+            Duration::from_secs_f64(-bucket.tokens / bucket.per_second)
+        };
+
+        time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_allows_a_burst_then_waits_for_refills() {
+        let limiter = RateLimiter::new(2, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_refills_up_to_the_burst() {
+        let limiter = RateLimiter::new(1, 2);
+
+        time::sleep(Duration::from_secs(10)).await;
+
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
@@ -25,13 +25,13 @@ impl RequestSigner {
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Payload {
-    uri: String,
-    nonce: u64,
-    iat: u64,
-    exp: u64,
-    sub: String,
+    pub(crate) uri: String,
+    pub(crate) nonce: u64,
+    pub(crate) iat: u64,
+    pub(crate) exp: u64,
+    pub(crate) sub: String,
     #[serde(rename = "bodyHash")]
-    body_hash: String,
+    pub(crate) body_hash: String,
 }
 
 impl Payload {
@@ -0,0 +1,156 @@
+//! Waiting on submitted transactions.
+
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    objects::transaction::{StatusClass, TransactionDetails, TransactionStatus},
+    Error, FbArgs,
+};
+
+/// Controls how long [`Client::wait_on_transaction_completion`](crate::Client::wait_on_transaction_completion)
+/// waits on a transaction and which statuses end the wait.
+#[derive(Debug, Clone)]
+pub struct WaitPolicy {
+    /// How long to wait on a pending transaction before giving up.
+    pub deadline: Duration,
+    /// How often the transaction is checked.
+    pub interval: Duration,
+    /// Whether to cancel the transaction with Fireblocks once the deadline passes.
+    pub cancel_on_timeout: bool,
+    overrides: HashMap<TransactionStatus, StatusClass>,
+}
+
+impl Default for WaitPolicy {
+    fn default() -> Self {
+        Self {
+            deadline: Duration::from_secs(600),
+            interval: Duration::from_millis(2500),
+            cancel_on_timeout: false,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl WaitPolicy {
+    #[must_use]
+    pub fn new(args: &FbArgs) -> Self {
+        let overrides = args
+            .fireblocks_pending_statuses
+            .iter()
+            .map(|status| (*status, StatusClass::Pending))
+            .chain(
+                args.fireblocks_failure_statuses
+                    .iter()
+                    .map(|status| (*status, StatusClass::Failure)),
+            )
+            .collect();
+
+        Self {
+            deadline: Duration::from_secs(args.fireblocks_wait_deadline_secs),
+            interval: Duration::from_millis(args.fireblocks_wait_interval_ms),
+            cancel_on_timeout: args.fireblocks_cancel_on_timeout,
+            overrides,
+        }
+    }
+
+    /// Treats `status` as `class` instead of its default class.
+    #[must_use]
+    pub fn with_status(mut self, status: TransactionStatus, class: StatusClass) -> Self {
+        self.overrides.insert(status, class);
+        self
+    }
+
+    /// The class of `status` under this policy.
+    #[must_use]
+    pub fn classify(&self, status: TransactionStatus) -> StatusClass {
+        self.overrides
+            .get(&status)
+            .copied()
+            .unwrap_or_else(|| status.class())
+    }
+}
+
+/// The state a transaction was left in once waiting on it ended.
+#[derive(Debug, Clone)]
+pub enum TransactionOutcome {
+    /// The transaction completed.
+    Completed(TransactionDetails),
+    /// The transaction reached a status classified as a failure.
+    Failed(TransactionDetails),
+    /// The transaction was still pending when the deadline passed.
+    TimedOut {
+        details: TransactionDetails,
+        cancelled: bool,
+    },
+}
+
+impl TransactionOutcome {
+    /// The last known details of the transaction.
+    #[must_use]
+    pub fn details(&self) -> &TransactionDetails {
+        match self {
+            Self::Completed(details) | Self::Failed(details) | Self::TimedOut { details, .. } => {
+                details
+            },
+        }
+    }
+
+    /// Returns the details of a completed transaction.
+    ///
+    /// # Errors
+    /// Returns [`Error::Transaction`] with the last status of the transaction if it did not complete.
+    pub fn completed(self) -> Result<TransactionDetails, Error> {
+        match self {
+            Self::Completed(details) => Ok(details),
+            Self::Failed(details) | Self::TimedOut { details, .. } => {
+                Err(Error::Transaction(details.status))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_defaults_to_the_class_of_the_status() {
+        let policy = WaitPolicy::default();
+
+        assert_eq!(
+            policy.classify(TransactionStatus::COMPLETED),
+            StatusClass::Success
+        );
+        assert_eq!(
+            policy.classify(TransactionStatus::BLOCKED),
+            StatusClass::Failure
+        );
+        assert_eq!(
+            policy.classify(TransactionStatus::CONFIRMING),
+            StatusClass::Pending
+        );
+    }
+
+    #[test]
+    fn classify_prefers_overridden_classes() {
+        let policy = WaitPolicy::default()
+            .with_status(
+                TransactionStatus::PENDING_AML_SCREENING,
+                StatusClass::Failure,
+            )
+            .with_status(TransactionStatus::CANCELLED, StatusClass::Pending);
+
+        assert_eq!(
+            policy.classify(TransactionStatus::PENDING_AML_SCREENING),
+            StatusClass::Failure
+        );
+        assert_eq!(
+            policy.classify(TransactionStatus::CANCELLED),
+            StatusClass::Pending
+        );
+        assert_eq!(
+            policy.classify(TransactionStatus::REJECTED),
+            StatusClass::Failure
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Verification of the notifications Fireblocks posts to webhooks.
+
+use hub_core::anyhow::{anyhow, Result};
+use rsa::{
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier as _,
+    RsaPublicKey,
+};
+use sha2::Sha512;
+
+/// The header holding the base64 encoded RSA-SHA512 signature of the notification body.
+pub const SIGNATURE_HEADER: &str = "Fireblocks-Signature";
+
+/// Verifies webhook notifications against the public key of the Fireblocks workspace.
+#[derive(Debug, Clone)]
+pub struct WebhookVerifier(VerifyingKey<Sha512>);
+
+impl WebhookVerifier {
+    /// Parses the PEM encoded public key Fireblocks signs notifications with.
+    ///
+    /// # Errors
+    /// Returns an error if the key is not a valid PEM encoded RSA public key.
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let key = RsaPublicKey::from_public_key_pem(pem)
+            .map_err(|e| anyhow!("invalid Fireblocks webhook public key: {e}"))?;
+
+        Ok(Self(VerifyingKey::new(key)))
+    }
+
+    /// Checks `signature`, the value of the [`SIGNATURE_HEADER`] header, against the raw body of the notification.
+    ///
+    /// # Errors
+    /// Returns an error if the signature is malformed or does not match the body.
+    pub fn verify(&self, body: &[u8], signature: &str) -> Result<()> {
+        let signature = base64::decode(signature.trim())?;
+        let signature = Signature::try_from(signature.as_slice())
+            .map_err(|e| anyhow!("malformed webhook signature: {e}"))?;
+
+        self.0
+            .verify(body, &signature)
+            .map_err(|e| anyhow!("webhook signature mismatch: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::{
+        pkcs1v15::SigningKey,
+        pkcs8::{EncodePublicKey, LineEnding},
+        signature::{SignatureEncoding, Signer},
+        RsaPrivateKey,
+    };
+
+    use super::*;
+    use crate::objects::{transaction::TransactionStatus, webhook::WebhookNotification};
+
+    const BODY: &[u8] = br#"{"type":"TRANSACTION_STATUS_UPDATED","data":{"id":"1"}}"#;
+
+    fn keys() -> (SigningKey<Sha512>, WebhookVerifier) {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        let pem = key
+            .to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+
+        (
+            SigningKey::new(key),
+            WebhookVerifier::from_pem(&pem).unwrap(),
+        )
+    }
+
+    fn sign(key: &SigningKey<Sha512>, body: &[u8]) -> String {
+        base64::encode(key.sign(body).to_bytes())
+    }
+
+    #[test]
+    fn verify_accepts_the_signed_body() {
+        let (key, verifier) = keys();
+
+        verifier
+            .verify(BODY, &format!(" {}\n", sign(&key, BODY)))
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_rejects_a_tampered_body() {
+        let (key, verifier) = keys();
+        let signature = sign(&key, BODY);
+
+        assert!(verifier.verify(b"{}", &signature).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_signatures_of_another_key() {
+        let (_, verifier) = keys();
+        let (other, _) = keys();
+
+        assert!(verifier.verify(BODY, &sign(&other, BODY)).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_malformed_signatures() {
+        let (_, verifier) = keys();
+
+        assert!(verifier.verify(BODY, "not a signature").is_err());
+        assert!(verifier.verify(BODY, &base64::encode([0; 8])).is_err());
+    }
+
+    #[test]
+    fn from_pem_rejects_invalid_keys() {
+        assert!(WebhookVerifier::from_pem("not a key").is_err());
+    }
+
+    #[test]
+    fn notifications_with_unknown_statuses_deserialize() {
+        let body =
+            r#"{"type":"TRANSACTION_STATUS_UPDATED","data":{"id":"1","status":"NEW_STATUS"}}"#;
+
+        let WebhookNotification::TransactionStatusUpdated(transaction) =
+            hub_core::serde_json::from_str(body).unwrap()
+        else {
+            panic!("expected a transaction notification");
+        };
+
+        assert_eq!(transaction.status, TransactionStatus::UNKNOWN);
+        assert!("NEW_STATUS".parse::<TransactionStatus>().is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Signs RAW messages through the client against the in-process emulator.
+
+use std::net::SocketAddr;
+
+use ed25519_dalek::Verifier;
+use fireblocks::{
+    mock::Emulator,
+    objects::{
+        transaction::{SignedMessageResponse, TransactionStatus},
+        vault::{CreateVault, CreateVaultWallet},
+    },
+    Client, FbArgs,
+};
+use hub_core::{bs58, uuid::Uuid};
+use k256::{
+    ecdsa::{signature::hazmat::PrehashVerifier, RecoveryId, Signature, VerifyingKey},
+    elliptic_curve::sec1::ToEncodedPoint,
+};
+use poem::{
+    listener::{Acceptor, Listener, TcpListener},
+    Server,
+};
+use rsa::{
+    pkcs1::EncodeRsaPrivateKey,
+    pkcs8::{EncodePublicKey, LineEnding},
+    RsaPrivateKey,
+};
+use sha3::{Digest, Keccak256};
+
+const API_KEY: &str = "emulator-api-key";
+
+/// Serves a new emulator on a free port and returns a client signing requests for it.
+async fn client() -> Client {
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+    let public_key = key
+        .to_public_key()
+        .to_public_key_pem(LineEnding::LF)
+        .unwrap();
+    let secret_path = std::env::temp_dir().join(format!("fireblocks-{}.pem", Uuid::new_v4()));
+
+    std::fs::write(
+        &secret_path,
+        key.to_pkcs1_pem(LineEnding::LF).unwrap().as_bytes(),
+    )
+    .unwrap();
+
+    let emulator = Emulator::new(API_KEY.to_string(), public_key.as_bytes()).unwrap();
+    let acceptor = TcpListener::bind("127.0.0.1:0")
+        .into_acceptor()
+        .await
+        .unwrap();
+    let addr: SocketAddr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+
+    tokio::spawn(Server::new_with_acceptor(acceptor).run(emulator.routes()));
+
+    Client::new(FbArgs {
+        fireblocks_endpoint: format!("http://{addr}"),
+        fireblocks_api_key: API_KEY.to_string(),
+        fireblocks_secret_path: secret_path.to_string_lossy().into_owned(),
+        fireblocks_test_mode: true,
+        fireblocks_supported_asset_ids: Vec::new(),
+        fireblocks_treasury_vault_id: String::new(),
+        fireblocks_whitelisted_contract_wallet_id: String::new(),
+        fireblocks_wait_deadline_secs: 5,
+        fireblocks_wait_interval_ms: 10,
+        fireblocks_cancel_on_timeout: false,
+        fireblocks_pending_statuses: Vec::new(),
+        fireblocks_failure_statuses: Vec::new(),
+        fireblocks_webhook_public_key: None,
+        fireblocks_rate_limit_per_sec: 100,
+        fireblocks_rate_limit_burst: 100,
+        fireblocks_max_retries: 0,
+    })
+    .unwrap()
+}
+
+/// Creates a vault holding a wallet of `asset_id` and signs `message` with it.
+/// Returns the address of the wallet and the signed message.
+async fn sign(
+    client: &Client,
+    asset_id: &str,
+    message: Vec<u8>,
+) -> (String, SignedMessageResponse) {
+    let vault = client
+        .create()
+        .vault(CreateVault {
+            name: format!("project:{}", Uuid::new_v4()),
+            hidden_on_ui: None,
+            customer_ref_id: None,
+            auto_fuel: Some(false),
+        })
+        .await
+        .unwrap();
+
+    let wallet = client
+        .create()
+        .wallet(vault.id.clone(), asset_id.to_string(), CreateVaultWallet {
+            eos_account_name: None,
+        })
+        .await
+        .unwrap();
+
+    let transaction = client
+        .create()
+        .raw_transaction(
+            asset_id.to_string(),
+            vault.id,
+            vec![message.clone()],
+            "signing round trip".to_string(),
+            Some(Uuid::new_v4().to_string()),
+        )
+        .await
+        .unwrap();
+
+    let details = client
+        .wait_on_transaction_completion(transaction.id)
+        .await
+        .unwrap()
+        .completed()
+        .unwrap();
+
+    assert_eq!(details.status, TransactionStatus::COMPLETED);
+    assert_eq!(details.signed_messages.len(), 1);
+
+    let signed = details.signed_messages.into_iter().next().unwrap();
+
+    assert_eq!(signed.content, hex::encode(message));
+
+    (wallet.address, signed)
+}
+
+#[tokio::test]
+async fn signs_raw_messages_with_ed25519_keys() {
+    let client = client().await;
+    let message = b"treasury signing round trip".to_vec();
+
+    let (address, signed) = sign(&client, "SOL_TEST", message.clone()).await;
+
+    let public_key: [u8; 32] = hex::decode(&signed.public_key).unwrap().try_into().unwrap();
+    let key = ed25519_dalek::VerifyingKey::from_bytes(&public_key).unwrap();
+    let signature: [u8; 64] = hex::decode(&signed.signature.full_sig)
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    assert_eq!(bs58::encode(public_key).into_string(), address);
+    key.verify(&message, &ed25519_dalek::Signature::from_bytes(&signature))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn signs_raw_messages_with_secp256k1_keys() {
+    let client = client().await;
+    let hash = Keccak256::digest(b"treasury signing round trip").to_vec();
+
+    let (address, signed) = sign(&client, "ETH_TEST", hash.clone()).await;
+
+    let key = VerifyingKey::from_sec1_bytes(&hex::decode(&signed.public_key).unwrap()).unwrap();
+    let signature =
+        Signature::from_slice(&hex::decode(&signed.signature.full_sig).unwrap()).unwrap();
+    let recovery_id =
+        RecoveryId::from_byte(u8::try_from(signed.signature.v.unwrap()).unwrap()).unwrap();
+
+    key.verify_prehash(&hash, &signature).unwrap();
+    assert_eq!(
+        VerifyingKey::recover_from_prehash(&hash, &signature, recovery_id).unwrap(),
+        key
+    );
+
+    let point = key.to_encoded_point(false);
+    let expected = format!(
+        "0x{}",
+        hex::encode(&Keccak256::digest(&point.as_bytes()[1..])[12..])
+    );
+
+    assert_eq!(address, expected);
+}
@@ -18,6 +18,22 @@ mod m20230721_145312_add_create_collection_to_tx_type_enum;
 mod m20230724_142109_add_mint_to_collection_to_tx_type_enum;
 mod m20230823_114606_add_switch_collection_to_tx_type;
 mod m20230828_114322_downcase_wallet_address_field_for_polygon;
+mod m20261017_093512_add_event_context_to_transactions;
+mod m20261017_141205_create_processed_events_table;
+mod m20261017_160412_add_external_tx_id_to_transactions;
+mod m20261017_161530_create_pending_events_table;
+mod m20261017_183020_create_dead_letters_table;
+mod m20261017_201145_create_project_balance_thresholds_table;
+mod m20261017_213410_add_transfer_native_to_tx_type;
+mod m20261017_224015_add_fuel_vault_to_tx_type;
+mod m20261017_224120_create_gas_station_fuelings_table;
+mod m20261017_231045_create_wallet_creations_table;
+mod m20261017_234210_create_auto_provisioned_assets_table;
+mod m20261017_235530_create_project_settings_table;
+mod m20261018_001215_add_sub_status_and_event_kind_to_transactions;
+mod m20261018_001340_create_transaction_status_changes_table;
+mod m20261018_091530_add_organization_id_to_project_treasuries;
+mod m20261018_094210_create_organization_credit_balances_table;
 
 pub struct Migrator;
 
@@ -43,6 +59,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20230724_142109_add_mint_to_collection_to_tx_type_enum::Migration),
             Box::new(m20230828_114322_downcase_wallet_address_field_for_polygon::Migration),
             Box::new(m20230823_114606_add_switch_collection_to_tx_type::Migration),
+            Box::new(m20261017_093512_add_event_context_to_transactions::Migration),
+            Box::new(m20261017_141205_create_processed_events_table::Migration),
+            Box::new(m20261017_160412_add_external_tx_id_to_transactions::Migration),
+            Box::new(m20261017_161530_create_pending_events_table::Migration),
+            Box::new(m20261017_183020_create_dead_letters_table::Migration),
+            Box::new(m20261017_201145_create_project_balance_thresholds_table::Migration),
+            Box::new(m20261017_213410_add_transfer_native_to_tx_type::Migration),
+            Box::new(m20261017_224015_add_fuel_vault_to_tx_type::Migration),
+            Box::new(m20261017_224120_create_gas_station_fuelings_table::Migration),
+            Box::new(m20261017_231045_create_wallet_creations_table::Migration),
+            Box::new(m20261017_234210_create_auto_provisioned_assets_table::Migration),
+            Box::new(m20261017_235530_create_project_settings_table::Migration),
+            Box::new(m20261018_001215_add_sub_status_and_event_kind_to_transactions::Migration),
+            Box::new(m20261018_001340_create_transaction_status_changes_table::Migration),
+            Box::new(m20261018_091530_add_organization_id_to_project_treasuries::Migration),
+            Box::new(m20261018_094210_create_organization_credit_balances_table::Migration),
         ]
     }
 }
@@ -0,0 +1,142 @@
+use sea_orm_migration::{prelude::*, sea_orm::Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The context columns are added as nullable and tightened once existing transactions are
+        // backfilled.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Transactions::Signatures)
+                            .array(ColumnType::Text)
+                            .not_null()
+                            .default("{}"),
+                    )
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::EventId).string())
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::ProjectId).uuid())
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::VaultId).string())
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::AssetId).string())
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::Status).string())
+                    .add_column_if_not_exists(ColumnDef::new(Transactions::TxHash).string())
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Transactions::UpdatedAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Transactions were only recorded once signed, so existing ones are completed. Their event,
+        // project, vault and asset were never recorded: the event id falls back to the Fireblocks
+        // id, and the others to the nil project and empty ids.
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"UPDATE transactions
+            SET signatures = CASE WHEN signature = '' THEN '{}' ELSE ARRAY[signature] END,
+                event_id = fireblocks_id::text,
+                project_id = '00000000-0000-0000-0000-000000000000',
+                vault_id = '',
+                asset_id = '',
+                status = 'COMPLETED'
+            WHERE event_id IS NULL;"#
+                .to_string(),
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"ALTER TABLE transactions
+                ALTER COLUMN event_id SET NOT NULL,
+                ALTER COLUMN project_id SET NOT NULL,
+                ALTER COLUMN vault_id SET NOT NULL,
+                ALTER COLUMN asset_id SET NOT NULL,
+                ALTER COLUMN status SET NOT NULL,
+                DROP COLUMN signature;"#
+                .to_string(),
+        ))
+        .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("transactions_event_id_idx")
+                    .table(Transactions::Table)
+                    .col(Transactions::EventId)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("transactions_project_id_idx")
+                    .table(Transactions::Table)
+                    .col(Transactions::ProjectId)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Transactions::Signature)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "UPDATE transactions SET signature = COALESCE(signatures[1], '');".to_string(),
+            ))
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::Signatures)
+                    .drop_column(Transactions::EventId)
+                    .drop_column(Transactions::ProjectId)
+                    .drop_column(Transactions::VaultId)
+                    .drop_column(Transactions::AssetId)
+                    .drop_column(Transactions::Status)
+                    .drop_column(Transactions::TxHash)
+                    .drop_column(Transactions::UpdatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    Signature,
+    Signatures,
+    EventId,
+    ProjectId,
+    VaultId,
+    AssetId,
+    Status,
+    TxHash,
+    UpdatedAt,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProcessedEvents::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ProcessedEvents::EventId).string().not_null())
+                    .col(
+                        ColumnDef::new(ProcessedEvents::EventKind)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProcessedEvents::PayloadDigest)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProcessedEvents::Position)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProcessedEvents::MessageKey)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProcessedEvents::MessagePayload)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProcessedEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ProcessedEvents::EventId)
+                            .col(ProcessedEvents::EventKind)
+                            .col(ProcessedEvents::PayloadDigest)
+                            .col(ProcessedEvents::Position),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProcessedEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ProcessedEvents {
+    Table,
+    EventId,
+    EventKind,
+    PayloadDigest,
+    Position,
+    MessageKey,
+    MessagePayload,
+    CreatedAt,
+}
@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingEvents::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PendingEvents::Topic).string().not_null())
+                    .col(
+                        ColumnDef::new(PendingEvents::MessageKey)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::MessagePayload)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::FireblocksId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PendingEvents::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("pending_events_locked_until_idx")
+                    .table(PendingEvents::Table)
+                    .col(PendingEvents::LockedUntil)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PendingEvents {
+    Table,
+    Id,
+    Topic,
+    MessageKey,
+    MessagePayload,
+    FireblocksId,
+    CreatedAt,
+    UpdatedAt,
+    Attempts,
+    LockedUntil,
+}
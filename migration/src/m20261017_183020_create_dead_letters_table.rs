@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DeadLetters::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DeadLetters::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(ColumnDef::new(DeadLetters::Topic).string().not_null())
+                    .col(ColumnDef::new(DeadLetters::MessageKey).binary().not_null())
+                    .col(
+                        ColumnDef::new(DeadLetters::MessagePayload)
+                            .binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(DeadLetters::Error).text().not_null())
+                    .col(ColumnDef::new(DeadLetters::ErrorKind).string().not_null())
+                    .col(
+                        ColumnDef::new(DeadLetters::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .col(ColumnDef::new(DeadLetters::ReplayedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("dead_letters_created_at_idx")
+                    .table(DeadLetters::Table)
+                    .col(DeadLetters::CreatedAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DeadLetters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum DeadLetters {
+    Table,
+    Id,
+    Topic,
+    MessageKey,
+    MessagePayload,
+    Error,
+    ErrorKind,
+    CreatedAt,
+    ReplayedAt,
+}
@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectBalanceThresholds::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectBalanceThresholds::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceThresholds::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceThresholds::AssetId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceThresholds::Threshold)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceThresholds::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("project_balance_thresholds_project_id_asset_id_idx")
+                    .table(ProjectBalanceThresholds::Table)
+                    .col(ProjectBalanceThresholds::ProjectId)
+                    .col(ProjectBalanceThresholds::AssetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // The treasuries reported as running low on an asset, so each is reported once until it
+        // recovers.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectBalanceAlerts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectBalanceAlerts::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceAlerts::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceAlerts::AssetId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectBalanceAlerts::NotifiedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("project_balance_alerts_project_id_asset_id_idx")
+                    .table(ProjectBalanceAlerts::Table)
+                    .col(ProjectBalanceAlerts::ProjectId)
+                    .col(ProjectBalanceAlerts::AssetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProjectBalanceAlerts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ProjectBalanceThresholds::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ProjectBalanceThresholds {
+    Table,
+    Id,
+    ProjectId,
+    AssetId,
+    Threshold,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+pub enum ProjectBalanceAlerts {
+    Table,
+    Id,
+    ProjectId,
+    AssetId,
+    NotifiedAt,
+}
@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GasStationFuelings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GasStationFuelings::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::VaultId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::AssetId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::Balance)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::Amount)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::Outcome)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GasStationFuelings::ExternalTxId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(GasStationFuelings::FireblocksId).uuid())
+                    .col(
+                        ColumnDef::new(GasStationFuelings::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("gas_station_fuelings_project_id_created_at_idx")
+                    .table(GasStationFuelings::Table)
+                    .col(GasStationFuelings::ProjectId)
+                    .col(GasStationFuelings::CreatedAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GasStationFuelings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum GasStationFuelings {
+    Table,
+    Id,
+    ProjectId,
+    VaultId,
+    AssetId,
+    Balance,
+    Amount,
+    Outcome,
+    ExternalTxId,
+    FireblocksId,
+    CreatedAt,
+}
@@ -0,0 +1,122 @@
+use sea_orm_migration::{prelude::*, sea_orm::Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WalletCreations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WalletCreations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletCreations::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletCreations::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(WalletCreations::CustomerId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletCreations::ProjectId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(WalletCreations::TreasuryId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletCreations::AssetId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletCreations::Address).string())
+                    .col(ColumnDef::new(WalletCreations::Status).string().not_null())
+                    .col(ColumnDef::new(WalletCreations::Error).text())
+                    .col(
+                        ColumnDef::new(WalletCreations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .col(
+                        ColumnDef::new(WalletCreations::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("wallet_creations_status_updated_at_idx")
+                    .table(WalletCreations::Table)
+                    .col(WalletCreations::Status)
+                    .col(WalletCreations::UpdatedAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("wallet_creations_treasury_id_asset_id_idx")
+                    .table(WalletCreations::Table)
+                    .col(WalletCreations::TreasuryId)
+                    .col(WalletCreations::AssetId)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        // A treasury has at most one creation in progress per asset.
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                r#"CREATE UNIQUE INDEX IF NOT EXISTS wallet_creations_in_progress_treasury_id_asset_id_idx
+                ON wallet_creations (treasury_id, asset_id)
+                WHERE status IN ('reserved', 'provisioned', 'recorded', 'charged');"#
+                    .to_string(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WalletCreations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum WalletCreations {
+    Table,
+    Id,
+    OrganizationId,
+    UserId,
+    CustomerId,
+    ProjectId,
+    TreasuryId,
+    AssetId,
+    Address,
+    Status,
+    Error,
+    CreatedAt,
+    UpdatedAt,
+}
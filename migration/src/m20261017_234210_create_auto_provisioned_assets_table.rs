@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AutoProvisionedAssets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::ProjectId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::AssetId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::CreatedBy)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AutoProvisionedAssets::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("auto_provisioned_assets_project_id_asset_id_idx")
+                    .table(AutoProvisionedAssets::Table)
+                    .col(AutoProvisionedAssets::ProjectId)
+                    .col(AutoProvisionedAssets::AssetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AutoProvisionedAssets::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum AutoProvisionedAssets {
+    Table,
+    Id,
+    ProjectId,
+    AssetId,
+    OrganizationId,
+    CreatedBy,
+    CreatedAt,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectSettings::ProjectId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectSettings::EnabledChains)
+                            .array(ColumnType::String(None)),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectSettings::FeeLevel)
+                            .string()
+                            .not_null()
+                            .default("high"),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectSettings::AutoFuel)
+                            .string()
+                            .not_null()
+                            .default("gas_station"),
+                    )
+                    .col(ColumnDef::new(ProjectSettings::SigningVaultId).string())
+                    .col(ColumnDef::new(ProjectSettings::PolygonContractWalletId).string())
+                    .col(ColumnDef::new(ProjectSettings::UpdatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(ProjectSettings::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default("now()"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProjectSettings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ProjectSettings {
+    Table,
+    ProjectId,
+    EnabledChains,
+    FeeLevel,
+    AutoFuel,
+    SigningVaultId,
+    PolygonContractWalletId,
+    UpdatedBy,
+    UpdatedAt,
+}
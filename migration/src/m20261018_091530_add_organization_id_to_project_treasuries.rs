@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, sea_orm::Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectTreasuries::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ProjectTreasuries::OrganizationId).uuid(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Backfill the owners of existing projects from the records that name both the
+        // project and its organization.
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"UPDATE project_treasuries
+            SET organization_id = owners.organization_id
+            FROM (
+                SELECT DISTINCT ON (project_id) project_id, organization_id
+                FROM (
+                    SELECT project_id, organization_id, created_at FROM wallet_creations
+                    UNION ALL
+                    SELECT project_id, organization_id, created_at FROM auto_provisioned_assets
+                ) AS records
+                ORDER BY project_id, created_at DESC
+            ) AS owners
+            WHERE project_treasuries.project_id = owners.project_id
+                AND project_treasuries.organization_id IS NULL;"#
+                .to_string(),
+        ))
+        .await?;
+
+        // Projects with no such record keep a NULL owner until their `ProjectCreated` event is
+        // consumed again or their organization first looks them up. The column is only
+        // tightened once every owner is known.
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"DO $$
+            BEGIN
+                IF NOT EXISTS (SELECT 1 FROM project_treasuries WHERE organization_id IS NULL) THEN
+                    ALTER TABLE project_treasuries ALTER COLUMN organization_id SET NOT NULL;
+                END IF;
+            END $$;"#
+                .to_string(),
+        ))
+        .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("project_treasuries_organization_id_idx")
+                    .table(ProjectTreasuries::Table)
+                    .col(ProjectTreasuries::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectTreasuries::Table)
+                    .drop_column(ProjectTreasuries::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ProjectTreasuries {
+    Table,
+    OrganizationId,
+}